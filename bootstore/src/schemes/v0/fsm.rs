@@ -13,8 +13,9 @@
 use super::request_manager::ShareAcks;
 use super::{
     create_pkgs, Envelope, FsmConfig, LearnedSharePkg, Msg, MsgError, RackUuid,
-    Request, RequestManager, RequestType, Response, ResponseType, Share,
-    SharePkg, Shares, TrackableRequest,
+    Request, RequestKind, RequestManager, RequestSnapshot, RequestType,
+    Response, ResponseType, Share, SharePkg, Shares, TrackableRequest,
+    MAX_INITIAL_MEMBERS, MIN_INITIAL_MEMBERS,
 };
 use crate::schemes::v0::share_pkg::SharePkgCommon;
 use crate::trust_quorum::{RackSecret, TrustQuorumError};
@@ -58,10 +59,27 @@ pub enum State {
         /// trust quourum protocol schemes.
         #[serde_as(as = "Vec<(_, _)>")]
         distributed_shares: BTreeMap<Baseboard, ShareIdx>,
+
+        /// Members of the initial group that have been permanently removed
+        /// from the trust quorum via [`Fsm::remove_member`]
+        #[serde(default)]
+        removed_members: BTreeSet<Baseboard>,
+
+        /// Members added to the trust quorum after rack initialization via
+        /// [`Fsm::add_member`]
+        #[serde(default)]
+        added_members: BTreeSet<Baseboard>,
     },
     Learning,
     Learned {
         pkg: LearnedSharePkg,
+
+        /// The peer that handed us our share
+        ///
+        /// This is only for auditing and debugging. It's `None` for peers
+        /// that learned their share before this was recorded.
+        #[serde(default)]
+        learned_from: Option<Baseboard>,
     },
 }
 
@@ -76,6 +94,33 @@ impl State {
     }
 }
 
+/// The result of a call to [`Fsm::tick`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TickOutput {
+    /// Requests that expired during this tick, mapped to their request id
+    pub expired: BTreeMap<Uuid, RequestKind>,
+
+    /// Errors for expired api requests, mapped to their request id
+    pub errors: BTreeMap<Uuid, ApiError>,
+}
+
+/// A snapshot of an [`Fsm`] for debugging
+///
+/// Like the `Debug` output of [`Share`], this never includes secret material:
+/// shares, share packages, and the rack secret are all omitted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FsmDebugSnapshot {
+    pub id: Baseboard,
+    pub state: &'static str,
+    pub rack_uuid: Option<Uuid>,
+    pub learned_from: Option<Baseboard>,
+    /// Number of learners an initial member has handed out a share to
+    pub distributed_shares: usize,
+    pub connected_peers: BTreeSet<Baseboard>,
+    pub requests: Vec<RequestSnapshot>,
+    pub rack_init_error: Option<String>,
+}
+
 /// A response to an Fsm API request
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ApiOutput {
@@ -99,6 +144,22 @@ pub enum ApiOutput {
     ///
     /// The caller *must* persist `Fsm::State`
     LearningCompleted,
+
+    /// A member was permanently removed from the trust quorum
+    ///
+    /// The caller *must* persist `Fsm::State`
+    MemberRemoved { request_id: Uuid, member: Baseboard },
+
+    /// A share has been set aside for a member that is being added, and its
+    /// package is about to be sent
+    ///
+    /// The caller *must* persist `Fsm::State`
+    ShareDistributedToMember { request_id: Uuid, member: Baseboard },
+
+    /// A member was added to the trust quorum and acknowledged its package
+    ///
+    /// The caller *must* persist `Fsm::State`
+    MemberAdded { request_id: Uuid, member: Baseboard },
 }
 
 /// An error returned from an Fsm API request
@@ -119,9 +180,51 @@ pub enum ApiError {
     #[error("rack init falied: trust quorum error: {0:?}")]
     RackInitFailed(TrustQuorumError),
 
+    #[error("initial membership too small: {size} members (minimum is {min})")]
+    InitialMembershipTooSmall { size: usize, min: usize },
+
+    #[error("initial membership too large: {size} members (maximum is {max})")]
+    InitialMembershipTooLarge { size: usize, max: usize },
+
     #[error("rack secret load timeout")]
     RackSecretLoadTimeout,
 
+    #[error("only initial members may remove members")]
+    NotAnInitialMember,
+
+    #[error("{member} is not a current member of the trust quorum")]
+    NotAMember { member: Baseboard },
+
+    #[error("a peer cannot remove itself from the trust quorum")]
+    CannotRemoveSelf,
+
+    #[error("a member removal is already in progress")]
+    RemoveMemberInProgress,
+
+    #[error(
+        "removal would leave {remaining} members, fewer than the threshold \
+         of {threshold}"
+    )]
+    MembershipBelowThreshold { remaining: usize, threshold: usize },
+
+    #[error("timed out removing member {member}")]
+    RemoveMemberTimeout { member: Baseboard },
+
+    #[error("{member} is already a member of the trust quorum")]
+    AlreadyAMember { member: Baseboard },
+
+    #[error("a member addition is already in progress")]
+    AddMemberInProgress,
+
+    #[error("timed out adding member {member}")]
+    AddMemberTimeout { member: Baseboard },
+
+    #[error("no extra shares left to hand out")]
+    CannotSpareAShare,
+
+    #[error("failed to create package for new member: {0:?}")]
+    AddMemberFailed(TrustQuorumError),
+
     #[error("share from {from} has invalid sha3_256 digest")]
     InvalidShare { from: Baseboard },
 
@@ -217,6 +320,40 @@ impl Fsm {
         &self.state
     }
 
+    /// If this peer learned its share, return the peer it learned it from
+    pub fn learned_from(&self) -> Option<&Baseboard> {
+        match &self.state {
+            State::Learned { learned_from, .. } => learned_from.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Return a snapshot of this peer's state for debugging
+    ///
+    /// Request expiries are reported relative to `now`.
+    pub fn dump_state(&self, now: Instant) -> FsmDebugSnapshot {
+        let (rack_uuid, distributed_shares) = match &self.state {
+            State::Uninitialized | State::Learning => (None, 0),
+            State::InitialMember { pkg, distributed_shares, .. } => {
+                (Some(pkg.common.rack_uuid), distributed_shares.len())
+            }
+            State::Learned { pkg, .. } => (Some(pkg.common.rack_uuid), 0),
+        };
+        FsmDebugSnapshot {
+            id: self.id.clone(),
+            state: self.state.name(),
+            rack_uuid,
+            learned_from: self.learned_from().cloned(),
+            distributed_shares,
+            connected_peers: self.connected_peers.clone(),
+            requests: self.request_manager.snapshot(now),
+            rack_init_error: self
+                .rack_init_error
+                .as_ref()
+                .map(|(_, err)| err.to_string()),
+        }
+    }
+
     pub fn rack_init_failed(&self) -> bool {
         self.rack_init_error.is_some()
     }
@@ -243,6 +380,19 @@ impl Fsm {
         let State::Uninitialized = self.state else {
             return Err(ApiError::AlreadyInitialized);
         };
+        let size = initial_membership.len();
+        if size < MIN_INITIAL_MEMBERS {
+            return Err(ApiError::InitialMembershipTooSmall {
+                size,
+                min: MIN_INITIAL_MEMBERS,
+            });
+        }
+        if size > MAX_INITIAL_MEMBERS {
+            return Err(ApiError::InitialMembershipTooLarge {
+                size,
+                max: MAX_INITIAL_MEMBERS,
+            });
+        }
         let pkgs = create_pkgs(rack_uuid.0, initial_membership.clone())
             .map_err(ApiError::RackInitFailed)?;
         let mut iter = pkgs.expose_secret().into_iter();
@@ -252,6 +402,8 @@ impl Fsm {
         self.state = State::InitialMember {
             pkg: our_pkg,
             distributed_shares: BTreeMap::new(),
+            removed_members: BTreeSet::new(),
+            added_members: BTreeSet::new(),
         };
 
         let packages: BTreeMap<Baseboard, SharePkg> = initial_membership
@@ -270,11 +422,55 @@ impl Fsm {
         Ok(())
     }
 
+    /// Return this peer to `State::Uninitialized` so that it can be
+    /// initialized again, e.g. after rack initialization fails
+    ///
+    /// All outstanding requests, unsent messages, and any rack init error are
+    /// dropped. Any shares held in the prior state are zeroized as they are
+    /// dropped.
+    ///
+    /// The caller *must* persist `Fsm::State` after calling `reset`.
+    pub fn reset(&mut self) {
+        self.state = State::Uninitialized;
+        self.request_manager.clear();
+        self.responses.clear();
+        self.rack_init_error = None;
+    }
+
     /// Are we still waiting for `InitAck` responses from peers?
     pub fn is_rack_initializing(&self) -> bool {
         self.request_manager.has_init_rack_req()
     }
 
+    /// Return how many peers have acknowledged rack initialization, out of
+    /// how many are expected to, as `(received, expected)`
+    ///
+    /// Return `None` if rack initialization is not in progress.
+    pub fn rack_init_progress(&self) -> Option<(usize, usize)> {
+        self.request_manager.init_rack_progress()
+    }
+
+    /// Is a call to [`Fsm::load_rack_secret`] still waiting for shares?
+    pub fn pending_rack_secret(&self) -> bool {
+        self.request_manager.has_load_rack_secret_req()
+    }
+
+    /// Return the current members of the trust quorum
+    ///
+    /// Only initial members track membership, so this returns `None` for peers
+    /// in any other state.
+    pub fn members(&self) -> Option<BTreeSet<Baseboard>> {
+        match &self.state {
+            State::InitialMember {
+                pkg,
+                removed_members,
+                added_members,
+                ..
+            } => Some(current_members(pkg, removed_members, added_members)),
+            _ => None,
+        }
+    }
+
     /// Initialize a node added after rack initialization
     ///
     /// Persistence is required after a successful call to `init_learner`
@@ -302,7 +498,7 @@ impl Fsm {
             State::Uninitialized => return Err(ApiError::NotInitialized),
             State::Learning { .. } => return Err(ApiError::StillLearning),
             State::InitialMember { pkg, .. } => &pkg.common,
-            State::Learned { pkg } => &pkg.common,
+            State::Learned { pkg, .. } => &pkg.common,
         };
         let request_id = self.request_manager.new_load_rack_secret_req(
             now,
@@ -314,22 +510,126 @@ impl Fsm {
         Ok(request_id)
     }
 
-    /// Periodic tick to check for request expiration and trigger learner
-    /// attempt peer rotation.
+    /// Begin permanently removing `member` from the trust quorum (e.g., because
+    /// its sled was decommissioned)
+    ///
+    /// Only initial members may remove members, and enough members must
+    /// remain to reconstruct the rack secret. A threshold of shares is
+    /// gathered before the removal is recorded, so that it requires the
+    /// participation of a quorum. Once that completes, `handle_msg` returns
+    /// `ApiOutput::MemberRemoved` and the caller *must* persist `Fsm::State`.
     ///
-    /// Return any expired request errors mapped to their request id
-    pub fn tick(
+    /// The removed member's share remains valid until shares are
+    /// redistributed under a new epoch, which this version of the protocol
+    /// does not yet do. Removal is currently limited to bookkeeping.
+    pub fn remove_member(
         &mut self,
         now: Instant,
-    ) -> Result<(), BTreeMap<Uuid, ApiError>> {
+        member: Baseboard,
+    ) -> Result<Uuid, ApiError> {
+        self.check_init_err()?;
+        let (pkg, members) = match &self.state {
+            State::Uninitialized => return Err(ApiError::NotInitialized),
+            State::Learning => return Err(ApiError::StillLearning),
+            State::Learned { .. } => return Err(ApiError::NotAnInitialMember),
+            State::InitialMember {
+                pkg,
+                removed_members,
+                added_members,
+                ..
+            } => (pkg, current_members(pkg, removed_members, added_members)),
+        };
+        if member == self.id {
+            return Err(ApiError::CannotRemoveSelf);
+        }
+        if !members.contains(&member) {
+            return Err(ApiError::NotAMember { member });
+        }
+        if self.request_manager.has_remove_member_req() {
+            return Err(ApiError::RemoveMemberInProgress);
+        }
+        let remaining = members.len() - 1;
+        let threshold = usize::from(pkg.common.threshold);
+        if remaining < threshold {
+            return Err(ApiError::MembershipBelowThreshold {
+                remaining,
+                threshold,
+            });
+        }
+
+        let request_id = self.request_manager.new_remove_member_req(
+            now,
+            pkg.common.rack_uuid.into(),
+            pkg.common.threshold,
+            member,
+            &self.connected_peers,
+        );
+        Ok(request_id)
+    }
+
+    /// Begin adding `member` to the trust quorum as a full member after rack
+    /// initialization (e.g., to promote a learner or re-provision a replaced
+    /// sled)
+    ///
+    /// Only initial members may add members. A threshold of shares is
+    /// gathered so that one of this peer's extra shares can be decrypted and
+    /// distributed to `member` in a freshly generated [`SharePkg`] via a
+    /// `RequestType::AddMember`. Once `member` acknowledges the package,
+    /// `handle_msg` returns `ApiOutput::MemberAdded` and the caller *must*
+    /// persist `Fsm::State`.
+    ///
+    /// Adding a member that was already added is allowed, and hands it the
+    /// same share, so that an addition that timed out can be retried.
+    pub fn add_member(
+        &mut self,
+        now: Instant,
+        member: Baseboard,
+    ) -> Result<Uuid, ApiError> {
+        self.check_init_err()?;
+        let (pkg, removed_members) = match &self.state {
+            State::Uninitialized => return Err(ApiError::NotInitialized),
+            State::Learning => return Err(ApiError::StillLearning),
+            State::Learned { .. } => return Err(ApiError::NotAnInitialMember),
+            State::InitialMember { pkg, removed_members, .. } => {
+                (pkg, removed_members)
+            }
+        };
+        if member == self.id
+            || (pkg.initial_membership.contains(&member)
+                && !removed_members.contains(&member))
+        {
+            return Err(ApiError::AlreadyAMember { member });
+        }
+        if self.request_manager.has_add_member_req() {
+            return Err(ApiError::AddMemberInProgress);
+        }
+
+        let request_id = self.request_manager.new_add_member_req(
+            now,
+            pkg.common.rack_uuid.into(),
+            pkg.common.threshold,
+            member,
+            &self.connected_peers,
+        );
+        Ok(request_id)
+    }
+
+    /// Periodic tick to check for request expiration and trigger learner
+    /// attempt peer rotation.
+    ///
+    /// Return the requests that expired during this tick, along with any
+    /// resulting api request errors.
+    pub fn tick(&mut self, now: Instant) -> TickOutput {
+        let mut output = TickOutput::default();
         if let State::Uninitialized = &self.state {
-            return Ok(());
+            return output;
         }
         if let Some((request_id, err)) = &self.rack_init_error {
-            return Err(BTreeMap::from([(*request_id, err.clone())]));
+            output.errors.insert(*request_id, err.clone());
+            return output;
         }
-        let mut errors = BTreeMap::new();
         for (req_id, req) in self.request_manager.expired(now) {
+            output.expired.insert(req_id, req.kind());
             match req {
                 TrackableRequest::InitRack { acks, .. } => {
                     let unacked_peers = acks
@@ -338,16 +638,30 @@ impl Fsm {
                         .cloned()
                         .collect();
                     let err = ApiError::RackInitTimeout { unacked_peers };
-                    errors.insert(req_id, err.clone());
+                    output.errors.insert(req_id, err.clone());
                     self.rack_init_error = Some((req_id, err));
                 }
                 TrackableRequest::LoadRackSecret { .. } => {
-                    errors.insert(req_id, ApiError::RackSecretLoadTimeout);
+                    output
+                        .errors
+                        .insert(req_id, ApiError::RackSecretLoadTimeout);
                 }
                 TrackableRequest::LearnReceived { .. } => {
                     // Nothing to do here, as these are requests from messages
                     // and not api requests
                 }
+                TrackableRequest::RemoveMember { member, .. } => {
+                    output.errors.insert(
+                        req_id,
+                        ApiError::RemoveMemberTimeout { member },
+                    );
+                }
+                TrackableRequest::AddMember { member, .. }
+                | TrackableRequest::AddMemberSent { member, .. } => {
+                    output
+                        .errors
+                        .insert(req_id, ApiError::AddMemberTimeout { member });
+                }
                 TrackableRequest::LearnSent { to } => {
                     if let State::Learning = &self.state {
                         // Issue the next request
@@ -360,11 +674,8 @@ impl Fsm {
                 }
             }
         }
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
-        }
+        self.request_manager.retry(now, &self.connected_peers);
+        output
     }
 
     /// A peer has been connected.
@@ -409,7 +720,7 @@ impl Fsm {
         self.check_init_err()?;
         match msg {
             Msg::Req(req) => self.handle_request(now, from, req),
-            Msg::Rsp(rsp) => self.handle_response(from, rsp),
+            Msg::Rsp(rsp) => self.handle_response(now, from, rsp),
         }
     }
 
@@ -430,6 +741,9 @@ impl Fsm {
                 self.on_learn(now, from, req.id);
                 Ok(None)
             }
+            RequestType::AddMember(pkg) => {
+                self.on_add_member(from, req.id, pkg)
+            }
         }
     }
 
@@ -446,6 +760,8 @@ impl Fsm {
                 self.state = State::InitialMember {
                     pkg: new_pkg,
                     distributed_shares: BTreeMap::new(),
+                    removed_members: BTreeSet::new(),
+                    added_members: BTreeSet::new(),
                 };
                 self.push_response(from, request_id, ResponseType::InitAck);
                 Ok(Some(ApiOutput::PeerInitialized))
@@ -487,7 +803,7 @@ impl Fsm {
         let response = match &self.state {
             State::Uninitialized => MsgError::NotInitialized.into(),
             State::Learning => MsgError::StillLearning.into(),
-            State::Learned { pkg: LearnedSharePkg { common } }
+            State::Learned { pkg: LearnedSharePkg { common }, .. }
             | State::InitialMember { pkg: SharePkg { common, .. }, .. } => {
                 if rack_uuid.0 != common.rack_uuid {
                     MsgError::RackUuidMismatch {
@@ -529,20 +845,93 @@ impl Fsm {
         }
     }
 
+    // Handle a `RequestType::AddMember` from a peer
+    fn on_add_member(
+        &mut self,
+        from: Baseboard,
+        request_id: Uuid,
+        new_pkg: SharePkg,
+    ) -> Result<Option<ApiOutput>, ApiError> {
+        match &self.state {
+            State::Learned { pkg, .. }
+                if pkg.common.rack_uuid != new_pkg.common.rack_uuid =>
+            {
+                // A learned peer must never switch racks
+                self.push_response(
+                    from,
+                    request_id,
+                    MsgError::RackUuidMismatch {
+                        expected: pkg.common.rack_uuid.into(),
+                        got: new_pkg.common.rack_uuid.into(),
+                    }
+                    .into(),
+                );
+                Ok(None)
+            }
+            State::Uninitialized | State::Learned { .. } => {
+                // Become a full member and ack. A learned share is replaced
+                // by the one in the package.
+                self.state = State::InitialMember {
+                    pkg: new_pkg,
+                    distributed_shares: BTreeMap::new(),
+                    removed_members: BTreeSet::new(),
+                    added_members: BTreeSet::new(),
+                };
+                self.push_response(
+                    from,
+                    request_id,
+                    ResponseType::AddMemberAck,
+                );
+                Ok(Some(ApiOutput::PeerInitialized))
+            }
+            State::InitialMember { pkg, .. } => {
+                // Return success on idempotence. A retried addition hands out
+                // the same share, but the package is encrypted with a new
+                // nonce.
+                if pkg.common == new_pkg.common {
+                    self.push_response(
+                        from,
+                        request_id,
+                        ResponseType::AddMemberAck,
+                    );
+                } else {
+                    self.push_response(
+                        from,
+                        request_id,
+                        MsgError::AlreadyInitialized.into(),
+                    );
+                }
+                Ok(None)
+            }
+            State::Learning => {
+                self.push_response(
+                    from,
+                    request_id,
+                    MsgError::StillLearning.into(),
+                );
+                Ok(None)
+            }
+        }
+    }
+
     // Handle a `Response` from a peer
     fn handle_response(
         &mut self,
+        now: Instant,
         from: Baseboard,
         rsp: Response,
     ) -> Result<Option<ApiOutput>, ApiError> {
         match rsp.type_ {
             ResponseType::InitAck => self.on_init_ack(from, rsp.request_id),
             ResponseType::Share(share) => {
-                self.on_share(from, rsp.request_id, share)
+                self.on_share(now, from, rsp.request_id, share)
             }
             ResponseType::LearnPkg(pkg) => {
                 self.on_learn_pkg(from, rsp.request_id, pkg)
             }
+            ResponseType::AddMemberAck => {
+                self.on_add_member_ack(from, rsp.request_id)
+            }
             ResponseType::Error(error) => {
                 Err(ApiError::ErrorResponseReceived {
                     from,
@@ -572,6 +961,30 @@ impl Fsm {
         }
     }
 
+    // Handle a `ResponseType::AddMemberAck` from a peer
+    fn on_add_member_ack(
+        &mut self,
+        from: Baseboard,
+        request_id: Uuid,
+    ) -> Result<Option<ApiOutput>, ApiError> {
+        let member = self.request_manager.on_add_member_ack(&from, request_id);
+        if let (
+            Some(member),
+            State::InitialMember { removed_members, added_members, .. },
+        ) = (member, &mut self.state)
+        {
+            removed_members.remove(&member);
+            added_members.insert(member.clone());
+            return Ok(Some(ApiOutput::MemberAdded { request_id, member }));
+        }
+        Err(ApiError::UnexpectedResponse {
+            from,
+            state: self.state.name(),
+            request_id,
+            msg: "AddMemberAck",
+        })
+    }
+
     // Handle a `ResponseType::Pkg` from a peer
     fn on_learn_pkg(
         &mut self,
@@ -583,7 +996,7 @@ impl Fsm {
             // This pkg matched our outstanding request. Let's transition from
             // `State::Learning` to `State::Learned`.
             assert_eq!(self.state, State::Learning);
-            self.state = State::Learned { pkg };
+            self.state = State::Learned { pkg, learned_from: Some(from) };
             Ok(Some(ApiOutput::LearningCompleted))
         } else if self.state == State::Learning {
             // This is a stale response. We could choose to accept it, but
@@ -604,6 +1017,7 @@ impl Fsm {
     // Handle a `ResponseType::Share` from a peer
     fn on_share(
         &mut self,
+        now: Instant,
         from: Baseboard,
         request_id: Uuid,
         share: Share,
@@ -618,7 +1032,12 @@ impl Fsm {
                     msg: "Share",
                 });
             }
-            State::InitialMember { pkg, distributed_shares } => {
+            State::InitialMember {
+                pkg,
+                distributed_shares,
+                removed_members,
+                ..
+            } => {
                 validate_share(&from, &share, &pkg.common.share_digests)?;
                 match self.request_manager.on_share(from, request_id, share) {
                     Some(TrackableRequest::LoadRackSecret { acks, .. }) => {
@@ -642,6 +1061,52 @@ impl Fsm {
                             &mut self.responses,
                         )
                     }
+                    Some(TrackableRequest::RemoveMember {
+                        member,
+                        acks,
+                        ..
+                    }) => {
+                        // Reconstructing the rack secret confirms that a
+                        // quorum of members took part in the removal.
+                        let _rack_secret =
+                            combine_shares(&pkg.common.share, acks)?;
+                        removed_members.insert(member.clone());
+                        Ok(Some(ApiOutput::MemberRemoved {
+                            request_id,
+                            member,
+                        }))
+                    }
+                    Some(TrackableRequest::AddMember {
+                        member, acks, ..
+                    }) => {
+                        let rack_secret =
+                            combine_shares(&pkg.common.share, acks)?;
+                        let (new_pkg, newly_distributed) = create_member_pkg(
+                            &member,
+                            pkg,
+                            distributed_shares,
+                            &rack_secret,
+                        )?;
+                        self.request_manager.new_add_member_sent_req(
+                            request_id,
+                            now,
+                            member.clone(),
+                            new_pkg,
+                            &self.connected_peers,
+                        );
+                        if newly_distributed {
+                            // The share index must be persisted before the
+                            // package goes out, so that it is never handed to
+                            // another peer if this request fails.
+                            Ok(Some(ApiOutput::ShareDistributedToMember {
+                                request_id,
+                                member,
+                            }))
+                        } else {
+                            // No state was updated, so no need to persist
+                            Ok(None)
+                        }
+                    }
                     // Only LoadRackSecret and LearnReceived track shares so we
                     // cannot get another variant back.
                     //
@@ -655,7 +1120,7 @@ impl Fsm {
                     _ => Ok(None),
                 }
             }
-            State::Learned { pkg } => {
+            State::Learned { pkg, .. } => {
                 validate_share(&from, &share, &pkg.common.share_digests)?;
                 match self.request_manager.on_share(from, request_id, share) {
                     Some(TrackableRequest::LoadRackSecret { acks, .. }) => {
@@ -668,6 +1133,18 @@ impl Fsm {
                             accept 'Learn' requests"
                         )
                     }
+                    Some(TrackableRequest::RemoveMember { .. }) => {
+                        panic!(
+                            "Invariant violation: Learned members must not \
+                             remove members"
+                        )
+                    }
+                    Some(TrackableRequest::AddMember { .. }) => {
+                        panic!(
+                            "Invariant violation: Learned members must not \
+                             add members"
+                        )
+                    }
                     // Only LoadRackSecret and LearnReceived track shares so we
                     // cannot get another variant back.
                     //
@@ -717,14 +1194,8 @@ fn decrypt_and_send_share_response(
         // No state was updated, so no need to persist
         Ok(None)
     } else {
-        // We need to pick a share to hand out and persist that fact. We find
-        // the highest currently used index and add 1 or we select index 0.
-        let idx = distributed_shares
-            .values()
-            .max()
-            .cloned()
-            .map(|idx| idx.0 + 1)
-            .unwrap_or(0);
+        // We need to pick a share to hand out and persist that fact.
+        let idx = next_share_idx(distributed_shares);
 
         match shares.expose_secret().get(idx) {
             Some(share) => {
@@ -749,6 +1220,52 @@ fn decrypt_and_send_share_response(
     }
 }
 
+// Find the highest currently used share index and add 1 or select index 0.
+fn next_share_idx(distributed_shares: &BTreeMap<Baseboard, ShareIdx>) -> usize {
+    distributed_shares.values().max().cloned().map(|idx| idx.0 + 1).unwrap_or(0)
+}
+
+// Decrypt our extra shares and create a package for a new member around one
+// of them. A member that was already handed a share gets the same one again.
+//
+// Return the package and whether a share was newly assigned to `member`, in
+// which case `Fsm::State` must be persisted before the package is sent.
+fn create_member_pkg(
+    member: &Baseboard,
+    pkg: &SharePkg,
+    distributed_shares: &mut BTreeMap<Baseboard, ShareIdx>,
+    rack_secret: &RackSecret,
+) -> Result<(SharePkg, bool), ApiError> {
+    let shares = pkg
+        .decrypt_shares(rack_secret)
+        .map_err(|_| ApiError::FailedToDecryptExtraShares)?;
+    let existing = distributed_shares.get(member).map(|idx| idx.0);
+    let idx = existing.unwrap_or_else(|| next_share_idx(distributed_shares));
+    let share = shares
+        .expose_secret()
+        .get(idx)
+        .cloned()
+        .ok_or(ApiError::CannotSpareAShare)?;
+    let new_pkg = pkg
+        .new_member_pkg(rack_secret, share)
+        .map_err(ApiError::AddMemberFailed)?;
+    distributed_shares.insert(member.clone(), ShareIdx(idx));
+    Ok((new_pkg, existing.is_none()))
+}
+
+// Return all current members of the trust quorum known to an initial member
+fn current_members(
+    pkg: &SharePkg,
+    removed_members: &BTreeSet<Baseboard>,
+    added_members: &BTreeSet<Baseboard>,
+) -> BTreeSet<Baseboard> {
+    pkg.initial_membership
+        .union(added_members)
+        .filter(|member| !removed_members.contains(*member))
+        .cloned()
+        .collect()
+}
+
 // We have a share to hand back to a learner. Enqueue it in a response envelope.
 fn queue_pkg_response(
     from: Baseboard,
@@ -813,3 +1330,653 @@ fn validate_share(
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use std::time::Duration;
+
+    fn config() -> FsmConfig {
+        FsmConfig {
+            learn_timeout: Duration::from_secs(5),
+            rack_init_timeout: Duration::from_secs(5),
+            rack_secret_request_timeout: Duration::from_secs(5),
+            retry_interval: Duration::from_secs(1),
+        }
+    }
+
+    fn members(n: usize) -> BTreeSet<Baseboard> {
+        (0..n)
+            .map(|i| Baseboard::new_pc(format!("sled-{i}"), "0".to_string()))
+            .collect()
+    }
+
+    fn init_rack_with_members(n: usize) -> (Fsm, Result<(), ApiError>) {
+        let membership = members(n);
+        let id = membership
+            .first()
+            .cloned()
+            .unwrap_or_else(|| Baseboard::new_pc("rss".into(), "0".into()));
+        let mut fsm = Fsm::new_uninitialized(id, config());
+        let res =
+            fsm.init_rack(Instant::now(), RackUuid(Uuid::new_v4()), membership);
+        (fsm, res)
+    }
+
+    #[test]
+    fn init_rack_rejects_empty_membership() {
+        let (fsm, res) = init_rack_with_members(0);
+        assert_eq!(
+            res,
+            Err(ApiError::InitialMembershipTooSmall {
+                size: 0,
+                min: MIN_INITIAL_MEMBERS
+            })
+        );
+        assert_eq!(fsm.state(), &State::Uninitialized);
+    }
+
+    #[test]
+    fn init_rack_rejects_single_member() {
+        let (fsm, res) = init_rack_with_members(1);
+        assert_eq!(
+            res,
+            Err(ApiError::InitialMembershipTooSmall {
+                size: 1,
+                min: MIN_INITIAL_MEMBERS
+            })
+        );
+        assert_eq!(fsm.state(), &State::Uninitialized);
+    }
+
+    #[test]
+    fn init_rack_rejects_oversized_membership() {
+        let (fsm, res) = init_rack_with_members(MAX_INITIAL_MEMBERS + 1);
+        assert_eq!(
+            res,
+            Err(ApiError::InitialMembershipTooLarge {
+                size: MAX_INITIAL_MEMBERS + 1,
+                max: MAX_INITIAL_MEMBERS
+            })
+        );
+        assert_eq!(fsm.state(), &State::Uninitialized);
+    }
+
+    #[test]
+    fn init_rack_accepts_minimum_membership() {
+        let (fsm, res) = init_rack_with_members(MIN_INITIAL_MEMBERS);
+        assert_eq!(res, Ok(()));
+        assert_eq!(fsm.state().name(), "initial_member");
+    }
+
+    #[test]
+    fn reset_allows_rack_reinit_after_failure() {
+        let membership = members(3);
+        let id = membership.first().cloned().unwrap();
+        let mut fsm = Fsm::new_uninitialized(id, config());
+        let start = Instant::now();
+        fsm.init_rack(start, RackUuid(Uuid::new_v4()), membership.clone())
+            .unwrap();
+
+        // No peers ever ack, so rack init fails
+        let output = fsm.tick(start + Duration::from_secs(6));
+        assert!(fsm.rack_init_failed());
+        assert_matches!(
+            output.errors.values().collect::<Vec<_>>().as_slice(),
+            [ApiError::RackInitTimeout { .. }]
+        );
+        assert_matches!(
+            fsm.load_rack_secret(start),
+            Err(ApiError::RackInitTimeout { .. })
+        );
+
+        fsm.reset();
+        assert_eq!(fsm.state(), &State::Uninitialized);
+        assert!(!fsm.rack_init_failed());
+        assert!(fsm.dump_state(start).requests.is_empty());
+        assert!(fsm.drain_envelopes().next().is_none());
+
+        let now = start + Duration::from_secs(7);
+        assert_eq!(
+            fsm.init_rack(now, RackUuid(Uuid::new_v4()), membership),
+            Ok(())
+        );
+        assert!(fsm.is_rack_initializing());
+        assert_eq!(
+            fsm.tick(now + Duration::from_secs(1)),
+            TickOutput::default()
+        );
+    }
+
+    #[test]
+    fn rack_init_progress_reports_acks() {
+        let membership = members(3);
+        let mut iter = membership.iter().cloned();
+        let id = iter.next().unwrap();
+        let peer = iter.next().unwrap();
+        let mut fsm = Fsm::new_uninitialized(id, config());
+        let now = Instant::now();
+        assert_eq!(fsm.rack_init_progress(), None);
+        assert_eq!(fsm.members(), None);
+
+        fsm.on_connected(now, peer.clone()).unwrap();
+        fsm.init_rack(now, RackUuid(Uuid::new_v4()), membership.clone())
+            .unwrap();
+        assert_eq!(fsm.rack_init_progress(), Some((0, 2)));
+        assert_eq!(fsm.members(), Some(membership));
+
+        let envelopes: Vec<_> = fsm.drain_envelopes().collect();
+        let [Envelope {
+            to,
+            msg:
+                Msg::Req(Request { id: request_id, type_: RequestType::Init(_) }),
+        }] = envelopes.as_slice()
+        else {
+            panic!("expected a single init request: {:?}", envelopes);
+        };
+        assert_eq!(to, &peer);
+
+        let rsp =
+            Response { request_id: *request_id, type_: ResponseType::InitAck };
+        fsm.handle_msg(now, peer, rsp.into()).unwrap();
+        assert_eq!(fsm.rack_init_progress(), Some((1, 2)));
+
+        assert!(!fsm.pending_rack_secret());
+        fsm.load_rack_secret(now).unwrap();
+        assert!(fsm.pending_rack_secret());
+    }
+
+    #[test]
+    fn learned_from_reports_source_peer() {
+        let learner = Baseboard::new_pc("learner".to_string(), "0".to_string());
+        let peer = Baseboard::new_pc("sled-0".to_string(), "0".to_string());
+        let mut fsm = Fsm::new_uninitialized(learner, config());
+        let now = Instant::now();
+        assert_eq!(fsm.learned_from(), None);
+
+        fsm.init_learner(now).unwrap();
+        fsm.on_connected(now, peer.clone()).unwrap();
+        let envelopes: Vec<_> = fsm.drain_envelopes().collect();
+        assert_eq!(envelopes.len(), 1);
+        let Envelope {
+            to,
+            msg: Msg::Req(Request { id: request_id, type_: RequestType::Learn }),
+        } = &envelopes[0]
+        else {
+            panic!("expected a learn request: {:?}", envelopes[0]);
+        };
+        assert_eq!(to, &peer);
+        assert_eq!(fsm.learned_from(), None);
+
+        let pkgs =
+            create_pkgs(Uuid::new_v4(), members(MIN_INITIAL_MEMBERS)).unwrap();
+        let pkg =
+            LearnedSharePkg { common: pkgs.expose_secret()[0].common.clone() };
+        let rsp = Response {
+            request_id: *request_id,
+            type_: ResponseType::LearnPkg(pkg),
+        };
+        let output = fsm.handle_msg(now, peer.clone(), rsp.into());
+        assert_eq!(output, Ok(Some(ApiOutput::LearningCompleted)));
+        assert_eq!(fsm.learned_from(), Some(&peer));
+    }
+
+    #[test]
+    fn add_member_rejects_pkg_for_another_rack_when_learned() {
+        let learner = Baseboard::new_pc("learner".to_string(), "0".to_string());
+        let peer = Baseboard::new_pc("sled-0".to_string(), "0".to_string());
+        let mut fsm = Fsm::new_uninitialized(learner, config());
+        let now = Instant::now();
+        fsm.init_learner(now).unwrap();
+        fsm.on_connected(now, peer.clone()).unwrap();
+        let request_id = fsm.drain_envelopes().next().unwrap().msg.request_id();
+
+        let pkgs =
+            create_pkgs(Uuid::new_v4(), members(MIN_INITIAL_MEMBERS)).unwrap();
+        let learned =
+            LearnedSharePkg { common: pkgs.expose_secret()[0].common.clone() };
+        let rsp =
+            Response { request_id, type_: ResponseType::LearnPkg(learned) };
+        fsm.handle_msg(now, peer.clone(), rsp.into()).unwrap();
+        let learned_state = fsm.state().clone();
+
+        // A package for a different rack is rejected and the learned share
+        // is kept
+        let other_rack_pkgs =
+            create_pkgs(Uuid::new_v4(), members(MIN_INITIAL_MEMBERS)).unwrap();
+        let other_rack_pkg = other_rack_pkgs.expose_secret()[0].clone();
+        let id = Uuid::new_v4();
+        let req = Request {
+            id,
+            type_: RequestType::AddMember(other_rack_pkg.clone()),
+        };
+        assert_eq!(fsm.handle_msg(now, peer.clone(), req.into()), Ok(None));
+        assert_eq!(fsm.state(), &learned_state);
+        let envelopes: Vec<_> = fsm.drain_envelopes().collect();
+        assert_eq!(envelopes.len(), 1);
+        assert_eq!(
+            envelopes[0].msg,
+            Msg::Rsp(Response {
+                request_id: id,
+                type_: MsgError::RackUuidMismatch {
+                    expected: pkgs.expose_secret()[0].common.rack_uuid.into(),
+                    got: other_rack_pkg.common.rack_uuid.into(),
+                }
+                .into(),
+            })
+        );
+    }
+
+    #[test]
+    fn tick_reports_expired_requests_in_expiry_order() {
+        let config = FsmConfig {
+            learn_timeout: Duration::from_secs(5),
+            rack_init_timeout: Duration::from_secs(60),
+            rack_secret_request_timeout: Duration::from_secs(10),
+            retry_interval: Duration::from_secs(1),
+        };
+        let membership = members(3);
+        let mut iter = membership.iter().cloned();
+        let id = iter.next().unwrap();
+        let learner = iter.next().unwrap();
+        let mut fsm = Fsm::new_uninitialized(id, config);
+        let start = Instant::now();
+        fsm.init_rack(start, RackUuid(Uuid::new_v4()), membership).unwrap();
+
+        // A load rack secret request expires after 10s, and a learn request
+        // received from a peer expires after 5s.
+        let load_id = fsm.load_rack_secret(start).unwrap();
+        let learn_id = Uuid::new_v4();
+        let learn = Request { id: learn_id, type_: RequestType::Learn };
+        fsm.handle_msg(start, learner, learn.into()).unwrap();
+
+        let output = fsm.tick(start + Duration::from_secs(1));
+        assert_eq!(output, TickOutput::default());
+
+        let output = fsm.tick(start + Duration::from_secs(6));
+        assert_eq!(
+            output.expired,
+            BTreeMap::from([(learn_id, RequestKind::LearnReceived)])
+        );
+        assert!(output.errors.is_empty());
+
+        let output = fsm.tick(start + Duration::from_secs(11));
+        assert_eq!(
+            output.expired,
+            BTreeMap::from([(load_id, RequestKind::LoadRackSecret)])
+        );
+        assert_eq!(
+            output.errors,
+            BTreeMap::from([(load_id, ApiError::RackSecretLoadTimeout)])
+        );
+
+        // Nothing else expires until rack initialization times out
+        let output = fsm.tick(start + Duration::from_secs(30));
+        assert_eq!(output, TickOutput::default());
+    }
+
+    #[test]
+    fn tick_times_out_concurrent_rack_secret_loads() {
+        let config = FsmConfig {
+            rack_init_timeout: Duration::from_secs(60),
+            ..config()
+        };
+        let membership = members(3);
+        let id = membership.first().cloned().unwrap();
+        let mut fsm = Fsm::new_uninitialized(id, config);
+        let start = Instant::now();
+        fsm.init_rack(start, RackUuid(Uuid::new_v4()), membership).unwrap();
+
+        // Loads started at the same time expire at the same time
+        let ids: BTreeSet<_> =
+            (0..3).map(|_| fsm.load_rack_secret(start).unwrap()).collect();
+        assert_eq!(fsm.dump_state(start).requests.len(), 4);
+
+        let output = fsm.tick(start + Duration::from_secs(6));
+        assert_eq!(output.errors.keys().cloned().collect::<BTreeSet<_>>(), ids);
+        assert!(output
+            .errors
+            .values()
+            .all(|err| *err == ApiError::RackSecretLoadTimeout));
+    }
+
+    #[test]
+    fn tick_resends_get_share_to_unacked_peers() {
+        let config = FsmConfig {
+            rack_init_timeout: Duration::from_secs(60),
+            rack_secret_request_timeout: Duration::from_secs(10),
+            ..config()
+        };
+        let membership = members(3);
+        let mut iter = membership.iter().cloned();
+        let id = iter.next().unwrap();
+        let peer = iter.next().unwrap();
+        let mut fsm = Fsm::new_uninitialized(id, config);
+        let start = Instant::now();
+        fsm.init_rack(start, RackUuid(Uuid::new_v4()), membership).unwrap();
+        fsm.on_connected(start, peer.clone()).unwrap();
+        let _ = fsm.drain_envelopes().count();
+
+        let load_id = fsm.load_rack_secret(start).unwrap();
+        assert_eq!(fsm.drain_envelopes().count(), 1);
+
+        // Ignore resent `Init` requests, as the peer never acks those either
+        let resent_loads = |fsm: &mut Fsm| -> Vec<Envelope> {
+            fsm.drain_envelopes()
+                .filter(|envelope| {
+                    matches!(
+                        &envelope.msg,
+                        Msg::Req(Request { id, .. }) if *id == load_id
+                    )
+                })
+                .collect()
+        };
+
+        // Nothing is resent before the retry interval elapses
+        fsm.tick(start + Duration::from_millis(500));
+        assert!(resent_loads(&mut fsm).is_empty());
+
+        fsm.tick(start + Duration::from_secs(2));
+        assert_matches!(resent_loads(&mut fsm).as_slice(), [Envelope {
+            to,
+            msg: Msg::Req(Request { type_: RequestType::GetShare { .. }, .. })
+        }] if *to == peer);
+    }
+
+    #[test]
+    fn dump_state_redacts_secrets() {
+        let membership = members(3);
+        let mut iter = membership.iter().cloned();
+        let id = iter.next().unwrap();
+        let peer = iter.next().unwrap();
+        let mut fsm = Fsm::new_uninitialized(id.clone(), config());
+        let start = Instant::now();
+        let rack_uuid = Uuid::new_v4();
+        fsm.on_connected(start, peer.clone()).unwrap();
+        fsm.init_rack(start, RackUuid(rack_uuid), membership).unwrap();
+        let load_id = fsm.load_rack_secret(start).unwrap();
+
+        let snapshot = fsm.dump_state(start + Duration::from_secs(1));
+        assert_eq!(snapshot.id, id);
+        assert_eq!(snapshot.state, "initial_member");
+        assert_eq!(snapshot.rack_uuid, Some(rack_uuid));
+        assert_eq!(snapshot.connected_peers, BTreeSet::from([peer]));
+        let kinds: BTreeSet<_> =
+            snapshot.requests.iter().map(|req| req.kind).collect();
+        assert_eq!(
+            kinds,
+            BTreeSet::from([
+                RequestKind::InitRack,
+                RequestKind::LoadRackSecret
+            ])
+        );
+        let load = snapshot.requests.iter().find(|r| r.id == load_id).unwrap();
+        assert_eq!(load.expires_in, Duration::from_secs(4));
+
+        // Neither the raw share nor its serialized form may appear anywhere
+        // in the serialized snapshot.
+        let State::InitialMember { pkg, .. } = fsm.state() else {
+            panic!("unexpected state: {}", fsm.state().name());
+        };
+        let mut serialized = Vec::new();
+        ciborium::into_writer(&snapshot, &mut serialized).unwrap();
+        let mut share = Vec::new();
+        ciborium::into_writer(&pkg.common.share, &mut share).unwrap();
+        for secret in [&pkg.common.share, &share] {
+            assert!(!serialized
+                .windows(secret.len())
+                .any(|window| window == secret.as_slice()));
+        }
+    }
+
+    // Initialize a rack coordinated by the first of three members, with the
+    // second connected, and return the second member's package
+    fn init_rack_with_connected_peer() -> (Fsm, Baseboard, SharePkg) {
+        let membership = members(3);
+        let mut iter = membership.iter().cloned();
+        let id = iter.next().unwrap();
+        let peer = iter.next().unwrap();
+        let mut fsm = Fsm::new_uninitialized(id, config());
+        let now = Instant::now();
+        fsm.on_connected(now, peer.clone()).unwrap();
+        fsm.init_rack(now, RackUuid(Uuid::new_v4()), membership).unwrap();
+        let pkg =
+            fsm.drain_envelopes()
+                .find_map(|envelope| match envelope {
+                    Envelope {
+                        to,
+                        msg:
+                            Msg::Req(Request {
+                                type_: RequestType::Init(pkg), ..
+                            }),
+                    } if to == peer => Some(pkg),
+                    _ => None,
+                })
+                .expect("init request sent to connected peer");
+        (fsm, peer, pkg)
+    }
+
+    #[test]
+    fn remove_member_records_removal_after_threshold_of_shares() {
+        let (mut fsm, peer, peer_pkg) = init_rack_with_connected_peer();
+        let removed = members(3).last().cloned().unwrap();
+        let now = Instant::now();
+
+        let request_id = fsm.remove_member(now, removed.clone()).unwrap();
+        assert_eq!(
+            fsm.remove_member(now, removed.clone()),
+            Err(ApiError::RemoveMemberInProgress)
+        );
+        let envelopes: Vec<_> = fsm.drain_envelopes().collect();
+        assert_eq!(envelopes.len(), 1);
+        assert_eq!(envelopes[0].to, peer);
+        assert_matches!(
+            &envelopes[0].msg,
+            Msg::Req(Request { id, type_: RequestType::GetShare { .. } })
+                if *id == request_id
+        );
+
+        // With a threshold of 2, our own share and the peer's are enough.
+        let rsp = Response {
+            request_id,
+            type_: ResponseType::Share(Share(peer_pkg.common.share.clone())),
+        };
+        let output = fsm.handle_msg(now, peer, rsp.into());
+        assert_eq!(
+            output,
+            Ok(Some(ApiOutput::MemberRemoved {
+                request_id,
+                member: removed.clone()
+            }))
+        );
+        let State::InitialMember { removed_members, .. } = fsm.state() else {
+            panic!("unexpected state: {}", fsm.state().name());
+        };
+        assert_eq!(removed_members, &BTreeSet::from([removed.clone()]));
+
+        // The member can't be removed twice
+        assert_eq!(
+            fsm.remove_member(now, removed.clone()),
+            Err(ApiError::NotAMember { member: removed })
+        );
+    }
+
+    #[test]
+    fn remove_member_excludes_removed_member_from_share_requests() {
+        let (mut fsm, peer, peer_pkg) = init_rack_with_connected_peer();
+        let removed = members(3).last().cloned().unwrap();
+        let now = Instant::now();
+        fsm.on_connected(now, removed.clone()).unwrap();
+        let removed_pkg = fsm
+            .drain_envelopes()
+            .find_map(|envelope| match envelope.msg {
+                Msg::Req(Request { type_: RequestType::Init(pkg), .. }) => {
+                    Some(pkg)
+                }
+                _ => None,
+            })
+            .expect("init request resent to removed member");
+
+        // Only the remaining peer is asked for its share, on the initial
+        // broadcast and on retries
+        let request_id = fsm.remove_member(now, removed.clone()).unwrap();
+        let envelopes: Vec<_> = fsm.drain_envelopes().collect();
+        assert_eq!(envelopes.len(), 1);
+        assert_eq!(envelopes[0].to, peer);
+        fsm.tick(now + Duration::from_secs(2));
+        let retries: Vec<_> = fsm
+            .drain_envelopes()
+            .filter(|envelope| envelope.msg.request_id() == request_id)
+            .map(|envelope| envelope.to)
+            .collect();
+        assert_eq!(retries, vec![peer.clone()]);
+
+        // A share from the member being removed doesn't count toward the
+        // threshold
+        let rsp = Response {
+            request_id,
+            type_: ResponseType::Share(Share(removed_pkg.common.share.clone())),
+        };
+        assert_eq!(fsm.handle_msg(now, removed.clone(), rsp.into()), Ok(None));
+        let rsp = Response {
+            request_id,
+            type_: ResponseType::Share(Share(peer_pkg.common.share.clone())),
+        };
+        assert_eq!(
+            fsm.handle_msg(now, peer, rsp.into()),
+            Ok(Some(ApiOutput::MemberRemoved { request_id, member: removed }))
+        );
+    }
+
+    #[test]
+    fn remove_member_rejects_removal_below_threshold() {
+        let (mut fsm, peer, peer_pkg) = init_rack_with_connected_peer();
+        let removed = members(3).last().cloned().unwrap();
+        let now = Instant::now();
+
+        let request_id = fsm.remove_member(now, removed).unwrap();
+        let rsp = Response {
+            request_id,
+            type_: ResponseType::Share(Share(peer_pkg.common.share.clone())),
+        };
+        fsm.handle_msg(now, peer.clone(), rsp.into()).unwrap();
+
+        // Only two members remain, which is exactly the threshold
+        assert_eq!(
+            fsm.remove_member(now, peer),
+            Err(ApiError::MembershipBelowThreshold {
+                remaining: 1,
+                threshold: 2
+            })
+        );
+        assert_eq!(
+            fsm.remove_member(now, fsm.id.clone()),
+            Err(ApiError::CannotRemoveSelf)
+        );
+        let stranger =
+            Baseboard::new_pc("stranger".to_string(), "0".to_string());
+        assert_eq!(
+            fsm.remove_member(now, stranger.clone()),
+            Err(ApiError::NotAMember { member: stranger })
+        );
+    }
+
+    #[test]
+    fn add_member_distributes_pkg_after_threshold_of_shares() {
+        let (mut fsm, peer, peer_pkg) = init_rack_with_connected_peer();
+        let new_member = Baseboard::new_pc("new".to_string(), "0".to_string());
+        let now = Instant::now();
+
+        assert_eq!(
+            fsm.add_member(now, peer.clone()),
+            Err(ApiError::AlreadyAMember { member: peer.clone() })
+        );
+        let request_id = fsm.add_member(now, new_member.clone()).unwrap();
+        assert_eq!(
+            fsm.add_member(now, new_member.clone()),
+            Err(ApiError::AddMemberInProgress)
+        );
+        let envelopes: Vec<_> = fsm.drain_envelopes().collect();
+        assert_eq!(envelopes.len(), 1);
+        assert_eq!(envelopes[0].to, peer);
+
+        // With a threshold of 2, our own share and the peer's are enough. The
+        // share is assigned, and must be persisted, before anything is sent.
+        // The new member isn't connected yet, so nothing is sent.
+        let rsp = Response {
+            request_id,
+            type_: ResponseType::Share(Share(peer_pkg.common.share.clone())),
+        };
+        assert_eq!(
+            fsm.handle_msg(now, peer.clone(), rsp.into()),
+            Ok(Some(ApiOutput::ShareDistributedToMember {
+                request_id,
+                member: new_member.clone()
+            }))
+        );
+        assert!(fsm.drain_envelopes().next().is_none());
+        let State::InitialMember { distributed_shares, .. } = fsm.state()
+        else {
+            panic!("unexpected state: {}", fsm.state().name());
+        };
+        assert!(distributed_shares.contains_key(&new_member));
+
+        // The package is sent once the new member connects
+        fsm.on_connected(now, new_member.clone()).unwrap();
+        let envelopes: Vec<_> = fsm.drain_envelopes().collect();
+        assert_eq!(envelopes.len(), 1);
+        let Envelope {
+            to,
+            msg: Msg::Req(Request { id, type_: RequestType::AddMember(pkg) }),
+        } = &envelopes[0]
+        else {
+            panic!("expected an add member request: {:?}", envelopes[0]);
+        };
+        assert_eq!(to, &new_member);
+        assert_eq!(*id, request_id);
+
+        // The new member becomes a full member and acks
+        let mut new_fsm = Fsm::new_uninitialized(new_member.clone(), config());
+        let req = Request {
+            id: request_id,
+            type_: RequestType::AddMember(pkg.clone()),
+        };
+        assert_eq!(
+            new_fsm.handle_msg(now, fsm.id.clone(), req.into()),
+            Ok(Some(ApiOutput::PeerInitialized))
+        );
+        assert_eq!(new_fsm.state().name(), "initial_member");
+        let envelopes: Vec<_> = new_fsm.drain_envelopes().collect();
+        assert_eq!(envelopes.len(), 1);
+        assert_matches!(
+            &envelopes[0].msg,
+            Msg::Rsp(Response { request_id: id, type_: ResponseType::AddMemberAck })
+                if *id == request_id
+        );
+
+        let ack = Response { request_id, type_: ResponseType::AddMemberAck };
+        assert_eq!(
+            fsm.handle_msg(now, new_member.clone(), ack.into()),
+            Ok(Some(ApiOutput::MemberAdded {
+                request_id,
+                member: new_member.clone()
+            }))
+        );
+        let State::InitialMember { added_members, distributed_shares, .. } =
+            fsm.state()
+        else {
+            panic!("unexpected state: {}", fsm.state().name());
+        };
+        assert_eq!(added_members, &BTreeSet::from([new_member.clone()]));
+        assert!(distributed_shares.contains_key(&new_member));
+
+        // The new member's share is accepted by the rack
+        validate_share(
+            &new_member,
+            &Share(pkg.common.share.clone()),
+            &peer_pkg.common.share_digests,
+        )
+        .unwrap();
+    }
+}
@@ -76,6 +76,10 @@ pub enum RequestType {
     /// Get a [`LearnedSharePkg`] from a peer that was part of the rack
     /// initialization group
     Learn,
+
+    /// A request informing the peer that it has been added to the trust
+    /// quorum as a full member after rack initialization
+    AddMember(SharePkg),
 }
 
 impl RequestType {
@@ -84,6 +88,7 @@ impl RequestType {
             RequestType::Init(_) => "init",
             RequestType::GetShare { .. } => "get_share",
             RequestType::Learn => "learn",
+            RequestType::AddMember(_) => "add_member",
         }
     }
 }
@@ -100,6 +105,9 @@ pub enum ResponseType {
     /// Response to [`RequestType::Learn`]
     LearnPkg(LearnedSharePkg),
 
+    /// Response to [`RequestType::AddMember`]
+    AddMemberAck,
+
     /// An error response
     Error(MsgError),
 }
@@ -110,6 +118,7 @@ impl ResponseType {
             ResponseType::InitAck => "init_ack",
             ResponseType::Share(_) => "share",
             ResponseType::LearnPkg(_) => "learn_pkg",
+            ResponseType::AddMemberAck => "add_member_ack",
             ResponseType::Error(_) => "error",
         }
     }
@@ -19,12 +19,14 @@ use std::time::Duration;
 use uuid::Uuid;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
-pub use fsm::{ApiError, ApiOutput, Fsm, State};
+pub use fsm::{ApiError, ApiOutput, Fsm, FsmDebugSnapshot, State, TickOutput};
 pub use messages::{
     Envelope, Msg, MsgError, Request, RequestType, Response, ResponseType,
 };
 pub use peer::{Config, Node, NodeHandle, NodeRequestError, Status};
-pub use request_manager::{RequestManager, TrackableRequest};
+pub use request_manager::{
+    RequestKind, RequestManager, RequestSnapshot, TrackableRequest,
+};
 pub use share_pkg::{create_pkgs, LearnedSharePkg, SharePkg, SharePkgCommon};
 pub use storage::NetworkConfig;
 
@@ -33,7 +35,18 @@ pub use storage::NetworkConfig;
 /// This number should be incremented when new messages or enum variants are
 /// added.
 #[allow(unused)]
-pub const CURRENT_VERSION: u32 = 0;
+pub const CURRENT_VERSION: u32 = 1;
+
+/// The smallest initial membership allowed for rack initialization
+///
+/// Anything smaller results in a degenerate trust quorum where one or two
+/// sleds can unlock the rack secret.
+pub const MIN_INITIAL_MEMBERS: usize = 3;
+
+/// The largest initial membership allowed for rack initialization
+///
+/// There are only up to 32 sleds in a rack.
+pub const MAX_INITIAL_MEMBERS: usize = 32;
 
 /// A static description of the V0 scheme for trust quorum
 ///
@@ -100,4 +113,7 @@ pub struct FsmConfig {
     pub learn_timeout: Duration,
     pub rack_init_timeout: Duration,
     pub rack_secret_request_timeout: Duration,
+    /// How often outstanding requests are resent to connected peers that
+    /// have not yet responded
+    pub retry_interval: Duration,
 }
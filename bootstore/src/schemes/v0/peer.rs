@@ -32,6 +32,7 @@ pub struct Config {
     pub learn_timeout: Duration,
     pub rack_init_timeout: Duration,
     pub rack_secret_request_timeout: Duration,
+    pub retry_interval: Duration,
     pub fsm_state_ledger_paths: Vec<Utf8PathBuf>,
     pub network_config_ledger_paths: Vec<Utf8PathBuf>,
 }
@@ -320,6 +321,7 @@ impl From<Config> for FsmConfig {
             learn_timeout: value.learn_timeout,
             rack_init_timeout: value.rack_init_timeout,
             rack_secret_request_timeout: value.rack_secret_request_timeout,
+            retry_interval: value.retry_interval,
         }
     }
 }
@@ -396,10 +398,9 @@ impl Node {
                 }
                 Some(msg) = self.conn_rx.recv() => self.on_conn_msg(msg).await,
                 _ = interval.tick() => {
-                    if let Err(errors) = self.fsm.tick(Instant::now().into()) {
-                        for (_, err) in errors {
-                            self.handle_api_error(err).await;
-                        }
+                    let output = self.fsm.tick(Instant::now().into());
+                    for (_, err) in output.errors {
+                        self.handle_api_error(err).await;
                     }
                 }
             }
@@ -740,7 +741,10 @@ impl Node {
                     );
                 }
             }
-            ApiOutput::ShareDistributedToLearner => {
+            ApiOutput::ShareDistributedToLearner
+            | ApiOutput::ShareDistributedToMember { .. }
+            | ApiOutput::MemberRemoved { .. }
+            | ApiOutput::MemberAdded { .. } => {
                 self.fsm_ledger_generation = PersistentFsmState::save(
                     &self.log,
                     self.config.fsm_state_ledger_paths.clone(),
@@ -773,7 +777,10 @@ impl Node {
     async fn handle_api_error(&mut self, err: ApiError) {
         warn!(self.log, "Fsm error= {err:?}");
         match err {
-            ApiError::AlreadyInitialized | ApiError::RackInitTimeout { .. } => {
+            ApiError::AlreadyInitialized
+            | ApiError::RackInitTimeout { .. }
+            | ApiError::InitialMembershipTooSmall { .. }
+            | ApiError::InitialMembershipTooLarge { .. } => {
                 if let Some(responder) = self.init_responder.take() {
                     let _ = responder.send(Err(err.into()));
                 }
@@ -796,7 +803,18 @@ impl Node {
             ApiError::FailedToDecryptExtraShares
             | ApiError::UnexpectedResponse { .. }
             | ApiError::ErrorResponseReceived { .. }
-            | ApiError::InvalidShare { .. } => {}
+            | ApiError::InvalidShare { .. }
+            | ApiError::NotAnInitialMember
+            | ApiError::NotAMember { .. }
+            | ApiError::CannotRemoveSelf
+            | ApiError::RemoveMemberInProgress
+            | ApiError::MembershipBelowThreshold { .. }
+            | ApiError::RemoveMemberTimeout { .. }
+            | ApiError::AlreadyAMember { .. }
+            | ApiError::AddMemberInProgress
+            | ApiError::AddMemberTimeout { .. }
+            | ApiError::CannotSpareAShare
+            | ApiError::AddMemberFailed(_) => {}
         }
     }
 
@@ -1172,6 +1190,7 @@ mod tests {
                         learn_timeout: Duration::from_secs(5),
                         rack_init_timeout: Duration::from_secs(10),
                         rack_secret_request_timeout: Duration::from_secs(1),
+                        retry_interval: Duration::from_millis(200),
                         fsm_state_ledger_paths: vec![tempdir
                             .path()
                             .join(&fsm_file)],
@@ -1240,6 +1259,7 @@ mod tests {
                 learn_timeout: Duration::from_secs(5),
                 rack_init_timeout: Duration::from_secs(10),
                 rack_secret_request_timeout: Duration::from_secs(1),
+                retry_interval: Duration::from_millis(200),
                 fsm_state_ledger_paths: vec![self
                     .tempdir
                     .path()
@@ -7,9 +7,10 @@
 use super::{
     Envelope, FsmConfig, Msg, RackUuid, Request, RequestType, Share, SharePkg,
 };
+use serde::Serialize;
 use sled_hardware_types::Baseboard;
 use std::collections::{BTreeMap, BTreeSet};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 /// Acknowledgement tracking for `RequestType::InitRack`.
@@ -63,6 +64,74 @@ pub enum TrackableRequest {
     /// A request sent from a peer in `Learning` state to another peer
     /// to learn a key share.
     LearnSent { to: Baseboard },
+
+    /// A request from the caller of the Fsm API to permanently remove a
+    /// member from the trust quorum
+    ///
+    /// This gathers a threshold of shares, so that removal requires the
+    /// participation of a quorum of members.
+    ///
+    /// Only peers in `InitialMember` state can remove members
+    RemoveMember { rack_uuid: RackUuid, member: Baseboard, acks: ShareAcks },
+
+    /// A request from the caller of the Fsm API to add a member to the trust
+    /// quorum after rack initialization
+    ///
+    /// This gathers a threshold of shares, so that the rack secret can be
+    /// reconstructed and an extra share decrypted for the new member.
+    ///
+    /// Only peers in `InitialMember` state can add members
+    AddMember { rack_uuid: RackUuid, member: Baseboard, acks: ShareAcks },
+
+    /// A `RequestType::AddMember` sent to a new member once its package has
+    /// been created, awaiting an acknowledgement.
+    AddMemberSent { member: Baseboard, pkg: SharePkg },
+}
+
+impl TrackableRequest {
+    /// Return the kind of this request, without any of its tracking state
+    pub fn kind(&self) -> RequestKind {
+        match self {
+            TrackableRequest::InitRack { .. } => RequestKind::InitRack,
+            TrackableRequest::LoadRackSecret { .. } => {
+                RequestKind::LoadRackSecret
+            }
+            TrackableRequest::LearnReceived { .. } => {
+                RequestKind::LearnReceived
+            }
+            TrackableRequest::LearnSent { .. } => RequestKind::LearnSent,
+            TrackableRequest::RemoveMember { .. } => RequestKind::RemoveMember,
+            TrackableRequest::AddMember { .. } => RequestKind::AddMember,
+            TrackableRequest::AddMemberSent { .. } => {
+                RequestKind::AddMemberSent
+            }
+        }
+    }
+}
+
+/// The kind of a [`TrackableRequest`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum RequestKind {
+    InitRack,
+    LoadRackSecret,
+    LearnReceived,
+    LearnSent,
+    RemoveMember,
+    AddMember,
+    AddMemberSent,
+}
+
+/// A view of an outstanding request for debugging, without any shares
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RequestSnapshot {
+    pub id: Uuid,
+    pub kind: RequestKind,
+    /// Time remaining until the request expires (zero if it already has)
+    pub expires_in: Duration,
+    /// Number of acks or shares received so far
+    pub acks_received: usize,
+    /// Number of acks or shares needed for the request to complete
+    pub acks_needed: usize,
 }
 
 /// A mechanism to manage all in flight requests
@@ -72,7 +141,16 @@ pub struct RequestManager {
     id: Baseboard,
     config: FsmConfig,
     requests: BTreeMap<Uuid, TrackableRequest>,
-    expiry_to_id: BTreeMap<Instant, Uuid>,
+
+    /// Request expiries, ordered by time
+    ///
+    /// Requests created at the same time with the same timeout share an
+    /// expiry, so the request id is part of the key.
+    expiries: BTreeSet<(Instant, Uuid)>,
+
+    /// When each outstanding request should next be resent to connected
+    /// peers that have not yet responded
+    next_retry: BTreeMap<Uuid, Instant>,
 
     /// Messages that need sending to other peers.
     ///
@@ -87,11 +165,20 @@ impl RequestManager {
             id,
             config,
             requests: BTreeMap::new(),
-            expiry_to_id: BTreeMap::new(),
+            expiries: BTreeSet::new(),
+            next_retry: BTreeMap::new(),
             envelopes: vec![],
         }
     }
 
+    /// Drop all outstanding requests and any unsent messages
+    pub fn clear(&mut self) {
+        self.requests.clear();
+        self.expiries.clear();
+        self.next_retry.clear();
+        self.envelopes.clear();
+    }
+
     /// Return an iterator of drained envelopes
     pub fn drain_elements(&mut self) -> impl Iterator<Item = Envelope> + '_ {
         self.envelopes.drain(..)
@@ -115,7 +202,7 @@ impl RequestManager {
             packages: packages.clone(),
             acks,
         };
-        let request_id = self.new_request(expiry, req);
+        let request_id = self.new_request(now, expiry, req);
 
         // Send a `Request::Init` to each connected peer in the initial group
         let iter = packages
@@ -141,6 +228,7 @@ impl RequestManager {
     ) -> Uuid {
         let expiry = now + self.config.rack_secret_request_timeout;
         let request_id = self.new_request(
+            now,
             expiry,
             TrackableRequest::LoadRackSecret {
                 rack_uuid,
@@ -170,8 +258,7 @@ impl RequestManager {
             acks: ShareAcks::new(threshold),
         };
         let expiry = now + self.config.learn_timeout;
-        self.requests.insert(request_id, request);
-        self.expiry_to_id.insert(expiry, request_id);
+        self.track_request(request_id, now, expiry, request);
         self.broadcast_get_share(request_id, rack_uuid, connected_peers);
     }
 
@@ -180,6 +267,7 @@ impl RequestManager {
     pub fn new_learn_sent_req(&mut self, now: Instant, to: Baseboard) -> Uuid {
         let expiry = now + self.config.learn_timeout;
         let request_id = self.new_request(
+            now,
             expiry,
             TrackableRequest::LearnSent { to: to.clone() },
         );
@@ -195,23 +283,118 @@ impl RequestManager {
         request_id
     }
 
+    /// Track a new `Fsm::remove_member` api request and broadcast a
+    /// `RequestType::GetShare` to connected peers.
+    pub fn new_remove_member_req(
+        &mut self,
+        now: Instant,
+        rack_uuid: RackUuid,
+        threshold: u8,
+        member: Baseboard,
+        connected_peers: &BTreeSet<Baseboard>,
+    ) -> Uuid {
+        let expiry = now + self.config.rack_secret_request_timeout;
+        let request_id = self.new_request(
+            now,
+            expiry,
+            TrackableRequest::RemoveMember {
+                rack_uuid,
+                member: member.clone(),
+                acks: ShareAcks::new(threshold),
+            },
+        );
+        // The member being removed must not take part in its own removal
+        let mut peers = connected_peers.clone();
+        peers.remove(&member);
+        self.broadcast_get_share(request_id, rack_uuid, &peers);
+        request_id
+    }
+
+    /// Track a new `Fsm::add_member` api request and broadcast a
+    /// `RequestType::GetShare` to connected peers.
+    pub fn new_add_member_req(
+        &mut self,
+        now: Instant,
+        rack_uuid: RackUuid,
+        threshold: u8,
+        member: Baseboard,
+        connected_peers: &BTreeSet<Baseboard>,
+    ) -> Uuid {
+        let expiry = now + self.config.rack_secret_request_timeout;
+        let request_id = self.new_request(
+            now,
+            expiry,
+            TrackableRequest::AddMember {
+                rack_uuid,
+                member,
+                acks: ShareAcks::new(threshold),
+            },
+        );
+        self.broadcast_get_share(request_id, rack_uuid, connected_peers);
+        request_id
+    }
+
+    /// Track a `RequestType::AddMember` for a completed `AddMember` request
+    /// and send it to the new member if it is connected.
+    ///
+    /// The request keeps the id of the original `Fsm::add_member` api request.
+    pub fn new_add_member_sent_req(
+        &mut self,
+        request_id: Uuid,
+        now: Instant,
+        member: Baseboard,
+        pkg: SharePkg,
+        connected_peers: &BTreeSet<Baseboard>,
+    ) {
+        if connected_peers.contains(&member) {
+            self.envelopes.push(Envelope {
+                to: member.clone(),
+                msg: Msg::Req(Request {
+                    id: request_id,
+                    type_: RequestType::AddMember(pkg.clone()),
+                }),
+            });
+        }
+        let expiry = now + self.config.rack_init_timeout;
+        self.track_request(
+            request_id,
+            now,
+            expiry,
+            TrackableRequest::AddMemberSent { member, pkg },
+        );
+    }
+
     fn remove_request(&mut self, request_id: Uuid) -> Option<TrackableRequest> {
-        self.expiry_to_id.retain(|_, id| *id != request_id);
+        self.expiries.retain(|(_, id)| *id != request_id);
+        self.next_retry.remove(&request_id);
         self.requests.remove(&request_id)
     }
 
     // Track a new request
     fn new_request(
         &mut self,
+        now: Instant,
         expiry: Instant,
         request: TrackableRequest,
     ) -> Uuid {
         let id = Uuid::new_v4();
-        self.requests.insert(id, request);
-        self.expiry_to_id.insert(expiry, id);
+        self.track_request(id, now, expiry, request);
         id
     }
 
+    // Track a request with a known id
+    fn track_request(
+        &mut self,
+        request_id: Uuid,
+        now: Instant,
+        expiry: Instant,
+        request: TrackableRequest,
+    ) {
+        self.requests.insert(request_id, request);
+        self.expiries.insert((expiry, request_id));
+        self.next_retry.insert(request_id, now + self.config.retry_interval);
+    }
+
     // Send a `GetShare` request to all connected peers
     fn broadcast_get_share(
         &mut self,
@@ -252,6 +435,42 @@ impl RequestManager {
         })
     }
 
+    /// Return the number of `InitAck`s received and expected for the
+    /// outstanding `InitRack` request, if there is one
+    pub fn init_rack_progress(&self) -> Option<(usize, usize)> {
+        self.requests.values().find_map(|req| match req {
+            TrackableRequest::InitRack { acks, .. } => {
+                Some((acks.received.len(), acks.expected.len()))
+            }
+            _ => None,
+        })
+    }
+
+    /// Is there an outstanding `LoadRackSecret` request
+    pub fn has_load_rack_secret_req(&self) -> bool {
+        self.requests
+            .values()
+            .any(|req| matches!(req, TrackableRequest::LoadRackSecret { .. }))
+    }
+
+    /// Is there an outstanding `RemoveMember` request
+    pub fn has_remove_member_req(&self) -> bool {
+        self.requests
+            .values()
+            .any(|req| matches!(req, TrackableRequest::RemoveMember { .. }))
+    }
+
+    /// Is there an outstanding `AddMember` or `AddMemberSent` request
+    pub fn has_add_member_req(&self) -> bool {
+        self.requests.values().any(|req| {
+            matches!(
+                req,
+                TrackableRequest::AddMember { .. }
+                    | TrackableRequest::AddMemberSent { .. }
+            )
+        })
+    }
+
     /// Return any expired requests mapped to their request id
     ///
     /// This is typically called during `tick` callbacks.
@@ -260,21 +479,83 @@ impl RequestManager {
         now: Instant,
     ) -> BTreeMap<Uuid, TrackableRequest> {
         let mut expired = BTreeMap::new();
-        while let Some((expiry, request_id)) = self.expiry_to_id.pop_last() {
+        while let Some((expiry, request_id)) = self.expiries.pop_first() {
             if expiry < now {
+                self.next_retry.remove(&request_id);
                 expired.insert(
                     request_id,
                     self.requests.remove(&request_id).unwrap(),
                 );
             } else {
-                // Put the last request back. We are done.
-                self.expiry_to_id.insert(expiry, request_id);
+                // Put the earliest unexpired request back. We are done.
+                self.expiries.insert((expiry, request_id));
                 break;
             }
         }
         expired
     }
 
+    /// Resend outstanding requests to connected peers that have not yet
+    /// responded, at most once per `FsmConfig::retry_interval` for each
+    /// request
+    ///
+    /// This is typically called during `tick` callbacks, so that a lost
+    /// message doesn't stall a request until the peer reconnects.
+    pub fn retry(
+        &mut self,
+        now: Instant,
+        connected_peers: &BTreeSet<Baseboard>,
+    ) {
+        for (request_id, next_retry) in &mut self.next_retry {
+            if *next_retry > now {
+                continue;
+            }
+            *next_retry = now + self.config.retry_interval;
+            let Some(request) = self.requests.get(request_id) else {
+                continue;
+            };
+            self.envelopes.extend(
+                connected_peers
+                    .iter()
+                    .filter_map(|peer| resend(*request_id, request, peer)),
+            );
+        }
+    }
+
+    /// Return a snapshot of all outstanding requests, ordered by expiry
+    pub fn snapshot(&self, now: Instant) -> Vec<RequestSnapshot> {
+        self.expiries
+            .iter()
+            .filter_map(|(expiry, id)| {
+                let req = self.requests.get(id)?;
+                let (acks_received, acks_needed) = match req {
+                    TrackableRequest::InitRack { acks, .. } => {
+                        (acks.received.len(), acks.expected.len())
+                    }
+                    TrackableRequest::LoadRackSecret { acks, .. }
+                    | TrackableRequest::LearnReceived { acks, .. }
+                    | TrackableRequest::RemoveMember { acks, .. }
+                    | TrackableRequest::AddMember { acks, .. } => {
+                        // We already have our own share
+                        (
+                            acks.received.len(),
+                            usize::from(acks.threshold.saturating_sub(1)),
+                        )
+                    }
+                    TrackableRequest::LearnSent { .. }
+                    | TrackableRequest::AddMemberSent { .. } => (0, 1),
+                };
+                Some(RequestSnapshot {
+                    id: *id,
+                    kind: req.kind(),
+                    expires_in: expiry.saturating_duration_since(now),
+                    acks_received,
+                    acks_needed,
+                })
+            })
+            .collect()
+    }
+
     /// Return `Some(true)` if initialization completed, `Some(false)` if it
     // did not.
     ///
@@ -315,6 +596,14 @@ impl RequestManager {
         let acks = match self.requests.get_mut(&request_id) {
             Some(TrackableRequest::LoadRackSecret { acks, .. }) => acks,
             Some(TrackableRequest::LearnReceived { acks, .. }) => acks,
+            Some(TrackableRequest::RemoveMember { member, acks, .. }) => {
+                if member == &from {
+                    // We never ask the member being removed for its share
+                    return None;
+                }
+                acks
+            }
+            Some(TrackableRequest::AddMember { acks, .. }) => acks,
             _ => return None,
         };
 
@@ -340,63 +629,152 @@ impl RequestManager {
         }
     }
 
+    /// Return `Some(member)` if there is an `AddMemberSent` for the given
+    /// `request_id` and it was sent to `from`, `None` otherwise.
+    pub fn on_add_member_ack(
+        &mut self,
+        from: &Baseboard,
+        request_id: Uuid,
+    ) -> Option<Baseboard> {
+        match self.requests.get(&request_id) {
+            Some(TrackableRequest::AddMemberSent { member, .. })
+                if member == from =>
+            {
+                let _req = self.remove_request(request_id);
+                Some(from.clone())
+            }
+            _ => None,
+        }
+    }
+
     /// If there are outstanding requests and this peer has not acknowledged
     /// the given request then send the request to the peer.
     pub fn on_connected(&mut self, peer_id: &Baseboard) {
         for (request_id, request) in &self.requests {
-            match request {
-                TrackableRequest::InitRack { packages, acks, .. } => {
-                    if acks.received.contains(peer_id)
-                        || !acks.expected.contains(peer_id)
-                    {
-                        continue;
-                    }
-                    if let Some(pkg) = packages.get(peer_id) {
-                        self.envelopes.push(Envelope {
-                            to: peer_id.clone(),
-                            msg: Msg::Req(Request {
-                                id: *request_id,
-                                type_: RequestType::Init(pkg.clone()),
-                            }),
-                        });
-                    }
-                }
-                TrackableRequest::LoadRackSecret {
-                    rack_uuid, acks, ..
-                } => {
-                    if acks.received.contains_key(peer_id) {
-                        continue;
-                    }
-                    self.envelopes.push(Envelope {
-                        to: peer_id.clone(),
-                        msg: Msg::Req(Request {
-                            id: *request_id,
-                            type_: RequestType::GetShare {
-                                rack_uuid: *rack_uuid,
-                            },
-                        }),
-                    });
-                }
-                TrackableRequest::LearnReceived { rack_uuid, acks, .. } => {
-                    if acks.received.contains_key(peer_id) {
-                        continue;
-                    }
-                    self.envelopes.push(Envelope {
-                        to: peer_id.clone(),
-                        msg: Msg::Req(Request {
-                            id: *request_id,
-                            type_: RequestType::GetShare {
-                                rack_uuid: *rack_uuid,
-                            },
-                        }),
-                    });
-                }
-                TrackableRequest::LearnSent { .. } => {
-                    // If we have an existing `LearnSender` request there is no
-                    // need to send another one currently.
-                    continue;
-                }
+            self.envelopes.extend(resend(*request_id, request, peer_id));
+        }
+    }
+}
+
+// Return the message for `request` that `peer_id` still needs, if any
+fn resend(
+    request_id: Uuid,
+    request: &TrackableRequest,
+    peer_id: &Baseboard,
+) -> Option<Envelope> {
+    let type_ = match request {
+        TrackableRequest::InitRack { packages, acks, .. } => {
+            if acks.received.contains(peer_id)
+                || !acks.expected.contains(peer_id)
+            {
+                return None;
+            }
+            RequestType::Init(packages.get(peer_id)?.clone())
+        }
+        TrackableRequest::RemoveMember { member, .. } if member == peer_id => {
+            return None;
+        }
+        TrackableRequest::LoadRackSecret { rack_uuid, acks }
+        | TrackableRequest::LearnReceived { rack_uuid, acks, .. }
+        | TrackableRequest::RemoveMember { rack_uuid, acks, .. }
+        | TrackableRequest::AddMember { rack_uuid, acks, .. } => {
+            if acks.received.contains_key(peer_id) {
+                return None;
             }
+            RequestType::GetShare { rack_uuid: *rack_uuid }
         }
+        TrackableRequest::AddMemberSent { member, pkg } => {
+            if member != peer_id {
+                return None;
+            }
+            RequestType::AddMember(pkg.clone())
+        }
+        TrackableRequest::LearnSent { .. } => {
+            // A learner moves on to the next peer when its request expires,
+            // rather than sending another request to the same peer.
+            return None;
+        }
+    };
+    Some(Envelope {
+        to: peer_id.clone(),
+        msg: Msg::Req(Request { id: request_id, type_ }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expired_returns_only_expired_requests() {
+        let config = FsmConfig {
+            learn_timeout: Duration::from_secs(5),
+            rack_init_timeout: Duration::from_secs(20),
+            rack_secret_request_timeout: Duration::from_secs(10),
+            retry_interval: Duration::from_secs(1),
+        };
+        let id = Baseboard::new_pc("sled-0".to_string(), "0".to_string());
+        let peer = Baseboard::new_pc("sled-1".to_string(), "0".to_string());
+        let mut manager = RequestManager::new(id, config);
+        let rack_uuid = RackUuid(Uuid::new_v4());
+        let connected_peers = BTreeSet::new();
+        let start = Instant::now();
+
+        // Expires at 5s
+        let learn = manager.new_learn_sent_req(start, peer);
+        // Both expire at 10s
+        let load1 = manager.new_load_rack_secret_req(
+            start,
+            rack_uuid,
+            2,
+            &connected_peers,
+        );
+        let load2 = manager.new_load_rack_secret_req(
+            start,
+            rack_uuid,
+            2,
+            &connected_peers,
+        );
+        // Expires at 12s
+        let load3 = manager.new_load_rack_secret_req(
+            start + Duration::from_secs(2),
+            rack_uuid,
+            2,
+            &connected_peers,
+        );
+        let ids = |expired: BTreeMap<Uuid, TrackableRequest>| {
+            expired.into_keys().collect::<BTreeSet<_>>()
+        };
+
+        // Nothing has expired until its expiry has passed
+        assert!(manager.expired(start).is_empty());
+        assert!(manager.expired(start + Duration::from_secs(5)).is_empty());
+        assert_eq!(manager.snapshot(start).len(), 4);
+
+        assert_eq!(
+            ids(manager.expired(start + Duration::from_secs(6))),
+            BTreeSet::from([learn])
+        );
+        assert!(!manager.has_learn_sent_req());
+        // Expired requests are only returned once
+        assert!(manager.expired(start + Duration::from_secs(6)).is_empty());
+
+        assert_eq!(
+            ids(manager.expired(start + Duration::from_secs(11))),
+            BTreeSet::from([load1, load2])
+        );
+        let remaining: Vec<_> = manager
+            .snapshot(start)
+            .into_iter()
+            .map(|snapshot| snapshot.id)
+            .collect();
+        assert_eq!(remaining, vec![load3]);
+
+        assert_eq!(
+            ids(manager.expired(start + Duration::from_secs(13))),
+            BTreeSet::from([load3])
+        );
+        assert!(manager.snapshot(start).is_empty());
+        assert!(manager.requests.is_empty());
     }
 }
@@ -102,6 +102,43 @@ impl SharePkg {
         );
         decrypt_shares(self.nonce, &cipher, &self.encrypted_shares)
     }
+
+    /// Create a package for a member added after rack initialization
+    ///
+    /// `share` must be one of the extra shares decrypted from this package.
+    /// The new package carries no extra shares of its own, so the new member
+    /// cannot hand out shares to learners.
+    pub fn new_member_pkg(
+        &self,
+        rack_secret: &RackSecret,
+        share: Vec<u8>,
+    ) -> Result<SharePkg, TrustQuorumError> {
+        let cipher = derive_encryption_key(
+            &self.common.rack_uuid,
+            &rack_secret,
+            &self.salt,
+        );
+        // The counter portion of the nonce is only unique among the initial
+        // members, so we rely on the random portion to keep it unique here.
+        let nonce =
+            new_nonce(u8::try_from(self.initial_membership.len()).unwrap());
+        let encrypted_shares = cipher
+            .encrypt((&nonce).into(), &[][..])
+            .map_err(|_| TrustQuorumError::FailedToEncrypt)?;
+        Ok(SharePkg {
+            common: SharePkgCommon {
+                rack_uuid: self.common.rack_uuid,
+                epoch: self.common.epoch,
+                threshold: self.common.threshold,
+                share,
+                share_digests: self.common.share_digests.clone(),
+            },
+            initial_membership: self.initial_membership.clone(),
+            salt: self.salt,
+            nonce,
+            encrypted_shares,
+        })
+    }
 }
 
 /// An analog to [`SharePkg`] for nodes that were added after rack
@@ -341,4 +378,36 @@ mod tests {
         let rack_secret2 = RackSecret::combine_shares(&random_shares).unwrap();
         assert_eq!(rack_secret, rack_secret2);
     }
+
+    #[test]
+    fn new_member_package() {
+        let uuid = Uuid::new_v4();
+        let initial_members: BTreeSet<Baseboard> = ["a", "b", "c"]
+            .iter()
+            .map(|id| Baseboard::new_pc(id.to_string(), "1".to_string()))
+            .collect();
+        let packages = create_pkgs(uuid, initial_members).unwrap();
+        let packages = packages.expose_secret();
+        let threshold_of_shares: Vec<_> =
+            packages.iter().take(2).map(|p| p.common.share.clone()).collect();
+        let rack_secret =
+            RackSecret::combine_shares(&threshold_of_shares).unwrap();
+
+        let extra_shares = packages[0].decrypt_shares(&rack_secret).unwrap();
+        let share = extra_shares.expose_secret()[0].clone();
+        let pkg =
+            packages[0].new_member_pkg(&rack_secret, share.clone()).unwrap();
+        assert_eq!(pkg.common.rack_uuid, uuid);
+        assert_eq!(pkg.common.threshold, packages[0].common.threshold);
+        assert_eq!(pkg.common.share, share);
+        assert_ne!(pkg.nonce, packages[0].nonce);
+
+        // The new member has no extra shares to hand out
+        let shares = pkg.decrypt_shares(&rack_secret).unwrap();
+        assert!(shares.expose_secret().is_empty());
+
+        // The new member's share can be used to reconstruct the rack secret
+        let shares = vec![share, packages[1].common.share.clone()];
+        assert_eq!(RackSecret::combine_shares(&shares).unwrap(), rack_secret);
+    }
 }
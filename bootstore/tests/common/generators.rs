@@ -15,8 +15,10 @@ use uuid::Uuid;
 // Ranges for timeout generation
 const LEARN_TIMEOUT_SECS: RangeInclusive<u64> = 5..=10;
 const RACK_SECRET_TIMEOUT_SECS: RangeInclusive<u64> = 5..=20;
+const RETRY_INTERVAL_MILLIS: RangeInclusive<u64> = 250..=2000;
 #[allow(dead_code)]
 const TICKS_PER_ACTION: RangeInclusive<usize> = 1..=5;
+const MAX_DROPPED_MESSAGES: RangeInclusive<usize> = 1..=5;
 pub const MAX_ACTIONS: usize = 1000;
 pub const MIN_INITIAL_MEMBERS: usize = 3;
 pub const MAX_INITIAL_MEMBERS: usize = 12;
@@ -35,6 +37,13 @@ pub enum Action {
     Learn(Baseboard),
     // Generate an error response from another peer
     ErrorResponse(Baseboard, MsgError),
+    // Add a new member to the trust quorum after rack init
+    AddMember(Baseboard),
+    // Reset the SUT to `State::Uninitialized` and initialize it again
+    RackReset,
+    // Discard up to n of the next requests sent by the SUT without
+    // disconnecting the peers they were sent to
+    DropMessages(usize),
 }
 
 pub fn arb_action(
@@ -59,7 +68,10 @@ pub fn arb_action(
         3 => selected_peer.clone().prop_map(Action::GetShare),
         3 => selected_peer.prop_map(Action::GetShareFail),
         5 => arb_learner_id().prop_map(Action::Learn),
-        3 => err_response
+        3 => err_response,
+        3 => arb_learner_id().prop_map(Action::AddMember),
+        1 => Just(Action::RackReset),
+        3 => (MAX_DROPPED_MESSAGES).prop_map(Action::DropMessages)
     ]
 }
 
@@ -92,15 +104,21 @@ pub fn arb_learner_id() -> impl Strategy<Value = Baseboard> {
 
 // Generate an FSM configuration
 pub fn arb_config() -> impl Strategy<Value = FsmConfig> {
-    (LEARN_TIMEOUT_SECS, RACK_SECRET_TIMEOUT_SECS).prop_map(
-        |(learn_timeout, rack_secret_request_timeout)| FsmConfig {
-            learn_timeout: Duration::from_secs(learn_timeout),
-            rack_init_timeout: Duration::from_secs(rack_secret_request_timeout),
-            rack_secret_request_timeout: Duration::from_secs(
-                rack_secret_request_timeout,
-            ),
-        },
-    )
+    (LEARN_TIMEOUT_SECS, RACK_SECRET_TIMEOUT_SECS, RETRY_INTERVAL_MILLIS)
+        .prop_map(
+            |(learn_timeout, rack_secret_request_timeout, retry_interval)| {
+                FsmConfig {
+                    learn_timeout: Duration::from_secs(learn_timeout),
+                    rack_init_timeout: Duration::from_secs(
+                        rack_secret_request_timeout,
+                    ),
+                    rack_secret_request_timeout: Duration::from_secs(
+                        rack_secret_request_timeout,
+                    ),
+                    retry_interval: Duration::from_millis(retry_interval),
+                }
+            },
+        )
 }
 
 // Generate a `MsgError`
@@ -53,8 +53,16 @@ pub struct CommonTestState {
     // `Fsm::load_rack_secret` requests triggered by an `Action::LoadRackSecret`
     pub load_rack_secret_requests: BTreeMap<Uuid, TestRequest>,
 
+    // `Fsm::add_member` requests triggered by an `Action::AddMember`, along
+    // with the member being added
+    pub add_member_requests: BTreeMap<Uuid, (Baseboard, TestRequest)>,
+
     // Rack secret threshold
     pub threshold: usize,
+
+    // The number of requests from the SUT that will be discarded rather than
+    // delivered to peers
+    pub messages_to_drop: usize,
 }
 
 impl CommonTestState {
@@ -73,7 +81,9 @@ impl CommonTestState {
             connected_peers: BTreeSet::new(),
             now: Instant::now(),
             load_rack_secret_requests: BTreeMap::new(),
+            add_member_requests: BTreeMap::new(),
             threshold,
+            messages_to_drop: 0,
         }
     }
     pub fn load_rack_secret(&mut self) -> Vec<Envelope> {
@@ -117,23 +127,63 @@ impl CommonTestState {
         assert_eq!(None, self.sut.drain_envelopes().next());
     }
 
-    pub fn tick(&mut self, ticks: usize) {
+    // Advance time at the SUT, returning any requests it resent to peers that
+    // have not yet responded
+    pub fn tick(&mut self, ticks: usize) -> Vec<Envelope> {
+        let mut envelopes = vec![];
         for _ in 0..ticks {
             self.now += TICK_TIMEOUT;
-            if let Err(errors) = self.sut.tick(self.now) {
-                // The only possible error is a timeout
-                // Ensure the request exists and it should have timed out
-                for (request_id, error) in errors {
-                    assert_eq!(error, ApiError::RackSecretLoadTimeout);
-                    let test_req = &self.load_rack_secret_requests[&request_id];
-                    let expiry = test_req.start
-                        + self.config.rack_secret_request_timeout;
-                    assert!(expiry < self.now);
-                    // Remove the tracking req from test state
-                    self.load_rack_secret_requests.remove(&request_id);
-                }
+            // The only possible errors are timeouts
+            // Ensure the request exists and it should have timed out
+            for (request_id, error) in self.sut.tick(self.now).errors {
+                // Remove the tracking req from test state
+                let test_req = match error {
+                    ApiError::RackSecretLoadTimeout => self
+                        .load_rack_secret_requests
+                        .remove(&request_id)
+                        .unwrap(),
+                    ApiError::AddMemberTimeout { member } => {
+                        let (expected, test_req) = self
+                            .add_member_requests
+                            .remove(&request_id)
+                            .unwrap();
+                        assert_eq!(member, expected);
+                        test_req
+                    }
+                    _ => panic!("unexpected error: {error:?}"),
+                };
+                let expiry =
+                    test_req.start + self.config.rack_secret_request_timeout;
+                assert!(expiry < self.now);
             }
+
+            // Every request must either complete or time out, so none that
+            // are still tracked may have expired
+            let timeout = self.config.rack_secret_request_timeout;
+            assert!(self
+                .load_rack_secret_requests
+                .values()
+                .chain(self.add_member_requests.values().map(|(_, req)| req))
+                .all(|req| req.start + timeout >= self.now));
+
+            envelopes.extend(self.sut.drain_envelopes());
+        }
+        envelopes
+    }
+
+    pub fn drop_messages(&mut self, n: usize) {
+        self.messages_to_drop += n;
+    }
+
+    // Return true if the next request from the SUT should be lost
+    //
+    // The SUT must recover by resending the request on a later tick.
+    pub fn should_drop(&mut self) -> bool {
+        if self.messages_to_drop == 0 {
+            return false;
         }
+        self.messages_to_drop -= 1;
+        true
     }
 
     pub fn get_share(&mut self, peer_id: Baseboard) {
@@ -205,12 +205,13 @@ impl TestState {
             type_: ResponseType::LearnPkg(learned_pkg.clone()),
         }
         .into();
-        let output = self.common.sut.handle_msg(self.common.now, peer_id, rsp);
+        let output =
+            self.common.sut.handle_msg(self.common.now, peer_id.clone(), rsp);
         assert_eq!(output, Ok(Some(ApiOutput::LearningCompleted)));
         assert!(self.common.sut.drain_envelopes().next().is_none());
         assert_eq!(
             self.common.sut.state(),
-            &State::Learned { pkg: learned_pkg }
+            &State::Learned { pkg: learned_pkg, learned_from: Some(peer_id) }
         );
     }
 
@@ -225,7 +226,7 @@ impl TestState {
         self.common.connected_peers = BTreeSet::new();
         for _ in 0..self.ticks_until_learn_timeout() {
             self.common.now += TICK_TIMEOUT;
-            assert!(self.common.sut.tick(self.common.now).is_ok());
+            assert!(self.common.sut.tick(self.common.now).errors.is_empty());
             assert!(self.common.sut.drain_envelopes().next().is_none());
         }
     }
@@ -315,12 +316,12 @@ impl TestState {
         // sent.
         for _ in 0..self.ticks_until_learn_timeout() - 1 {
             self.common.now += TICK_TIMEOUT;
-            assert!(self.common.sut.tick(self.common.now).is_ok());
+            assert!(self.common.sut.tick(self.common.now).errors.is_empty());
             assert!(self.common.sut.drain_envelopes().next().is_none());
         }
         // Trigger the timeout
         self.common.now += TICK_TIMEOUT;
-        assert!(self.common.sut.tick(self.common.now).is_ok());
+        assert!(self.common.sut.tick(self.common.now).errors.is_empty());
         let mut iter = self.common.sut.drain_envelopes();
         assert_matches!(iter.next().unwrap(),
         Envelope {
@@ -351,8 +352,36 @@ impl TestState {
         assert!(iter.next().is_none());
     }
 
+    // A learner cannot add members
+    pub fn add_member_must_fail(&mut self, peer_id: Baseboard) {
+        let output = self.common.sut.add_member(self.common.now, peer_id);
+        assert_eq!(output, Err(ApiError::NotAnInitialMember));
+        assert!(self.common.sut.drain_envelopes().next().is_none());
+    }
+
+    // Reset the SUT and have it learn its share again
+    pub fn reset_and_relearn(&mut self) {
+        self.common.sut.reset();
+        assert_eq!(self.common.sut.state(), &State::Uninitialized);
+        assert!(self.common.sut.drain_envelopes().next().is_none());
+        self.common.load_rack_secret_requests.clear();
+
+        // `learn_share_pkg` expects that no peers are connected when the SUT
+        // starts learning
+        let peers: Vec<_> =
+            self.common.connected_peers.iter().cloned().collect();
+        for peer in peers {
+            self.common.disconnect(peer);
+        }
+        let peer_id = self.common.initial_members.first().cloned().unwrap();
+        self.learn_share_pkg(vec![LearnAction::Success(peer_id)]);
+    }
+
     fn deliver_share_responses(&mut self, envelopes: Vec<Envelope>) {
         for envelope in envelopes {
+            if self.common.should_drop() {
+                continue;
+            }
             let share = Share(
                 self.pkgs.get(&envelope.to).unwrap().common.share.clone(),
             );
@@ -457,13 +486,19 @@ proptest! {
                     state.deliver_share_responses(envelopes);
                 }
                 Action::Disconnect(peer_id) => state.common.disconnect(peer_id),
-                Action::Ticks(ticks) => state.common.tick(ticks),
+                Action::Ticks(ticks) => {
+                    let envelopes = state.common.tick(ticks);
+                    state.deliver_share_responses(envelopes);
+                }
                 Action::GetShare(peer_id) => state.common.get_share(peer_id),
                 Action::GetShareFail(peer_id) => state.common.get_share_fail(peer_id),
                 Action::Learn(peer_id) => state.learn_attempt_must_fail(peer_id),
                 Action::ErrorResponse(peer_id, err) => {
                     state.common.handle_error_response(peer_id, err)
                 }
+                Action::AddMember(peer_id) => state.add_member_must_fail(peer_id),
+                Action::RackReset => state.reset_and_relearn(),
+                Action::DropMessages(n) => state.common.drop_messages(n),
             }
         }
 
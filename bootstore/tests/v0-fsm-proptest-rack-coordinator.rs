@@ -10,14 +10,15 @@
 //! connect a short time after `Fsm::rack_init` is called.
 //!
 //! After rack init succeeds various api calls will be generated to exercise
-//! the Fsm.
+//! the Fsm. This includes resetting the rack, after which the SUT initializes
+//! a new rack.
 
 mod common;
 
 use assert_matches::assert_matches;
 use bootstore::schemes::v0::{
     ApiError, ApiOutput, Envelope, Fsm, FsmConfig, Msg, MsgError, RackUuid,
-    Request, RequestType, Response, ResponseType, Share,
+    Request, RequestType, Response, ResponseType, Share, State,
 };
 use proptest::prelude::*;
 use sled_hardware_types::Baseboard;
@@ -122,7 +123,8 @@ pub struct TestState {
     // Generated Learn requests triggered by `Action::Learn`
     learn_requests: BTreeMap<Uuid, TestRequest>,
 
-    // Learners that have already learned their shares
+    // Learners that have already learned their shares, and members added
+    // after rack init, which are handed out shares in the same manner
     already_learned: BTreeSet<Baseboard>,
 
     // The number of "extra" shares per initial member sled
@@ -180,15 +182,26 @@ impl TestState {
         }
     }
 
+    // Initial members that are connected to the SUT. Learners and members
+    // added after rack init may be connected as well.
+    fn connected_initial_members(&self) -> BTreeSet<Baseboard> {
+        self.common
+            .connected_peers
+            .intersection(&self.common.initial_members)
+            .cloned()
+            .collect()
+    }
+
     fn check_rack_init_output(
         &self,
         result: Result<(), ApiError>,
         envelopes: &Vec<Envelope>,
     ) {
         assert!(result.is_ok());
-        assert_eq!(self.common.connected_peers.len(), envelopes.len());
+        let connected = self.connected_initial_members();
+        assert_eq!(connected.len(), envelopes.len());
         for envelope in envelopes {
-            assert!(self.common.connected_peers.contains(&envelope.to));
+            assert!(connected.contains(&envelope.to));
             assert_matches!(
                 &envelope.msg,
                 &Msg::Req(Request { type_: RequestType::Init(_), .. })
@@ -228,7 +241,7 @@ impl TestState {
                 .into();
             let output = self.common.sut.handle_msg(self.common.now, to, ack);
             if i == total - 1
-                && self.common.connected_peers.len()
+                && self.connected_initial_members().len()
                     == self.common.initial_members.len() - 1
             {
                 assert_matches!(output, Ok(Some(ApiOutput::RackInitComplete)));
@@ -238,6 +251,36 @@ impl TestState {
         }
     }
 
+    // Reset the SUT and initialize a new rack. Any disconnected initial
+    // members are connected so that rack init completes.
+    pub fn rack_reset(&mut self) {
+        self.common.sut.reset();
+        assert_eq!(self.common.sut.state(), &State::Uninitialized);
+        assert!(self.common.sut.drain_envelopes().next().is_none());
+
+        // Forget everything about the prior rack
+        self.common.rack_uuid = Uuid::new_v4().into();
+        self.common.load_rack_secret_requests.clear();
+        self.common.add_member_requests.clear();
+        self.rack_init_started = false;
+        self.shares.clear();
+        self.learn_requests.clear();
+        self.already_learned.clear();
+
+        let connected = self.connected_initial_members();
+        let mut actions = vec![RackInitAction::RackInit];
+        actions.extend(
+            self.common
+                .initial_members
+                .iter()
+                .skip(1)
+                .filter(|peer| !connected.contains(*peer))
+                .cloned()
+                .map(RackInitAction::Connect),
+        );
+        self.init_rack(actions);
+    }
+
     pub fn learn(&mut self, peer_id: Baseboard) {
         let request_id = Uuid::new_v4();
         self.learn_requests
@@ -250,9 +293,101 @@ impl TestState {
         self.deliver_share_responses(envelopes);
     }
 
+    pub fn add_member(&mut self, member: Baseboard) {
+        // Connect the member first so that its package can be delivered
+        if !self.common.connected_peers.contains(&member) {
+            let (result, envelopes) = self.common.connect(member.clone());
+            self.check_connect_output(&member, result, &envelopes);
+            self.deliver_share_responses(envelopes);
+        }
+
+        let result =
+            self.common.sut.add_member(self.common.now, member.clone());
+        if !self.common.add_member_requests.is_empty() {
+            assert_eq!(result, Err(ApiError::AddMemberInProgress));
+            assert!(self.common.sut.drain_envelopes().next().is_none());
+            return;
+        }
+        let request_id = result.unwrap();
+        self.common
+            .add_member_requests
+            .insert(request_id, (member, TestRequest::new(self.common.now)));
+        let envelopes = self.common.sut.drain_envelopes().collect();
+        self.common.expect_get_share_broadcast(&envelopes);
+        self.deliver_share_responses(envelopes);
+    }
+
+    // A threshold of shares was received for an `Fsm::add_member` request, so
+    // the SUT should send a package to the new member if it has a share to
+    // spare. Ack the package and record the new member's share.
+    fn complete_add_member(
+        &mut self,
+        request_id: Uuid,
+        member: Baseboard,
+        output: Result<Option<ApiOutput>, ApiError>,
+    ) {
+        let envelopes: Vec<_> = self.common.sut.drain_envelopes().collect();
+
+        // Have we handed out all our shares?
+        if self.already_learned.len() == self.encrypted_shares_per_sled
+            && !self.already_learned.contains(&member)
+        {
+            assert_eq!(output, Err(ApiError::CannotSpareAShare));
+            assert!(envelopes.is_empty());
+            return;
+        }
+        if self.already_learned.contains(&member) {
+            // We don't persist (inform the api) if the share has already been
+            // handed out
+            assert_eq!(output, Ok(None));
+        } else {
+            assert_eq!(
+                output,
+                Ok(Some(ApiOutput::ShareDistributedToMember {
+                    request_id,
+                    member: member.clone()
+                }))
+            );
+            self.already_learned.insert(member.clone());
+        }
+
+        assert_eq!(envelopes.len(), 1);
+        let Envelope {
+            to,
+            msg: Msg::Req(Request { id, type_: RequestType::AddMember(pkg) }),
+        } = &envelopes[0]
+        else {
+            panic!("expected an add member request: {:?}", envelopes[0]);
+        };
+        assert_eq!(to, &member);
+        assert_eq!(*id, request_id);
+        assert_eq!(RackUuid(pkg.common.rack_uuid), self.common.rack_uuid);
+
+        let ack =
+            Response { request_id, type_: ResponseType::AddMemberAck }.into();
+        let output =
+            self.common.sut.handle_msg(self.common.now, member.clone(), ack);
+        assert_eq!(
+            output,
+            Ok(Some(ApiOutput::MemberAdded {
+                request_id,
+                member: member.clone()
+            }))
+        );
+        assert!(self.common.sut.drain_envelopes().next().is_none());
+        self.shares.insert(member, Share(pkg.common.share.clone()));
+    }
+
     fn deliver_share_responses(&mut self, envelopes: Vec<Envelope>) {
         for envelope in envelopes {
-            let share = self.shares.get(&envelope.to).unwrap().clone();
+            if self.common.should_drop() {
+                continue;
+            }
+            // Connected learners that haven't been added as members don't
+            // have a share to respond with
+            let Some(share) = self.shares.get(&envelope.to).cloned() else {
+                continue;
+            };
             let request_id = envelope.msg.request_id();
             let rsp =
                 Response { request_id, type_: ResponseType::Share(share) }
@@ -331,6 +466,23 @@ impl TestState {
                     assert_matches!(output, Ok(None));
                     assert!(self.common.sut.drain_envelopes().next().is_none());
                 }
+            } else if let Some((_, test_req)) =
+                self.common.add_member_requests.get_mut(&request_id)
+            {
+                test_req.acks.insert(envelope.to);
+                // We don't count the SUT, which has its own share
+                if test_req.acks.len() == self.common.threshold - 1 {
+                    let (member, _) = self
+                        .common
+                        .add_member_requests
+                        .remove(&request_id)
+                        .unwrap();
+                    self.complete_add_member(request_id, member, output);
+                } else {
+                    // We don't have a threshold yet
+                    assert_matches!(output, Ok(None));
+                    assert!(self.common.sut.drain_envelopes().next().is_none());
+                }
             } else {
                 // These are extra shares (after the threshold is reached)
                 assert_matches!(output, Ok(None));
@@ -359,6 +511,10 @@ impl TestState {
                 self.common.load_rack_secret_requests.get(&request_id)
             {
                 assert!(!test_req.acks.contains(&envelope.to));
+            } else if let Some((_, test_req)) =
+                self.common.add_member_requests.get(&request_id)
+            {
+                assert!(!test_req.acks.contains(&envelope.to));
             } else {
                 assert!(!self.learn_requests[&request_id]
                     .acks
@@ -395,13 +551,19 @@ proptest! {
                     state.deliver_share_responses(envelopes);
                 }
                 Action::Disconnect(peer_id) => state.common.disconnect(peer_id),
-                Action::Ticks(ticks) => state.common.tick(ticks),
+                Action::Ticks(ticks) => {
+                    let envelopes = state.common.tick(ticks);
+                    state.deliver_share_responses(envelopes);
+                }
                 Action::GetShare(peer_id) => state.common.get_share(peer_id),
                 Action::GetShareFail(peer_id) => state.common.get_share_fail(peer_id),
                 Action::Learn(peer_id) => state.learn(peer_id),
                 Action::ErrorResponse(peer_id, err) => {
                     state.common.handle_error_response(peer_id, err)
                 }
+                Action::AddMember(peer_id) => state.add_member(peer_id),
+                Action::RackReset => state.rack_reset(),
+                Action::DropMessages(n) => state.common.drop_messages(n),
             }
         }
     }
@@ -31,6 +31,7 @@ use chrono::SecondsFormat;
 use chrono::Utc;
 use clap::ArgAction;
 use clap::Args;
+use clap::ColorChoice;
 use clap::Subcommand;
 use clap::ValueEnum;
 use diesel::expression::SelectableHelper;
@@ -74,6 +75,7 @@ use nexus_db_model::RegionReplacementStepType;
 use nexus_db_model::RegionSnapshot;
 use nexus_db_model::RegionSnapshotReplacement;
 use nexus_db_model::RegionSnapshotReplacementState;
+use nexus_db_model::RouterRoute;
 use nexus_db_model::Sled;
 use nexus_db_model::Snapshot;
 use nexus_db_model::SnapshotState;
@@ -110,9 +112,12 @@ use nexus_types::external_api::views::SledState;
 use nexus_types::identity::Resource;
 use nexus_types::internal_api::params::DnsRecord;
 use nexus_types::internal_api::params::Srv;
+use nexus_types::inventory::BaseboardId;
+use nexus_types::inventory::CabooseFound;
 use nexus_types::inventory::CabooseWhich;
 use nexus_types::inventory::Collection;
 use nexus_types::inventory::RotPageWhich;
+use omicron_common::api::external::ByteCount;
 use omicron_common::api::external::DataPageParams;
 use omicron_common::api::external::Generation;
 use omicron_common::api::external::InstanceState;
@@ -121,6 +126,9 @@ use omicron_uuid_kinds::CollectionUuid;
 use omicron_uuid_kinds::GenericUuid;
 use omicron_uuid_kinds::PropolisUuid;
 use omicron_uuid_kinds::SledUuid;
+use owo_colors::OwoColorize;
+use owo_colors::Style;
+use serde::Serialize;
 use sled_agent_client::types::VolumeConstructionRequest;
 use std::borrow::Cow;
 use std::cmp::Ordering;
@@ -132,13 +140,16 @@ use std::fmt::Display;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 use strum::IntoEnumIterator;
+use supports_color::Stream;
 use tabled::Tabled;
 use uuid::Uuid;
 
 const NO_ACTIVE_PROPOLIS_MSG: &str = "<no active Propolis>";
 const NOT_ON_SLED_MSG: &str = "<not on any sled>";
 
+#[derive(Serialize)]
 struct MaybePropolisId(Option<PropolisUuid>);
+#[derive(Serialize)]
 struct MaybeSledId(Option<SledUuid>);
 
 impl From<&InstanceAndActiveVmm> for MaybePropolisId {
@@ -283,6 +294,69 @@ pub struct DbFetchOptions {
         help_heading = DATABASE_OPTIONS_HEADING,
     )]
     include_deleted: bool,
+
+    /// output format for commands that list rows
+    #[clap(
+        long,
+        value_enum,
+        default_value_t = DbOutputFormat::Table,
+        global = true,
+        help_heading = DATABASE_OPTIONS_HEADING,
+    )]
+    format: DbOutputFormat,
+
+    /// whether to colorize output (e.g., added and removed DNS names and
+    /// inventory warnings)
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        global = true,
+        help_heading = DATABASE_OPTIONS_HEADING,
+    )]
+    color: ColorChoice,
+}
+
+impl DbFetchOptions {
+    /// Returns the styles to use for output written to `stream`
+    fn styles(&self, stream: Stream) -> Styles {
+        let mut styles = Styles::default();
+        let use_color = match self.color {
+            ColorChoice::Auto => supports_color::on_cached(stream).is_some(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        if use_color {
+            styles.colorize();
+        }
+        styles
+    }
+}
+
+/// Styles used to highlight parts of the output
+///
+/// By default, these are all plain (no color).
+#[derive(Clone, Debug, Default)]
+struct Styles {
+    added: Style,
+    removed: Style,
+    warning: Style,
+    error: Style,
+}
+
+impl Styles {
+    fn colorize(&mut self) {
+        self.added = Style::new().green();
+        self.removed = Style::new().red();
+        self.warning = Style::new().yellow();
+        self.error = Style::new().red().bold();
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum DbOutputFormat {
+    Table,
+    Json,
 }
 
 /// Subcommands that query or update the database
@@ -308,6 +382,8 @@ enum DbCommands {
     /// Query for information about region snapshot replacements, optionally
     /// manually triggering one.
     RegionSnapshotReplacement(RegionSnapshotReplacementArgs),
+    /// Print information about region snapshots
+    RegionSnapshots(RegionSnapshotsArgs),
     /// Print information about sleds
     Sleds(SledsArgs),
     /// Print information about customer instances
@@ -325,6 +401,58 @@ enum DbCommands {
     Volumes(VolumeArgs),
 }
 
+impl DbCommands {
+    /// Returns whether this command honours `--format json`
+    ///
+    /// Only commands that list rows support JSON output.  Everything else
+    /// rejects it rather than silently printing tables.
+    fn supports_json(&self) -> bool {
+        match self {
+            DbCommands::Rack(RackArgs { command: RackCommands::List })
+            | DbCommands::Disks(DiskArgs { command: DiskCommands::List(_) })
+            | DbCommands::Dns(DnsArgs {
+                command: DnsCommands::Show(DnsShowArgs { history: Some(_) }),
+            })
+            | DbCommands::PhysicalDisks(_)
+            | DbCommands::Region(RegionArgs {
+                command: RegionCommands::List(_),
+            })
+            | DbCommands::RegionReplacement(RegionReplacementArgs {
+                command: RegionReplacementCommands::List(_),
+            })
+            | DbCommands::RegionSnapshotReplacement(
+                RegionSnapshotReplacementArgs {
+                    command: RegionSnapshotReplacementCommands::List(_),
+                },
+            )
+            | DbCommands::RegionSnapshots(_)
+            | DbCommands::Sleds(SledsArgs { command: None, .. })
+            | DbCommands::Instances(InstancesOptions {
+                command: None, ..
+            })
+            | DbCommands::Network(_)
+            | DbCommands::Migrations(_)
+            | DbCommands::Snapshots(SnapshotArgs {
+                command: SnapshotCommands::List,
+            })
+            | DbCommands::Volumes(VolumeArgs {
+                command: VolumeCommands::List,
+            }) => true,
+            DbCommands::Inventory(InventoryArgs { command }) => match command {
+                InventoryCommands::BaseboardIds
+                | InventoryCommands::Cabooses
+                | InventoryCommands::Collections(CollectionsArgs {
+                    command: CollectionsCommands::List,
+                })
+                | InventoryCommands::PhysicalDisks(_)
+                | InventoryCommands::RotPages => true,
+                InventoryCommands::Collections(_) => false,
+            },
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Args)]
 struct RackArgs {
     #[command(subcommand)]
@@ -348,21 +476,38 @@ enum DiskCommands {
     /// Get info for a specific disk
     Info(DiskInfoArgs),
     /// Summarize current disks
-    List,
+    List(DiskListArgs),
     /// Determine what crucible resources are on the given physical disk.
     Physical(DiskPhysicalArgs),
 }
 
+#[derive(Debug, Args)]
+struct DiskListArgs {
+    #[clap(flatten)]
+    created: CreatedTimeRangeArgs,
+}
+
 #[derive(Debug, Args)]
 struct DiskInfoArgs {
     /// The UUID of the volume
     uuid: Uuid,
+    /// Also show how much space each region uses, along with the usage of
+    /// the dataset backing it
+    #[clap(long)]
+    usage: bool,
 }
 
 #[derive(Debug, Args)]
 struct DiskPhysicalArgs {
     /// The UUID of the physical disk
-    uuid: Uuid,
+    #[arg(required_unless_present = "by_serial", conflicts_with = "by_serial")]
+    uuid: Option<Uuid>,
+    /// Find the physical disk by its serial number instead
+    #[clap(long, value_name = "SERIAL")]
+    by_serial: Option<String>,
+    /// Only consider disks on this sled when searching by serial number
+    #[clap(long, requires = "by_serial")]
+    sled: Option<SledUuid>,
 }
 
 #[derive(Debug, Args)]
@@ -374,11 +519,54 @@ struct DnsArgs {
 #[derive(Debug, Subcommand)]
 enum DnsCommands {
     /// Summarize current version of all DNS zones
-    Show,
-    /// Show what changed in a given DNS version
-    Diff(DnsVersionArgs),
+    Show(DnsShowArgs),
+    /// Show what changed in a given DNS version (or range of versions)
+    Diff(DnsDiffArgs),
     /// Show the full contents of a given DNS zone and version
-    Names(DnsVersionArgs),
+    Names(DnsNamesArgs),
+}
+
+#[derive(Debug, Args)]
+struct DnsShowArgs {
+    /// list the last N versions of each DNS group instead
+    #[clap(long, value_name = "N")]
+    history: Option<NonZeroU32>,
+}
+
+#[derive(Debug, Args)]
+struct DnsDiffArgs {
+    /// name of a DNS group
+    #[arg(value_enum)]
+    group: CliDnsGroup,
+    /// version of the group's data
+    #[arg(required_unless_present = "from", conflicts_with = "from")]
+    version: Option<u32>,
+    /// show the net changes made after this version (requires --to)
+    #[clap(long, requires = "to")]
+    from: Option<u32>,
+    /// show the net changes made up to and including this version
+    #[clap(long, requires = "from")]
+    to: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+struct DnsNamesArgs {
+    #[clap(flatten)]
+    version: DnsVersionArgs,
+    /// print records in zone-file syntax (as used by BIND and similar)
+    #[clap(long)]
+    server_format: bool,
+    /// print only the records for this exact name
+    #[clap(long, value_name = "NAME")]
+    resolve: Option<String>,
+    /// print the names added, removed, or changed between this version and
+    /// the requested one
+    #[clap(
+        long,
+        value_name = "VERSION",
+        conflicts_with_all = ["resolve", "server_format"],
+    )]
+    diff_against: Option<u32>,
 }
 
 #[derive(Debug, Args)]
@@ -407,9 +595,75 @@ impl CliDnsGroup {
 
 #[derive(Debug, Args)]
 struct InstancesOptions {
+    #[command(subcommand)]
+    command: Option<InstancesCommands>,
+
     /// Only show the running instances
     #[arg(short, long, action=ArgAction::SetTrue)]
     running: bool,
+
+    #[clap(flatten)]
+    created: CreatedTimeRangeArgs,
+}
+
+/// Limits a listing to objects created within a time range
+#[derive(Debug, Args)]
+struct CreatedTimeRangeArgs {
+    /// Only show objects created at or after this time (RFC 3339, e.g.
+    /// 2024-06-01T12:00:00Z)
+    #[clap(long, value_parser = parse_rfc3339)]
+    created_after: Option<DateTime<Utc>>,
+
+    /// Only show objects created before this time (RFC 3339, e.g.
+    /// 2024-06-01T12:00:00Z)
+    #[clap(long, value_parser = parse_rfc3339)]
+    created_before: Option<DateTime<Utc>>,
+}
+
+impl CreatedTimeRangeArgs {
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        if let (Some(after), Some(before)) =
+            (self.created_after, self.created_before)
+        {
+            if after >= before {
+                bail!(
+                    "--created-after ({}) must be earlier than \
+                     --created-before ({})",
+                    after.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    before.to_rfc3339_opts(SecondsFormat::Secs, true),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_rfc3339(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s).map(|t| t.with_timezone(&Utc)).map_err(
+        |error| {
+            format!(
+                "expected an RFC 3339 timestamp \
+                 (like \"2024-06-01T12:00:00Z\"): {}",
+                error
+            )
+        },
+    )
+}
+
+#[derive(Debug, Subcommand)]
+enum InstancesCommands {
+    /// Show details about a particular instance
+    Info(InstanceInfoArgs),
+}
+
+#[derive(Debug, Args)]
+struct InstanceInfoArgs {
+    /// id of the instance
+    instance_id: Uuid,
+
+    /// also list the instance's VMMs and migrations, most recent first
+    #[clap(long)]
+    events: bool,
 }
 
 #[derive(Debug, Args)]
@@ -444,6 +698,8 @@ enum CollectionsCommands {
     List,
     /// show what was found in a particular collection
     Show(CollectionsShowArgs),
+    /// show how the devices found differ between two collections
+    Diff(CollectionsDiffArgs),
 }
 
 #[derive(Debug, Args)]
@@ -455,6 +711,14 @@ struct CollectionsShowArgs {
     show_long_strings: bool,
 }
 
+#[derive(Debug, Args)]
+struct CollectionsDiffArgs {
+    /// id of the earlier collection
+    id1: CollectionUuid,
+    /// id of the later collection
+    id2: CollectionUuid,
+}
+
 #[derive(Debug, Args, Clone, Copy)]
 struct InvPhysicalDisksArgs {
     #[clap(long)]
@@ -479,11 +743,26 @@ struct ReconfiguratorSaveArgs {
 
 #[derive(Debug, Args)]
 struct SledsArgs {
+    #[command(subcommand)]
+    command: Option<SledsCommands>,
+
     /// Show sleds that match the given filter
     #[clap(short = 'F', long, value_enum)]
     filter: Option<SledFilter>,
 }
 
+#[derive(Debug, Subcommand)]
+enum SledsCommands {
+    /// Show details about a particular sled
+    Show(SledShowArgs),
+}
+
+#[derive(Debug, Args)]
+struct SledShowArgs {
+    /// id of the sled
+    sled_id: SledUuid,
+}
+
 #[derive(Debug, Args)]
 struct RegionArgs {
     #[command(subcommand)]
@@ -510,6 +789,37 @@ struct RegionListArgs {
     /// Print region IDs only
     #[arg(short)]
     id_only: bool,
+
+    /// Only list regions that are part of this volume
+    #[arg(long)]
+    volume_id: Option<Uuid>,
+
+    /// Only list regions that are allocated on this dataset
+    #[arg(long)]
+    dataset_id: Option<Uuid>,
+}
+
+#[derive(Debug, Args)]
+struct RegionSnapshotsArgs {
+    #[command(subcommand)]
+    command: RegionSnapshotsCommands,
+}
+
+#[derive(Debug, Subcommand)]
+enum RegionSnapshotsCommands {
+    /// List region snapshots
+    List(RegionSnapshotsListArgs),
+}
+
+#[derive(Debug, Args)]
+struct RegionSnapshotsListArgs {
+    /// Only show region snapshots on this dataset
+    #[clap(long)]
+    dataset: Option<Uuid>,
+
+    /// Only show region snapshots for this snapshot
+    #[clap(long)]
+    snapshot: Option<Uuid>,
 }
 
 #[derive(Debug, Args)]
@@ -571,9 +881,34 @@ struct NetworkArgs {
 #[derive(Debug, Subcommand)]
 enum NetworkCommands {
     /// List external IPs
-    ListEips,
+    ListEips(EipsArgs),
     /// List virtual network interfaces
+    #[clap(alias = "list-nics")]
     ListVnics,
+    /// List VPC router routes
+    ListRoutes(RoutesArgs),
+}
+
+#[derive(Debug, Args)]
+struct EipsArgs {
+    /// Only show external IPs belonging to this instance
+    #[clap(long, conflicts_with = "owner_service")]
+    owner_instance: Option<Uuid>,
+
+    /// Only show external IPs belonging to this service
+    #[clap(long)]
+    owner_service: Option<Uuid>,
+}
+
+#[derive(Debug, Args)]
+struct RoutesArgs {
+    /// Only show routes belonging to this VPC router
+    #[clap(long, conflicts_with = "vpc")]
+    router: Option<Uuid>,
+
+    /// Only show routes belonging to routers in this VPC
+    #[clap(long)]
+    vpc: Option<Uuid>,
 }
 
 #[derive(Debug, Args)]
@@ -738,6 +1073,7 @@ struct VolumeArgs {
 #[derive(Debug, Subcommand)]
 enum VolumeCommands {
     /// Get info for a specific volume
+    #[clap(alias = "show")]
     Info(VolumeInfoArgs),
     /// Summarize current volumes
     List,
@@ -756,6 +1092,23 @@ impl DbArgs {
         omdb: &Omdb,
         log: &slog::Logger,
     ) -> Result<(), anyhow::Error> {
+        // Reject arguments that clap can't check for us before bothering to
+        // connect to the database.
+        match &self.command {
+            DbCommands::Disks(DiskArgs {
+                command: DiskCommands::List(args),
+            }) => args.created.validate()?,
+            DbCommands::Instances(instances_options) => {
+                instances_options.created.validate()?
+            }
+            _ => (),
+        }
+        if let DbOutputFormat::Json = self.fetch_opts.format {
+            if !self.command.supports_json() {
+                bail!("--format json is not supported for this command");
+            }
+        }
+
         let datastore = self.db_url_opts.connect(omdb, log).await?;
         let opctx = OpContext::for_tests(log.clone(), datastore.clone());
         match &self.command {
@@ -765,17 +1118,30 @@ impl DbArgs {
             DbCommands::Disks(DiskArgs {
                 command: DiskCommands::Info(uuid),
             }) => cmd_db_disk_info(&opctx, &datastore, uuid).await,
-            DbCommands::Disks(DiskArgs { command: DiskCommands::List }) => {
-                cmd_db_disk_list(&datastore, &self.fetch_opts).await
-            }
+            DbCommands::Disks(DiskArgs {
+                command: DiskCommands::List(args),
+            }) => cmd_db_disk_list(&datastore, &self.fetch_opts, args).await,
             DbCommands::Disks(DiskArgs {
                 command: DiskCommands::Physical(uuid),
             }) => {
                 cmd_db_disk_physical(&opctx, &datastore, &self.fetch_opts, uuid)
                     .await
             }
-            DbCommands::Dns(DnsArgs { command: DnsCommands::Show }) => {
-                cmd_db_dns_show(&opctx, &datastore, &self.fetch_opts).await
+            DbCommands::Dns(DnsArgs { command: DnsCommands::Show(args) }) => {
+                match args.history {
+                    Some(history) => {
+                        cmd_db_dns_history(
+                            &datastore,
+                            &self.fetch_opts,
+                            history,
+                        )
+                        .await
+                    }
+                    None => {
+                        cmd_db_dns_show(&opctx, &datastore, &self.fetch_opts)
+                            .await
+                    }
+                }
             }
             DbCommands::Dns(DnsArgs { command: DnsCommands::Diff(args) }) => {
                 cmd_db_dns_diff(&opctx, &datastore, &self.fetch_opts, args)
@@ -871,24 +1237,47 @@ impl DbArgs {
                 )
                 .await
             }
+            DbCommands::RegionSnapshots(RegionSnapshotsArgs {
+                command: RegionSnapshotsCommands::List(args),
+            }) => {
+                cmd_db_region_snapshots_list(&datastore, &self.fetch_opts, args)
+                    .await
+            }
+            DbCommands::Sleds(SledsArgs {
+                command: Some(SledsCommands::Show(args)),
+                ..
+            }) => cmd_db_sled_show(&opctx, &datastore, args).await,
             DbCommands::Sleds(args) => {
                 cmd_db_sleds(&opctx, &datastore, &self.fetch_opts, args).await
             }
+            DbCommands::Instances(InstancesOptions {
+                command: Some(InstancesCommands::Info(args)),
+                ..
+            }) => {
+                cmd_db_instance_info(&datastore, &self.fetch_opts, args).await
+            }
             DbCommands::Instances(instances_options) => {
                 cmd_db_instances(
                     &opctx,
                     &datastore,
                     &self.fetch_opts,
                     instances_options.running,
+                    &instances_options.created,
                 )
                 .await
             }
             DbCommands::Network(NetworkArgs {
-                command: NetworkCommands::ListEips,
+                command: NetworkCommands::ListEips(args),
                 verbose,
             }) => {
-                cmd_db_eips(&opctx, &datastore, &self.fetch_opts, *verbose)
-                    .await
+                cmd_db_eips(
+                    &opctx,
+                    &datastore,
+                    &self.fetch_opts,
+                    args,
+                    *verbose,
+                )
+                .await
             }
             DbCommands::Network(NetworkArgs {
                 command: NetworkCommands::ListVnics,
@@ -901,6 +1290,18 @@ impl DbArgs {
                 )
                 .await
             }
+            DbCommands::Network(NetworkArgs {
+                command: NetworkCommands::ListRoutes(args),
+                verbose,
+            }) => {
+                cmd_db_network_list_routes(
+                    &datastore,
+                    &self.fetch_opts,
+                    args,
+                    *verbose,
+                )
+                .await
+            }
             DbCommands::Migrations(MigrationsArgs {
                 command: MigrationsCommands::List(args),
             }) => {
@@ -1048,6 +1449,21 @@ where
     );
 }
 
+/// Prints `rows` to stdout as a JSON array
+///
+/// This is used in place of a table when `--format json` is given.  Warnings
+/// (like those from [`check_limit()`]) go to stderr so that stdout remains
+/// machine-parseable.
+fn print_json<T: Serialize>(
+    rows: impl IntoIterator<Item = T>,
+) -> Result<(), anyhow::Error> {
+    let rows: Vec<_> = rows.into_iter().collect();
+    let json =
+        serde_json::to_string_pretty(&rows).context("serializing rows")?;
+    println!("{}", json);
+    Ok(())
+}
+
 /// Returns pagination parameters to fetch the first page of results for a
 /// paginated endpoint
 fn first_page<'a, T>(limit: NonZeroU32) -> DataPageParams<'a, T> {
@@ -1162,8 +1578,9 @@ async fn lookup_project(
 async fn cmd_db_disk_list(
     datastore: &DataStore,
     fetch_opts: &DbFetchOptions,
+    args: &DiskListArgs,
 ) -> Result<(), anyhow::Error> {
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct DiskRow {
         name: String,
@@ -1180,6 +1597,12 @@ async fn cmd_db_disk_list(
     if !fetch_opts.include_deleted {
         query = query.filter(dsl::time_deleted.is_null());
     }
+    if let Some(after) = args.created.created_after {
+        query = query.filter(dsl::time_created.ge(after));
+    }
+    if let Some(before) = args.created.created_before {
+        query = query.filter(dsl::time_created.lt(before));
+    }
 
     let disks = query
         .limit(i64::from(u32::from(fetch_opts.fetch_limit)))
@@ -1200,6 +1623,10 @@ async fn cmd_db_disk_list(
             None => "-".to_string(),
         },
     });
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -1216,7 +1643,7 @@ async fn cmd_db_rack_list(
     datastore: &DataStore,
     fetch_opts: &DbFetchOptions,
 ) -> Result<(), anyhow::Error> {
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct RackRow {
         id: String,
@@ -1244,6 +1671,10 @@ async fn cmd_db_rack_list(
             .unwrap_or_else(|| "-".to_string()),
     });
 
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -1384,7 +1815,16 @@ async fn cmd_db_disk_info(
     let regions = datastore.get_allocated_regions(disk.volume_id).await?;
 
     let mut rows = Vec::with_capacity(3);
+    let mut usage_rows = Vec::new();
     for (dataset, region) in regions {
+        if args.usage {
+            let usage_row =
+                region_usage_row(&conn, &dataset, &region).await.with_context(
+                    || format!("computing usage of region {}", region.id()),
+                )?;
+            usage_rows.push(usage_row);
+        }
+
         let my_pool_id = dataset.pool_id;
         let (_, my_zpool) = LookupPath::new(opctx, datastore)
             .zpool_id(my_pool_id)
@@ -1415,10 +1855,82 @@ async fn cmd_db_disk_info(
 
     println!("{}", table);
 
+    if args.usage {
+        let table = tabled::Table::new(usage_rows)
+            .with(tabled::settings::Style::empty())
+            .with(tabled::settings::Padding::new(0, 1, 0, 0))
+            .to_string();
+        println!("\nREGION USAGE\n{}", table);
+    }
+
     get_and_display_vcr(disk.volume_id, datastore).await?;
     Ok(())
 }
 
+// The row describing the space used by a region (for `disk info --usage`)
+#[derive(Tabled)]
+#[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
+struct RegionUsageRow {
+    region: Uuid,
+    block_size: ByteCount,
+    blocks_per_extent: u64,
+    extent_count: u64,
+    region_size: ByteCount,
+    dataset: Uuid,
+    #[tabled(display_with = "display_option_blank")]
+    dataset_used: Option<ByteCount>,
+    #[tabled(display_with = "display_option_blank")]
+    zpool_total: Option<ByteCount>,
+}
+
+/// Describes how much space `region` takes up and, where known, how full the
+/// dataset and zpool backing it are
+///
+/// The zpool's total size comes from the most recent inventory collection that
+/// reported it.
+async fn region_usage_row(
+    conn: &DataStoreConnection,
+    dataset: &Dataset,
+    region: &Region,
+) -> Result<RegionUsageRow, anyhow::Error> {
+    use db::schema::inv_zpool::dsl;
+    let zpool_total = dsl::inv_zpool
+        .filter(dsl::id.eq(dataset.pool_id))
+        .order_by(dsl::time_collected.desc())
+        .select(dsl::total_size)
+        .first_async::<i64>(&**conn)
+        .await
+        .optional()
+        .context("loading zpool size from inventory")?
+        .map(ByteCount::try_from)
+        .transpose()
+        .context("parsing zpool size")?;
+    let dataset_used = dataset
+        .size_used
+        .map(ByteCount::try_from)
+        .transpose()
+        .context("parsing dataset size")?;
+
+    let block_size = region.block_size();
+    let region_size = ByteCount::try_from(
+        block_size.to_bytes()
+            * region.blocks_per_extent()
+            * region.extent_count(),
+    )
+    .context("computing region size")?;
+
+    Ok(RegionUsageRow {
+        region: region.id(),
+        block_size,
+        blocks_per_extent: region.blocks_per_extent(),
+        extent_count: region.extent_count(),
+        region_size,
+        dataset: dataset.id(),
+        dataset_used,
+        zpool_total,
+    })
+}
+
 // Given a UUID, search the database for a volume with that ID
 // If found, attempt to parse the .data field into a VolumeConstructionRequest
 // and display it if successful.
@@ -1459,6 +1971,49 @@ async fn cmd_db_disk_physical(
 ) -> Result<(), anyhow::Error> {
     let conn = datastore.pool_connection_for_tests().await?;
 
+    let disk_id = match (args.uuid, &args.by_serial) {
+        (Some(uuid), None) => uuid,
+        (None, Some(serial)) => {
+            use db::schema::physical_disk::dsl;
+            let mut query = dsl::physical_disk
+                .filter(dsl::serial.eq(serial.clone()))
+                .into_boxed();
+            if !fetch_opts.include_deleted {
+                query = query.filter(dsl::time_deleted.is_null());
+            }
+            if let Some(sled_id) = args.sled {
+                query =
+                    query.filter(dsl::sled_id.eq(sled_id.into_untyped_uuid()));
+            }
+            let disks = query
+                .select(PhysicalDisk::as_select())
+                .load_async(&*conn)
+                .await
+                .context("loading physical disks by serial")?;
+            match disks.as_slice() {
+                [] => bail!("no physical disk found with serial {:?}", serial),
+                [disk] => disk.id(),
+                _ => bail!(
+                    "serial {:?} matches {} physical disks ({}); \
+                     use --sled or the disk's UUID",
+                    serial,
+                    disks.len(),
+                    disks
+                        .iter()
+                        .map(|disk| format!(
+                            "{} on sled {}",
+                            disk.id(),
+                            disk.sled_id
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            }
+        }
+        // clap enforces that exactly one of these forms is used.
+        _ => unreachable!("unexpected arguments: {:?}", args),
+    };
+
     // We start by finding any zpools that are using the physical disk.
     use db::schema::zpool::dsl as zpool_dsl;
     let mut query = zpool_dsl::zpool.into_boxed();
@@ -1467,7 +2022,7 @@ async fn cmd_db_disk_physical(
     }
 
     let zpools = query
-        .filter(zpool_dsl::physical_disk_id.eq(args.uuid))
+        .filter(zpool_dsl::physical_disk_id.eq(disk_id))
         .select(Zpool::as_select())
         .load_async(&*conn)
         .await
@@ -1477,7 +2032,7 @@ async fn cmd_db_disk_physical(
     let mut dataset_ids = HashSet::new();
 
     if zpools.is_empty() {
-        println!("Found no zpools on physical disk UUID {}", args.uuid);
+        println!("Found no zpools on physical disk UUID {}", disk_id);
         return Ok(());
     }
 
@@ -1519,7 +2074,7 @@ async fn cmd_db_disk_physical(
 
         println!(
             "Physical disk: {} found on sled: {}",
-            args.uuid,
+            disk_id,
             my_sled.serial_number()
         );
     }
@@ -1689,7 +2244,7 @@ async fn cmd_db_disk_physical(
     Ok(())
 }
 
-#[derive(Tabled)]
+#[derive(Serialize, Tabled)]
 #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
 struct PhysicalDiskRow {
     id: Uuid,
@@ -1741,6 +2296,10 @@ async fn cmd_db_physical_disks(
     check_limit(&sleds, limit, || String::from("listing physical disks"));
 
     let rows = sleds.into_iter().map(|s| PhysicalDiskRow::from(s));
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(1, 1, 0, 0))
@@ -1764,11 +2323,12 @@ fn format_snapshot(state: &SnapshotState) -> impl Display {
 }
 
 // The row describing the snapshot
-#[derive(Tabled)]
+#[derive(Serialize, Tabled)]
 #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
 struct SnapshotRow {
     snap_name: String,
     id: String,
+    project_id: String,
     state: String,
     size: String,
     source_disk_id: String,
@@ -1781,6 +2341,7 @@ impl From<Snapshot> for SnapshotRow {
         SnapshotRow {
             snap_name: s.name().to_string(),
             id: s.id().to_string(),
+            project_id: s.project_id.to_string(),
             state: format_snapshot(&s.state).to_string(),
             size: s.size.to_string(),
             source_disk_id: s.disk_id.to_string(),
@@ -1815,6 +2376,10 @@ async fn cmd_db_snapshot_list(
 
     let rows =
         snapshots.into_iter().map(|snapshot| SnapshotRow::from(snapshot));
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -1921,13 +2486,14 @@ async fn cmd_db_volume_list(
     datastore: &DataStore,
     fetch_opts: &DbFetchOptions,
 ) -> Result<(), anyhow::Error> {
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct VolumeRow {
         id: String,
         created: String,
         modified: String,
         deleted: String,
+        summary: String,
     }
 
     let ctx = || "listing volumes".to_string();
@@ -1955,7 +2521,15 @@ async fn cmd_db_volume_list(
             Some(time) => time.to_string(),
             None => "NULL".to_string(),
         },
+        summary: match serde_json::from_str(&volume.data()) {
+            Ok(vcr) => vcr_summary(&vcr),
+            Err(e) => format!("invalid VCR: {e}"),
+        },
     });
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -2024,6 +2598,58 @@ async fn cmd_db_volume_info(
     Ok(())
 }
 
+/// Returns a one-line description of a VolumeConstructionRequest for
+/// `omdb db volumes list`
+fn vcr_summary(vcr: &VolumeConstructionRequest) -> String {
+    match vcr {
+        VolumeConstructionRequest::Volume {
+            sub_volumes,
+            read_only_parent,
+            ..
+        } => {
+            let mut summary = format!(
+                "volume: {} sub volume(s), {} region(s)",
+                sub_volumes.len(),
+                vcr_region_count(vcr),
+            );
+            if read_only_parent.is_some() {
+                summary.push_str(", read only parent");
+            }
+            summary
+        }
+        VolumeConstructionRequest::Region { opts, .. } => {
+            let mut summary =
+                format!("region: {} target(s)", opts.target.len());
+            if opts.read_only {
+                summary.push_str(", read only");
+            }
+            summary
+        }
+        VolumeConstructionRequest::Url { url, .. } => format!("url: {url}"),
+        VolumeConstructionRequest::File { path, .. } => {
+            format!("file: {path}")
+        }
+    }
+}
+
+/// Counts the regions in a VolumeConstructionRequest, including those in any
+/// sub volumes and read only parents
+fn vcr_region_count(vcr: &VolumeConstructionRequest) -> usize {
+    match vcr {
+        VolumeConstructionRequest::Volume {
+            sub_volumes,
+            read_only_parent,
+            ..
+        } => {
+            sub_volumes.iter().map(vcr_region_count).sum::<usize>()
+                + read_only_parent.as_deref().map(vcr_region_count).unwrap_or(0)
+        }
+        VolumeConstructionRequest::Region { .. } => 1,
+        VolumeConstructionRequest::Url { .. }
+        | VolumeConstructionRequest::File { .. } => 0,
+    }
+}
+
 // Print the fields that I want to see of a VolumeConstructionRequests
 // This will call itself on all sub_volumes and read_only_parents it finds.
 // We use the pad variable to indicate how much indent we want to display
@@ -2147,14 +2773,24 @@ async fn cmd_db_region_list(
 ) -> Result<(), anyhow::Error> {
     use db::schema::region::dsl;
 
-    let regions: Vec<Region> = paginated(
+    let conn = datastore.pool_connection_for_tests().await?;
+
+    let mut query = paginated(
         dsl::region,
         dsl::id,
         &first_page::<dsl::id>(fetch_opts.fetch_limit),
-    )
-    .select(Region::as_select())
-    .load_async(&*datastore.pool_connection_for_tests().await?)
-    .await?;
+    );
+
+    if let Some(volume_id) = args.volume_id {
+        query = query.filter(dsl::volume_id.eq(volume_id));
+    }
+
+    if let Some(dataset_id) = args.dataset_id {
+        query = query.filter(dsl::dataset_id.eq(dataset_id));
+    }
+
+    let regions: Vec<Region> =
+        query.select(Region::as_select()).load_async(&*conn).await?;
 
     check_limit(&regions, fetch_opts.fetch_limit, || {
         String::from("listing regions")
@@ -2165,7 +2801,7 @@ async fn cmd_db_region_list(
             println!("{}", region.id());
         }
     } else {
-        #[derive(Tabled)]
+        #[derive(Serialize, Tabled)]
         struct RegionRow {
             id: Uuid,
             dataset_id: Uuid,
@@ -2174,21 +2810,57 @@ async fn cmd_db_region_list(
             blocks_per_extent: u64,
             extent_count: u64,
             read_only: bool,
+            #[tabled(display_with = "display_option_blank")]
+            replacement_id: Option<Uuid>,
+            #[tabled(display_with = "display_option_blank")]
+            replacement_state: Option<String>,
         }
 
+        // Look up any in-progress replacement requests for the listed
+        // regions.
+        let replacements: BTreeMap<Uuid, RegionReplacement> = {
+            use db::schema::region_replacement::dsl;
+
+            let region_ids: Vec<Uuid> =
+                regions.iter().map(|region| region.id()).collect();
+
+            dsl::region_replacement
+                .filter(dsl::old_region_id.eq_any(region_ids))
+                .filter(
+                    dsl::replacement_state.ne(RegionReplacementState::Complete),
+                )
+                .select(RegionReplacement::as_select())
+                .load_async(&*conn)
+                .await?
+                .into_iter()
+                .map(|request| (request.old_region_id, request))
+                .collect()
+        };
+
         let rows: Vec<_> = regions
             .into_iter()
-            .map(|region: Region| RegionRow {
-                id: region.id(),
-                dataset_id: region.dataset_id(),
-                volume_id: region.volume_id(),
-                block_size: region.block_size().into(),
-                blocks_per_extent: region.blocks_per_extent(),
-                extent_count: region.extent_count(),
-                read_only: region.read_only(),
+            .map(|region: Region| {
+                let replacement = replacements.get(&region.id());
+                RegionRow {
+                    id: region.id(),
+                    dataset_id: region.dataset_id(),
+                    volume_id: region.volume_id(),
+                    block_size: region.block_size().into(),
+                    blocks_per_extent: region.blocks_per_extent(),
+                    extent_count: region.extent_count(),
+                    read_only: region.read_only(),
+                    replacement_id: replacement.map(|request| request.id),
+                    replacement_state: replacement.map(|request| {
+                        format!("{:?}", request.replacement_state)
+                    }),
+                }
             })
             .collect();
 
+        if let DbOutputFormat::Json = fetch_opts.format {
+            return print_json(rows);
+        }
+
         let table = tabled::Table::new(rows)
             .with(tabled::settings::Style::psql())
             .to_string();
@@ -2493,7 +3165,7 @@ async fn cmd_db_region_replacement_list(
 
     check_limit(&requests, limit, ctx);
 
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct Row {
         pub id: Uuid,
@@ -2511,6 +3183,10 @@ async fn cmd_db_region_replacement_list(
         });
     }
 
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -2763,7 +3439,7 @@ async fn cmd_db_region_replacement_request(
 
 // SLEDS
 
-#[derive(Tabled)]
+#[derive(Serialize, Tabled)]
 #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
 struct SledRow {
     serial: String,
@@ -2771,11 +3447,12 @@ struct SledRow {
     role: &'static str,
     policy: SledPolicy,
     state: SledState,
+    physical_disks: i64,
     id: Uuid,
 }
 
-impl From<Sled> for SledRow {
-    fn from(s: Sled) -> Self {
+impl SledRow {
+    fn new(s: Sled, physical_disks: i64) -> Self {
         SledRow {
             id: s.id(),
             serial: s.serial_number().to_string(),
@@ -2783,6 +3460,7 @@ impl From<Sled> for SledRow {
             role: if s.is_scrimlet() { "scrimlet" } else { "-" },
             policy: s.policy(),
             state: s.state().into(),
+            physical_disks,
         }
     }
 }
@@ -2812,18 +3490,183 @@ async fn cmd_db_sleds(
         .context("listing sleds")?;
     check_limit(&sleds, limit, || String::from("listing sleds"));
 
-    let rows = sleds.into_iter().map(|s| SledRow::from(s));
+    // Count the physical disks that the control plane knows about on each of
+    // these sleds.
+    let sled_ids: Vec<_> = sleds.iter().map(|s| s.id()).collect();
+    let physical_disk_counts: BTreeMap<Uuid, i64> = {
+        use db::schema::physical_disk::dsl;
+        dsl::physical_disk
+            .filter(dsl::time_deleted.is_null())
+            .filter(dsl::sled_id.eq_any(sled_ids))
+            .group_by(dsl::sled_id)
+            .select((dsl::sled_id, diesel::dsl::count_star()))
+            .load_async::<(Uuid, i64)>(
+                &*datastore.pool_connection_for_tests().await?,
+            )
+            .await
+            .context("counting physical disks")?
+            .into_iter()
+            .collect()
+    };
+
+    // Expunged sleds will never have anything placed on them again, which
+    // is easy to miss in a long table.  Call them out separately.
+    let expunged: Vec<_> = sleds
+        .iter()
+        .filter(|s| matches!(s.policy(), SledPolicy::Expunged))
+        .map(|s| (s.id(), s.serial_number().to_string()))
+        .collect();
+
+    let rows = sleds.into_iter().map(|s| {
+        let physical_disks =
+            physical_disk_counts.get(&s.id()).copied().unwrap_or(0);
+        SledRow::new(s, physical_disks)
+    });
+    if let DbOutputFormat::Json = fetch_opts.format {
+        print_json(rows)?;
+    } else {
+        let table = tabled::Table::new(rows)
+            .with(tabled::settings::Style::empty())
+            .with(tabled::settings::Padding::new(1, 1, 0, 0))
+            .to_string();
+
+        println!("{}", table);
+    }
+
+    for (id, serial) in expunged {
+        eprintln!(
+            "WARNING: sled {} ({}) is expunged; nothing will be placed on it",
+            serial, id
+        );
+    }
+
+    Ok(())
+}
+
+/// Run `omdb db sleds show`.
+async fn cmd_db_sled_show(
+    opctx: &OpContext,
+    datastore: &DataStore,
+    args: &SledShowArgs,
+) -> Result<(), anyhow::Error> {
+    let (_, sled) = LookupPath::new(opctx, datastore)
+        .sled_id(args.sled_id.into_untyped_uuid())
+        .fetch()
+        .await
+        .context("failed to look up sled")?;
+
+    println!("          id: {}", sled.id());
+    println!("      serial: {}", sled.serial_number());
+    println!("     address: {}", sled.address());
+    println!(
+        "        role: {}",
+        if sled.is_scrimlet() { "scrimlet" } else { "-" }
+    );
+    println!("      policy: {}", sled.policy());
+    println!("       state: {}", SledState::from(sled.state()));
+    println!("     rack id: {}", sled.rack_id);
+    println!();
+
+    // Physical disks (and the zpools on them) that the control plane knows
+    // about for this sled
+    #[derive(Tabled)]
+    #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct DiskRow {
+        id: Uuid,
+        serial: String,
+        vendor: String,
+        model: String,
+        policy: PhysicalDiskPolicy,
+        state: PhysicalDiskState,
+        #[tabled(display_with = "display_option_blank")]
+        zpool_id: Option<Uuid>,
+    }
+
+    let disks: Vec<(PhysicalDisk, Option<Zpool>)> = {
+        use db::schema::physical_disk::dsl;
+        use db::schema::zpool::dsl as zpool_dsl;
+        dsl::physical_disk
+            .filter(dsl::sled_id.eq(sled.id()))
+            .filter(dsl::time_deleted.is_null())
+            .left_join(
+                zpool_dsl::zpool.on(zpool_dsl::physical_disk_id
+                    .eq(dsl::id)
+                    .and(zpool_dsl::time_deleted.is_null())),
+            )
+            .select((PhysicalDisk::as_select(), Option::<Zpool>::as_select()))
+            .load_async(&*datastore.pool_connection_for_tests().await?)
+            .await
+            .context("loading physical disks")?
+    };
+
+    let rows = disks.into_iter().map(|(disk, zpool)| DiskRow {
+        id: disk.id(),
+        serial: disk.serial.clone(),
+        vendor: disk.vendor.clone(),
+        model: disk.model.clone(),
+        policy: disk.disk_policy.into(),
+        state: disk.disk_state.into(),
+        zpool_id: zpool.map(|z| z.id()),
+    });
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
-        .with(tabled::settings::Padding::new(1, 1, 0, 0))
+        .with(tabled::settings::Padding::new(0, 1, 0, 0))
+        .with(tabled::settings::Panel::header("Physical disks"))
         .to_string();
-
     println!("{}", table);
+    println!();
+
+    // What the sled itself reported in the latest inventory collection
+    let Some(collection) = datastore
+        .inventory_get_latest_collection(opctx)
+        .await
+        .context("loading latest inventory collection")?
+    else {
+        println!("no inventory collections found");
+        return Ok(());
+    };
+    println!("latest inventory collection: {}", collection.id);
+    let Some(sled_agent) = collection.sled_agents.get(&args.sled_id) else {
+        println!("    sled not found in collection");
+        return Ok(());
+    };
+    println!(
+        "    found at:    {} from {}",
+        sled_agent.time_collected, sled_agent.source
+    );
+    println!("    usable hw threads:   {}", sled_agent.usable_hardware_threads);
+    println!(
+        "    usable memory (GiB): {}",
+        sled_agent.usable_physical_ram.to_whole_gibibytes()
+    );
+    println!(
+        "    reservoir (GiB):     {}",
+        sled_agent.reservoir_size.to_whole_gibibytes()
+    );
+    println!("    physical disks found: {}", sled_agent.disks.len());
+    for disk in &sled_agent.disks {
+        println!(
+            "      slot {:2}: {:?} {} {} {}",
+            disk.slot,
+            disk.variant,
+            disk.identity.vendor,
+            disk.identity.model,
+            disk.identity.serial,
+        );
+    }
+    println!("    zpools found: {}", sled_agent.zpools.len());
+    for zpool in &sled_agent.zpools {
+        println!(
+            "      {} (total size: {} GiB)",
+            zpool.id,
+            zpool.total_size.to_whole_gibibytes()
+        );
+    }
 
     Ok(())
 }
 
-#[derive(Tabled)]
+#[derive(Serialize, Tabled)]
 #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
 struct CustomerInstanceRow {
     id: String,
@@ -2840,6 +3683,7 @@ async fn cmd_db_instances(
     datastore: &DataStore,
     fetch_opts: &DbFetchOptions,
     running: bool,
+    created: &CreatedTimeRangeArgs,
 ) -> Result<(), anyhow::Error> {
     use db::schema::instance::dsl;
     use db::schema::vmm::dsl as vmm_dsl;
@@ -2849,6 +3693,12 @@ async fn cmd_db_instances(
     if !fetch_opts.include_deleted {
         query = query.filter(dsl::time_deleted.is_null());
     }
+    if let Some(after) = created.created_after {
+        query = query.filter(dsl::time_created.ge(after));
+    }
+    if let Some(before) = created.created_before {
+        query = query.filter(dsl::time_created.lt(before));
+    }
 
     let instances: Vec<InstanceAndActiveVmm> = query
         .left_join(
@@ -2909,6 +3759,10 @@ async fn cmd_db_instances(
         rows.push(cir);
     }
 
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -2919,6 +3773,156 @@ async fn cmd_db_instances(
     Ok(())
 }
 
+/// Run `omdb db instances info`.
+async fn cmd_db_instance_info(
+    datastore: &DataStore,
+    fetch_opts: &DbFetchOptions,
+    args: &InstanceInfoArgs,
+) -> Result<(), anyhow::Error> {
+    let conn = datastore.pool_connection_for_tests().await?;
+    let instance = {
+        use db::schema::instance::dsl;
+        dsl::instance
+            .filter(dsl::id.eq(args.instance_id))
+            .select(Instance::as_select())
+            .first_async(&*conn)
+            .await
+            .optional()
+            .context("loading instance")?
+            .ok_or_else(|| anyhow!("no such instance: {}", args.instance_id))?
+    };
+    let active_vmm = match instance.runtime().propolis_id {
+        Some(vmm_id) => {
+            use db::schema::vmm::dsl;
+            dsl::vmm
+                .filter(dsl::id.eq(vmm_id))
+                .select(Vmm::as_select())
+                .first_async(&*conn)
+                .await
+                .optional()
+                .context("loading active VMM")?
+        }
+        None => None,
+    };
+
+    // The instance's state history isn't recorded, so show the current
+    // runtime state (and that of its active VMM) with its generation and
+    // the time it was last updated.
+    let runtime = instance.runtime();
+    let deleted = instance.time_deleted();
+    println!("              id: {}", instance.id());
+    println!("            name: {}", instance.name());
+    println!("         deleted: {}", display_option_blank(&deleted));
+    println!("           state: {}", runtime.nexus_state);
+    println!("      generation: {}", runtime.gen.0);
+    println!("    last updated: {}", runtime.time_updated);
+    println!(
+        "      active vmm: {}",
+        display_option_blank(&runtime.propolis_id)
+    );
+    println!(
+        "      target vmm: {}",
+        display_option_blank(&runtime.dst_propolis_id)
+    );
+    println!(
+        "    migration id: {}",
+        display_option_blank(&runtime.migration_id)
+    );
+    if let Some(vmm) = &active_vmm {
+        println!("       vmm state: {}", vmm.runtime.state);
+        println!("  vmm generation: {}", vmm.runtime.gen.0);
+        println!("vmm last updated: {}", vmm.runtime.time_state_updated);
+        println!("        vmm sled: {}", vmm.sled_id);
+    }
+
+    if !args.events {
+        return Ok(());
+    }
+
+    // Each VMM created for the instance and each migration of it is recorded
+    // with its latest state, which gives a rough picture of recent history.
+    #[derive(Tabled)]
+    #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct VmmRow {
+        created: DateTime<Utc>,
+        id: Uuid,
+        sled_id: Uuid,
+        state: String,
+        #[tabled(rename = "GEN")]
+        generation: u64,
+        updated: DateTime<Utc>,
+        #[tabled(display_with = "display_option_blank")]
+        deleted: Option<DateTime<Utc>>,
+    }
+
+    let limit = fetch_opts.fetch_limit;
+    let vmms: Vec<Vmm> = {
+        use db::schema::vmm::dsl;
+        dsl::vmm
+            .filter(dsl::instance_id.eq(args.instance_id))
+            .order_by(dsl::time_created.desc())
+            .limit(i64::from(u32::from(limit)))
+            .select(Vmm::as_select())
+            .load_async(&*conn)
+            .await
+            .context("loading VMMs")?
+    };
+    check_limit(&vmms, limit, || "listing VMMs");
+    let rows = vmms.into_iter().map(|vmm| VmmRow {
+        created: vmm.time_created,
+        id: vmm.id,
+        sled_id: vmm.sled_id,
+        state: vmm.runtime.state.to_string(),
+        generation: vmm.runtime.gen.0.into(),
+        updated: vmm.runtime.time_state_updated,
+        deleted: vmm.time_deleted,
+    });
+    let table = tabled::Table::new(rows)
+        .with(tabled::settings::Style::empty())
+        .with(tabled::settings::Padding::new(0, 1, 0, 0))
+        .to_string();
+    println!("\nVMMS\n{}", table);
+
+    #[derive(Tabled)]
+    #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct MigrationRow {
+        created: DateTime<Utc>,
+        id: Uuid,
+        src_state: String,
+        tgt_state: String,
+        src_vmm: Uuid,
+        tgt_vmm: Uuid,
+    }
+
+    let migrations: Vec<db::model::Migration> = {
+        use db::schema::migration::dsl;
+        dsl::migration
+            .filter(dsl::instance_id.eq(args.instance_id))
+            .order_by(dsl::time_created.desc())
+            .limit(i64::from(u32::from(limit)))
+            .select(db::model::Migration::as_select())
+            .load_async(&*conn)
+            .await
+            .context("loading migrations")?
+    };
+    check_limit(&migrations, limit, || "listing migrations");
+    let rows = migrations.into_iter().map(|migration| MigrationRow {
+        created: migration.time_created,
+        id: migration.id,
+        src_state: migration.source_state.to_string(),
+        tgt_state: migration.target_state.to_string(),
+        src_vmm: migration.source_propolis_id,
+        tgt_vmm: migration.target_propolis_id,
+    });
+    let table = tabled::Table::new(rows)
+        .with(tabled::settings::Style::empty())
+        .with(tabled::settings::Padding::new(0, 1, 0, 0))
+        .to_string();
+    println!("\nMIGRATIONS\n{}", table);
+
+    Ok(())
+}
+
 // DNS
 
 /// Run `omdb db dns show`.
@@ -2973,6 +3977,63 @@ async fn cmd_db_dns_show(
     Ok(())
 }
 
+/// Run `omdb db dns show --history`.
+async fn cmd_db_dns_history(
+    datastore: &DataStore,
+    fetch_opts: &DbFetchOptions,
+    history: NonZeroU32,
+) -> Result<(), anyhow::Error> {
+    #[derive(Serialize, Tabled)]
+    #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct VersionRow {
+        group: String,
+        #[tabled(rename = "ver")]
+        version: String,
+        created: String,
+        creator: String,
+        reason: String,
+    }
+
+    use nexus_db_queries::db::schema::dns_version::dsl;
+    let limit = std::cmp::min(history, fetch_opts.fetch_limit);
+    let conn = datastore.pool_connection_for_tests().await?;
+    let mut rows = Vec::new();
+    for group in [DnsGroup::Internal, DnsGroup::External] {
+        let ctx = || format!("listing DNS versions for DNS group {:?}", group);
+        let versions = dsl::dns_version
+            .filter(dsl::dns_group.eq(group))
+            .order_by(dsl::version.desc())
+            .limit(i64::from(u32::from(limit)))
+            .select(DnsVersion::as_select())
+            .load_async(&*conn)
+            .await
+            .with_context(ctx)?;
+        if limit < history {
+            check_limit(&versions, limit, ctx);
+        }
+
+        rows.extend(versions.into_iter().map(|version| VersionRow {
+            group: group.to_string(),
+            version: version.version.0.to_string(),
+            created:
+                version.time_created.to_rfc3339_opts(SecondsFormat::Secs, true),
+            creator: version.creator,
+            reason: version.comment,
+        }));
+    }
+
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
+    let table = tabled::Table::new(rows)
+        .with(tabled::settings::Style::empty())
+        .with(tabled::settings::Padding::new(0, 1, 0, 0))
+        .to_string();
+    println!("{}", table);
+    Ok(())
+}
+
 async fn load_zones_version(
     opctx: &OpContext,
     datastore: &DataStore,
@@ -3012,9 +4073,24 @@ async fn cmd_db_dns_diff(
     opctx: &OpContext,
     datastore: &DataStore,
     fetch_opts: &DbFetchOptions,
-    args: &DnsVersionArgs,
+    diff_args: &DnsDiffArgs,
 ) -> Result<(), anyhow::Error> {
+    let group = diff_args.group;
+    let version = match (diff_args.version, diff_args.from, diff_args.to) {
+        (Some(version), None, None) => version,
+        (None, Some(from), Some(to)) => {
+            return cmd_db_dns_diff_range(
+                opctx, datastore, fetch_opts, group, from, to,
+            )
+            .await;
+        }
+        // clap enforces that exactly one of these forms is used.
+        _ => unreachable!("unexpected arguments: {:?}", diff_args),
+    };
+    let args = &DnsVersionArgs { group, version };
+
     let limit = fetch_opts.fetch_limit;
+    let styles = fetch_opts.styles(Stream::Stdout);
     let (dns_zones, version) =
         load_zones_version(opctx, datastore, limit, args).await?;
 
@@ -3044,79 +4120,485 @@ async fn cmd_db_dns_diff(
             .context("loading added names")?;
         check_limit(&added, limit, || "loading added names");
 
-        let removed = dsl::dns_name
-            .filter(dsl::dns_zone_id.eq(zone.id))
-            .filter(dsl::version_removed.eq(version.version))
-            .limit(i64::from(u32::from(limit)))
-            .select(DnsName::as_select())
-            .load_async(&*datastore.pool_connection_for_tests().await?)
-            .await
-            .context("loading added names")?;
-        check_limit(&added, limit, || "loading removed names");
+        let removed = dsl::dns_name
+            .filter(dsl::dns_zone_id.eq(zone.id))
+            .filter(dsl::version_removed.eq(version.version))
+            .limit(i64::from(u32::from(limit)))
+            .select(DnsName::as_select())
+            .load_async(&*datastore.pool_connection_for_tests().await?)
+            .await
+            .context("loading added names")?;
+        check_limit(&added, limit, || "loading removed names");
+        println!(
+            "changes:                    names added: {}, names removed: {}",
+            added.len(),
+            removed.len()
+        );
+        println!("");
+
+        for a in added {
+            print_name(
+                &styles,
+                "+",
+                &a.name,
+                a.records().context("parsing records"),
+            );
+        }
+
+        for r in removed {
+            print_name(
+                &styles,
+                "-",
+                &r.name,
+                r.records().context("parsing records"),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `omdb db dns diff --from FROM --to TO`.
+///
+/// This summarizes the net changes made by all versions after `from` up to and
+/// including `to`.  Names that were added and then removed again within that
+/// range cancel out and are not shown.
+async fn cmd_db_dns_diff_range(
+    opctx: &OpContext,
+    datastore: &DataStore,
+    fetch_opts: &DbFetchOptions,
+    group: CliDnsGroup,
+    from: u32,
+    to: u32,
+) -> Result<(), anyhow::Error> {
+    if from >= to {
+        bail!("--from ({}) must be less than --to ({})", from, to);
+    }
+
+    let limit = fetch_opts.fetch_limit;
+    let styles = fetch_opts.styles(Stream::Stdout);
+    let (dns_zones, from_version) = load_zones_version(
+        opctx,
+        datastore,
+        limit,
+        &DnsVersionArgs { group, version: from },
+    )
+    .await?;
+    let (_, to_version) = load_zones_version(
+        opctx,
+        datastore,
+        limit,
+        &DnsVersionArgs { group, version: to },
+    )
+    .await?;
+
+    for zone in dns_zones {
+        println!(
+            "DNS zone:                   {} ({:?})",
+            zone.zone_name, group
+        );
+        println!(
+            "requested versions:         {} (created at {}) to {} (created at \
+             {})",
+            *from_version.version,
+            from_version
+                .time_created
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+            *to_version.version,
+            to_version.time_created.to_rfc3339_opts(SecondsFormat::Secs, true),
+        );
+
+        // Load every name added or removed by a version in the range.
+        use nexus_db_queries::db::schema::dns_name::dsl;
+
+        let added = dsl::dns_name
+            .filter(dsl::dns_zone_id.eq(zone.id))
+            .filter(dsl::version_added.gt(from_version.version))
+            .filter(dsl::version_added.le(to_version.version))
+            .limit(i64::from(u32::from(limit)))
+            .select(DnsName::as_select())
+            .load_async(&*datastore.pool_connection_for_tests().await?)
+            .await
+            .context("loading added names")?;
+        check_limit(&added, limit, || "loading added names");
+
+        let removed = dsl::dns_name
+            .filter(dsl::dns_zone_id.eq(zone.id))
+            .filter(dsl::version_removed.gt(from_version.version))
+            .filter(dsl::version_removed.le(to_version.version))
+            .limit(i64::from(u32::from(limit)))
+            .select(DnsName::as_select())
+            .load_async(&*datastore.pool_connection_for_tests().await?)
+            .await
+            .context("loading removed names")?;
+        check_limit(&removed, limit, || "loading removed names");
+
+        // Each row describes one name's records from the version that added
+        // them to the version that removed them.  A row that shows up in both
+        // lists was added and then removed within the range, so it's not a net
+        // change.
+        let transient: BTreeSet<_> = added
+            .iter()
+            .filter(|a| {
+                removed.iter().any(|r| {
+                    r.name == a.name && r.version_added == a.version_added
+                })
+            })
+            .map(|a| (a.name.clone(), a.version_added))
+            .collect();
+        let is_net_change = |n: &DnsName| {
+            !transient.contains(&(n.name.clone(), n.version_added))
+        };
+        let added: Vec<_> = added.into_iter().filter(is_net_change).collect();
+        let removed: Vec<_> =
+            removed.into_iter().filter(is_net_change).collect();
+
+        println!(
+            "net changes:                names added: {}, names removed: {}",
+            added.len(),
+            removed.len()
+        );
+        println!("");
+
+        for a in added {
+            print_name(
+                &styles,
+                "+",
+                &a.name,
+                a.records().context("parsing records"),
+            );
+        }
+
+        for r in removed {
+            print_name(
+                &styles,
+                "-",
+                &r.name,
+                r.records().context("parsing records"),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `omdb db dns names`.
+async fn cmd_db_dns_names(
+    opctx: &OpContext,
+    datastore: &DataStore,
+    fetch_opts: &DbFetchOptions,
+    names_args: &DnsNamesArgs,
+) -> Result<(), anyhow::Error> {
+    let args = &names_args.version;
+    let limit = fetch_opts.fetch_limit;
+    let styles = fetch_opts.styles(Stream::Stdout);
+    let (group_zones, version) =
+        load_zones_version(opctx, datastore, limit, args).await?;
+
+    if group_zones.is_empty() {
+        println!("no DNS zones found for group {:?}", args.group);
+        return Ok(());
+    }
+
+    if let Some(name) = &names_args.resolve {
+        return cmd_db_dns_resolve(
+            datastore,
+            fetch_opts,
+            names_args,
+            &group_zones,
+            &version,
+            name,
+        )
+        .await;
+    }
+
+    if let Some(other_version) = names_args.diff_against {
+        return cmd_db_dns_names_diff(
+            opctx,
+            datastore,
+            fetch_opts,
+            args.group,
+            &group_zones,
+            other_version,
+            &version,
+        )
+        .await;
+    }
+
+    // There will almost never be more than one zone.  But just in case, we'll
+    // iterate over whatever we find and print all the names in each one.
+    for zone in group_zones {
+        if names_args.server_format {
+            println!("$ORIGIN {}.", zone.zone_name);
+        } else {
+            println!("{:?} zone: {}", args.group, zone.zone_name);
+            println!("  {:50} {}", "NAME", "RECORDS");
+        }
+        let ctx = || format!("listing names for zone {:?}", zone.zone_name);
+        let mut names = datastore
+            .dns_names_list(opctx, zone.id, version.version, &first_page(limit))
+            .await
+            .with_context(ctx)?;
+        check_limit(&names, limit, ctx);
+        names.sort_by(|(n1, _), (n2, _)| {
+            // A natural sort by name puts records starting with numbers first
+            // (which will be some of the uuids), then underscores (the SRV
+            // names), and then the letters (the rest of the uuids).  This is
+            // ugly.  Put the SRV records last (based on the underscore).  (We
+            // could look at the record type instead, but that's just as cheesy:
+            // names can in principle have multiple different kinds of records,
+            // and we'd still want records of the same type to be sorted by
+            // name.)
+            match (n1.chars().next(), n2.chars().next()) {
+                (Some('_'), Some(c)) if c != '_' => Ordering::Greater,
+                (Some(c), Some('_')) if c != '_' => Ordering::Less,
+                _ => n1.cmp(n2),
+            }
+        });
+
+        // If the listing was truncated, SRV targets may be missing only
+        // because we didn't fetch them, so don't report anything.
+        let anomalies = if names.len() < usize::try_from(u32::from(limit))? {
+            dns_name_anomalies(&zone.zone_name, &names)
+        } else {
+            Vec::new()
+        };
+
+        for (name, records) in names {
+            if names_args.server_format {
+                print_name_zone_file(&name, &records);
+            } else {
+                print_name(&styles, "", &name, Ok(records));
+            }
+        }
+
+        // These are only advisory, so report them without failing the command.
+        let stderr_styles = fetch_opts.styles(Stream::Stderr);
+        for anomaly in anomalies {
+            eprintln!(
+                "{}",
+                format!("note: possible misconfiguration: {}", anomaly)
+                    .style(stderr_styles.warning)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns a description of each name in a DNS zone whose records look
+/// inconsistent
+///
+/// Names are expected to have either address (A/AAAA) records or SRV records,
+/// not both, and SRV records within the zone should point at names in the zone
+/// that have addresses.  SRV targets outside the zone can't be checked and are
+/// ignored.
+fn dns_name_anomalies(
+    zone_name: &str,
+    names: &[(String, Vec<DnsRecord>)],
+) -> Vec<String> {
+    let names_with_addresses: BTreeSet<&str> = names
+        .iter()
+        .filter(|(_, records)| {
+            records
+                .iter()
+                .any(|r| matches!(r, DnsRecord::A(_) | DnsRecord::Aaaa(_)))
+        })
+        .map(|(name, _)| name.as_str())
+        .collect();
+    let zone_suffix = format!(".{}", zone_name);
+
+    let mut anomalies = Vec::new();
+    for (name, records) in names {
+        let srv_targets: Vec<&str> = records
+            .iter()
+            .filter_map(|r| match r {
+                DnsRecord::Srv(Srv { target, .. }) => Some(target.as_str()),
+                DnsRecord::A(_) | DnsRecord::Aaaa(_) => None,
+            })
+            .collect();
+        if !srv_targets.is_empty()
+            && names_with_addresses.contains(name.as_str())
+        {
+            anomalies.push(format!(
+                "name {:?} has both address and SRV records",
+                name
+            ));
+        }
+
+        for target in srv_targets {
+            let fqdn = target.strip_suffix('.').unwrap_or(target);
+            let Some(target_name) = fqdn.strip_suffix(&zone_suffix) else {
+                continue;
+            };
+            if !names_with_addresses.contains(target_name) {
+                anomalies.push(format!(
+                    "name {:?} has SRV record for {:?}, which has no address \
+                     records in this zone",
+                    name, target
+                ));
+            }
+        }
+    }
+
+    anomalies
+}
+
+/// Run `omdb db dns names --diff-against VERSION`.
+///
+/// Unlike `omdb db dns diff`, this compares the complete set of names at each
+/// of the two versions, so the versions need not be adjacent (or even in
+/// order).
+async fn cmd_db_dns_names_diff(
+    opctx: &OpContext,
+    datastore: &DataStore,
+    fetch_opts: &DbFetchOptions,
+    group: CliDnsGroup,
+    group_zones: &[DnsZone],
+    other_version: u32,
+    version: &DnsVersion,
+) -> Result<(), anyhow::Error> {
+    let limit = fetch_opts.fetch_limit;
+    let styles = &fetch_opts.styles(Stream::Stdout);
+    let (_, other_version) = load_zones_version(
+        opctx,
+        datastore,
+        limit,
+        &DnsVersionArgs { group, version: other_version },
+    )
+    .await?;
+
+    for zone in group_zones {
+        println!("{:?} zone: {}", group, zone.zone_name);
+        println!(
+            "comparing version {} against version {}",
+            *version.version, *other_version.version
+        );
+
+        let mut names_by_version = Vec::with_capacity(2);
+        for v in [&other_version, version] {
+            let ctx = || {
+                format!(
+                    "listing names for zone {:?} at version {}",
+                    zone.zone_name, *v.version
+                )
+            };
+            let names = datastore
+                .dns_names_list(opctx, zone.id, v.version, &first_page(limit))
+                .await
+                .with_context(ctx)?;
+            check_limit(&names, limit, ctx);
+            names_by_version
+                .push(names.into_iter().collect::<BTreeMap<_, _>>());
+        }
+        let after = names_by_version.pop().unwrap();
+        let before = names_by_version.pop().unwrap();
+
+        let same_records = |r1: &[DnsRecord], r2: &[DnsRecord]| {
+            r1.len() == r2.len() && r1.iter().all(|r| r2.contains(r))
+        };
+        let added: Vec<_> =
+            after.iter().filter(|(n, _)| !before.contains_key(*n)).collect();
+        let removed: Vec<_> =
+            before.iter().filter(|(n, _)| !after.contains_key(*n)).collect();
+        let changed: Vec<_> = after
+            .iter()
+            .filter_map(|(name, after_records)| {
+                let before_records = before.get(name)?;
+                (!same_records(before_records, after_records)).then_some((
+                    name,
+                    before_records,
+                    after_records,
+                ))
+            })
+            .collect();
+
         println!(
-            "changes:                    names added: {}, names removed: {}",
+            "changes: names added: {}, names removed: {}, names changed: {}",
             added.len(),
-            removed.len()
+            removed.len(),
+            changed.len(),
         );
         println!("");
 
-        for a in added {
-            print_name("+", &a.name, a.records().context("parsing records"));
+        for (name, records) in added {
+            print_name(styles, "+", name, Ok(records.clone()));
         }
 
-        for r in removed {
-            print_name("-", &r.name, r.records().context("parsing records"));
+        for (name, records) in removed {
+            print_name(styles, "-", name, Ok(records.clone()));
+        }
+
+        for (name, before_records, after_records) in changed {
+            print_name(styles, "-", name, Ok(before_records.clone()));
+            print_name(styles, "+", name, Ok(after_records.clone()));
         }
     }
 
     Ok(())
 }
 
-/// Run `omdb db dns names`.
-async fn cmd_db_dns_names(
-    opctx: &OpContext,
+/// Run `omdb db dns names --resolve NAME`.
+async fn cmd_db_dns_resolve(
     datastore: &DataStore,
     fetch_opts: &DbFetchOptions,
-    args: &DnsVersionArgs,
+    names_args: &DnsNamesArgs,
+    group_zones: &[DnsZone],
+    version: &DnsVersion,
+    name: &str,
 ) -> Result<(), anyhow::Error> {
-    let limit = fetch_opts.fetch_limit;
-    let (group_zones, version) =
-        load_zones_version(opctx, datastore, limit, args).await?;
-
-    if group_zones.is_empty() {
-        println!("no DNS zones found for group {:?}", args.group);
-        return Ok(());
-    }
+    let args = &names_args.version;
+    let styles = fetch_opts.styles(Stream::Stdout);
+    let conn = datastore.pool_connection_for_tests().await?;
 
-    // There will almost never be more than one zone.  But just in case, we'll
-    // iterate over whatever we find and print all the names in each one.
+    // Look the name up directly rather than listing every name in the zone.
+    let mut found = Vec::new();
     for zone in group_zones {
-        println!("{:?} zone: {}", args.group, zone.zone_name);
-        println!("  {:50} {}", "NAME", "RECORDS");
-        let ctx = || format!("listing names for zone {:?}", zone.zone_name);
-        let mut names = datastore
-            .dns_names_list(opctx, zone.id, version.version, &first_page(limit))
+        use db::schema::dns_name::dsl;
+        let maybe_name = dsl::dns_name
+            .filter(dsl::dns_zone_id.eq(zone.id))
+            .filter(dsl::name.eq(name.to_string()))
+            .filter(dsl::version_added.le(version.version))
+            .filter(
+                dsl::version_removed
+                    .is_null()
+                    .or(dsl::version_removed.gt(version.version)),
+            )
+            .select(DnsName::as_select())
+            .first_async(&*conn)
             .await
-            .with_context(ctx)?;
-        check_limit(&names, limit, ctx);
-        names.sort_by(|(n1, _), (n2, _)| {
-            // A natural sort by name puts records starting with numbers first
-            // (which will be some of the uuids), then underscores (the SRV
-            // names), and then the letters (the rest of the uuids).  This is
-            // ugly.  Put the SRV records last (based on the underscore).  (We
-            // could look at the record type instead, but that's just as cheesy:
-            // names can in principle have multiple different kinds of records,
-            // and we'd still want records of the same type to be sorted by
-            // name.)
-            match (n1.chars().next(), n2.chars().next()) {
-                (Some('_'), Some(c)) if c != '_' => Ordering::Greater,
-                (Some(c), Some('_')) if c != '_' => Ordering::Less,
-                _ => n1.cmp(n2),
-            }
-        });
+            .optional()
+            .with_context(|| {
+                format!("looking up {:?} in zone {:?}", name, zone.zone_name)
+            })?;
+        if let Some(dns_name) = maybe_name {
+            found.push((zone, dns_name));
+        }
+    }
 
-        for (name, records) in names {
-            print_name("", &name, Ok(records));
+    if found.is_empty() {
+        bail!(
+            "name {:?} not found in {:?} DNS version {}",
+            name,
+            args.group,
+            *version.version
+        );
+    }
+
+    for (zone, dns_name) in found {
+        if names_args.server_format {
+            println!("$ORIGIN {}.", zone.zone_name);
+            let records = dns_name.records().context("parsing records")?;
+            print_name_zone_file(&dns_name.name, &records);
+        } else {
+            println!("{:?} zone: {}", args.group, zone.zone_name);
+            println!("  {:50} {}", "NAME", "RECORDS");
+            print_name(
+                &styles,
+                "",
+                &dns_name.name,
+                dns_name.records().context("parsing records"),
+            );
         }
     }
 
@@ -3127,6 +4609,7 @@ async fn cmd_db_eips(
     opctx: &OpContext,
     datastore: &DataStore,
     fetch_opts: &DbFetchOptions,
+    args: &EipsArgs,
     verbose: bool,
 ) -> Result<(), anyhow::Error> {
     use db::schema::external_ip::dsl;
@@ -3134,6 +4617,16 @@ async fn cmd_db_eips(
     if !fetch_opts.include_deleted {
         query = query.filter(dsl::time_deleted.is_null());
     }
+    if let Some(instance_id) = args.owner_instance {
+        query = query
+            .filter(dsl::parent_id.eq(instance_id))
+            .filter(dsl::is_service.eq(false));
+    }
+    if let Some(service_id) = args.owner_service {
+        query = query
+            .filter(dsl::parent_id.eq(service_id))
+            .filter(dsl::is_service.eq(true));
+    }
 
     let ips: Vec<ExternalIp> = query
         .select(ExternalIp::as_select())
@@ -3145,6 +4638,7 @@ async fn cmd_db_eips(
         String::from("listing external ips")
     });
 
+    #[derive(Serialize)]
     struct PortRange {
         first: u16,
         last: u16,
@@ -3212,7 +4706,7 @@ async fn cmd_db_eips(
         }
     }
 
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct IpRow {
         ip: ipnetwork::IpNetwork,
@@ -3329,6 +4823,10 @@ async fn cmd_db_eips(
     }
 
     rows.sort_by(|a, b| a.ip.cmp(&b.ip));
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .to_string();
@@ -3343,7 +4841,7 @@ async fn cmd_db_network_list_vnics(
     fetch_opts: &DbFetchOptions,
     verbose: bool,
 ) -> Result<(), anyhow::Error> {
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct NicRow {
         ip: IpNetwork,
@@ -3352,6 +4850,8 @@ async fn cmd_db_network_list_vnics(
         primary: bool,
         kind: &'static str,
         subnet: String,
+        subnet_id: Uuid,
+        vpc_id: Uuid,
         parent_id: Uuid,
         parent_name: String,
     }
@@ -3473,6 +4973,8 @@ async fn cmd_db_network_list_vnics(
             primary: nic.primary,
             kind,
             subnet,
+            subnet_id: nic.subnet_id,
+            vpc_id: nic.vpc_id,
             parent_id: nic.parent_id,
             parent_name,
         };
@@ -3480,6 +4982,149 @@ async fn cmd_db_network_list_vnics(
     }
 
     rows.sort_by(|a, b| a.ip.cmp(&b.ip));
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
+    let table = tabled::Table::new(rows)
+        .with(tabled::settings::Style::empty())
+        .to_string();
+
+    println!("{}", table);
+
+    Ok(())
+}
+
+async fn cmd_db_network_list_routes(
+    datastore: &DataStore,
+    fetch_opts: &DbFetchOptions,
+    args: &RoutesArgs,
+    verbose: bool,
+) -> Result<(), anyhow::Error> {
+    #[derive(Serialize, Tabled)]
+    #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct RouteRow {
+        id: Uuid,
+        router_id: Uuid,
+        name: String,
+        kind: String,
+        destination: String,
+        target: String,
+    }
+
+    use db::schema::router_route::dsl;
+    let mut query = dsl::router_route.into_boxed();
+    if !fetch_opts.include_deleted {
+        query = query.filter(dsl::time_deleted.is_null());
+    }
+    if let Some(router_id) = args.router {
+        query = query.filter(dsl::vpc_router_id.eq(router_id));
+    }
+    if let Some(vpc_id) = args.vpc {
+        use db::schema::vpc_router::dsl as router_dsl;
+        query = query.filter(
+            dsl::vpc_router_id.eq_any(
+                router_dsl::vpc_router
+                    .filter(router_dsl::vpc_id.eq(vpc_id))
+                    .select(router_dsl::id),
+            ),
+        );
+    }
+
+    let routes: Vec<RouterRoute> = query
+        .select(RouterRoute::as_select())
+        .limit(i64::from(u32::from(fetch_opts.fetch_limit)))
+        .get_results_async(&*datastore.pool_connection_for_tests().await?)
+        .await?;
+
+    check_limit(&routes, fetch_opts.fetch_limit, || {
+        String::from("listing router routes")
+    });
+
+    if verbose {
+        for route in &routes {
+            println!("{route:#?}");
+        }
+        return Ok(());
+    }
+
+    let mut rows: Vec<_> = routes
+        .iter()
+        .map(|route| RouteRow {
+            id: route.id(),
+            router_id: route.vpc_router_id,
+            name: route.name().to_string(),
+            kind: route.kind.0.to_string(),
+            destination: route.destination.0.to_string(),
+            target: route.target.0.to_string(),
+        })
+        .collect();
+
+    rows.sort_by(|a, b| (a.router_id, &a.name).cmp(&(b.router_id, &b.name)));
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
+    let table = tabled::Table::new(rows)
+        .with(tabled::settings::Style::empty())
+        .to_string();
+
+    println!("{}", table);
+
+    Ok(())
+}
+
+// REGION SNAPSHOTS
+
+/// List region snapshots, optionally filtered by dataset or snapshot
+async fn cmd_db_region_snapshots_list(
+    datastore: &DataStore,
+    fetch_opts: &DbFetchOptions,
+    args: &RegionSnapshotsListArgs,
+) -> Result<(), anyhow::Error> {
+    #[derive(Serialize, Tabled)]
+    #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct RegionSnapshotRow {
+        dataset_id: Uuid,
+        region_id: Uuid,
+        snapshot_id: Uuid,
+        snapshot_addr: String,
+        volume_references: i64,
+        deleting: bool,
+    }
+
+    use db::schema::region_snapshot::dsl;
+    let mut query = dsl::region_snapshot.into_boxed();
+    if let Some(dataset_id) = args.dataset {
+        query = query.filter(dsl::dataset_id.eq(dataset_id));
+    }
+    if let Some(snapshot_id) = args.snapshot {
+        query = query.filter(dsl::snapshot_id.eq(snapshot_id));
+    }
+
+    let region_snapshots: Vec<RegionSnapshot> = query
+        .order_by((dsl::dataset_id, dsl::region_id, dsl::snapshot_id))
+        .select(RegionSnapshot::as_select())
+        .limit(i64::from(u32::from(fetch_opts.fetch_limit)))
+        .get_results_async(&*datastore.pool_connection_for_tests().await?)
+        .await?;
+
+    check_limit(&region_snapshots, fetch_opts.fetch_limit, || {
+        String::from("listing region snapshots")
+    });
+
+    let rows = region_snapshots.into_iter().map(|rs| RegionSnapshotRow {
+        dataset_id: rs.dataset_id,
+        region_id: rs.region_id,
+        snapshot_id: rs.snapshot_id,
+        snapshot_addr: rs.snapshot_addr,
+        volume_references: rs.volume_references,
+        deleting: rs.deleting,
+    });
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .to_string();
@@ -3546,7 +5191,7 @@ async fn cmd_db_region_snapshot_replacement_list(
 
     check_limit(&requests, limit, ctx);
 
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct Row {
         pub id: Uuid,
@@ -3564,6 +5209,10 @@ async fn cmd_db_region_snapshot_replacement_list(
         });
     }
 
+    if let DbOutputFormat::Json = fetch_opts.format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -4132,16 +5781,28 @@ async fn cmd_db_validate_region_snapshots(
 }
 
 fn print_name(
+    styles: &Styles,
     prefix: &str,
     name: &str,
     maybe_records: Result<Vec<DnsRecord>, anyhow::Error>,
 ) {
+    // Lines for added and removed names are highlighted as a whole.
+    let style = match prefix {
+        "+" => styles.added,
+        "-" => styles.removed,
+        _ => Style::new(),
+    };
+
     let records = match maybe_records {
         Ok(records) => records,
         Err(error) => {
             println!(
-                "{}  {:50} (failed to parse record data: {:#})",
-                prefix, name, error
+                "{}",
+                format!(
+                    "{}  {:50} (failed to parse record data: {:#})",
+                    prefix, name, error
+                )
+                .style(style)
             );
             return;
         }
@@ -4152,19 +5813,50 @@ fn print_name(
             DnsRecord::Srv(_) => (),
             DnsRecord::Aaaa(_) | DnsRecord::A(_) => {
                 println!(
-                    "{}  {:50} {}",
-                    prefix,
-                    name,
-                    format_record(&records[0])
+                    "{}",
+                    format!(
+                        "{}  {:50} {}",
+                        prefix,
+                        name,
+                        format_record(&records[0])
+                    )
+                    .style(style)
                 );
                 return;
             }
         }
     }
 
-    println!("{}  {:50} (records: {})", prefix, name, records.len());
+    println!(
+        "{}",
+        format!("{}  {:50} (records: {})", prefix, name, records.len())
+            .style(style)
+    );
     for r in &records {
-        println!("{}      {}", prefix, format_record(r));
+        println!(
+            "{}",
+            format!("{}      {}", prefix, format_record(r)).style(style)
+        );
+    }
+}
+
+/// TTL printed for records in zone-file output
+///
+/// This matches what the internal DNS server reports for every record.
+const ZONE_FILE_TTL: u32 = 0;
+
+/// Print a name's records in zone-file syntax (relative to the zone's
+/// `$ORIGIN`)
+fn print_name_zone_file(name: &str, records: &[DnsRecord]) {
+    for r in records {
+        let (rtype, rdata) = match r {
+            DnsRecord::A(addr) => ("A", addr.to_string()),
+            DnsRecord::Aaaa(addr) => ("AAAA", addr.to_string()),
+            DnsRecord::Srv(Srv { prio, weight, port, target }) => {
+                ("SRV", format!("{} {} {} {}.", prio, weight, port, target))
+            }
+        };
+        println!("{:50} {} IN {:4} {}", name, ZONE_FILE_TTL, rtype, rdata);
     }
 }
 
@@ -4187,17 +5879,18 @@ async fn cmd_db_inventory(
     inventory_args: &InventoryArgs,
 ) -> Result<(), anyhow::Error> {
     let limit = fetch_opts.fetch_limit;
+    let format = fetch_opts.format;
     let conn = datastore.pool_connection_for_tests().await?;
     match inventory_args.command {
         InventoryCommands::BaseboardIds => {
-            cmd_db_inventory_baseboard_ids(&conn, limit).await
+            cmd_db_inventory_baseboard_ids(&conn, limit, format).await
         }
         InventoryCommands::Cabooses => {
-            cmd_db_inventory_cabooses(&conn, limit).await
+            cmd_db_inventory_cabooses(&conn, limit, format).await
         }
         InventoryCommands::Collections(CollectionsArgs {
             command: CollectionsCommands::List,
-        }) => cmd_db_inventory_collections_list(&conn, limit).await,
+        }) => cmd_db_inventory_collections_list(&conn, limit, format).await,
         InventoryCommands::Collections(CollectionsArgs {
             command:
                 CollectionsCommands::Show(CollectionsShowArgs {
@@ -4210,16 +5903,22 @@ async fn cmd_db_inventory(
             cmd_db_inventory_collections_show(
                 opctx,
                 datastore,
+                fetch_opts,
                 id,
                 long_string_formatter,
             )
             .await
         }
+        InventoryCommands::Collections(CollectionsArgs {
+            command: CollectionsCommands::Diff(CollectionsDiffArgs { id1, id2 }),
+        }) => {
+            cmd_db_inventory_collections_diff(opctx, datastore, id1, id2).await
+        }
         InventoryCommands::PhysicalDisks(args) => {
-            cmd_db_inventory_physical_disks(&conn, limit, args).await
+            cmd_db_inventory_physical_disks(&conn, limit, format, args).await
         }
         InventoryCommands::RotPages => {
-            cmd_db_inventory_rot_pages(&conn, limit).await
+            cmd_db_inventory_rot_pages(&conn, limit, format).await
         }
     }
 }
@@ -4227,8 +5926,9 @@ async fn cmd_db_inventory(
 async fn cmd_db_inventory_baseboard_ids(
     conn: &DataStoreConnection,
     limit: NonZeroU32,
+    format: DbOutputFormat,
 ) -> Result<(), anyhow::Error> {
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct BaseboardRow {
         id: Uuid,
@@ -4251,6 +5951,10 @@ async fn cmd_db_inventory_baseboard_ids(
         part_number: baseboard_id.part_number,
         serial_number: baseboard_id.serial_number,
     });
+    if let DbOutputFormat::Json = format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -4264,8 +5968,9 @@ async fn cmd_db_inventory_baseboard_ids(
 async fn cmd_db_inventory_cabooses(
     conn: &DataStoreConnection,
     limit: NonZeroU32,
+    format: DbOutputFormat,
 ) -> Result<(), anyhow::Error> {
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct CabooseRow {
         id: Uuid,
@@ -4292,6 +5997,10 @@ async fn cmd_db_inventory_cabooses(
         version: caboose.version,
         git_commit: caboose.git_commit,
     });
+    if let DbOutputFormat::Json = format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -4305,9 +6014,10 @@ async fn cmd_db_inventory_cabooses(
 async fn cmd_db_inventory_physical_disks(
     conn: &DataStoreConnection,
     limit: NonZeroU32,
+    format: DbOutputFormat,
     args: InvPhysicalDisksArgs,
 ) -> Result<(), anyhow::Error> {
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct DiskRow {
         inv_collection_id: Uuid,
@@ -4349,6 +6059,10 @@ async fn cmd_db_inventory_physical_disks(
         variant: format!("{:?}", disk.variant),
     });
 
+    if let DbOutputFormat::Json = format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -4362,8 +6076,9 @@ async fn cmd_db_inventory_physical_disks(
 async fn cmd_db_inventory_rot_pages(
     conn: &DataStoreConnection,
     limit: NonZeroU32,
+    format: DbOutputFormat,
 ) -> Result<(), anyhow::Error> {
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct RotPageRow {
         id: Uuid,
@@ -4384,6 +6099,10 @@ async fn cmd_db_inventory_rot_pages(
         id: rot_page.id,
         data_base64: rot_page.data_base64,
     });
+    if let DbOutputFormat::Json = format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -4397,8 +6116,9 @@ async fn cmd_db_inventory_rot_pages(
 async fn cmd_db_inventory_collections_list(
     conn: &DataStoreConnection,
     limit: NonZeroU32,
+    format: DbOutputFormat,
 ) -> Result<(), anyhow::Error> {
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct CollectionRow {
         id: CollectionUuid,
@@ -4461,6 +6181,10 @@ async fn cmd_db_inventory_collections_list(
         });
     }
 
+    if let DbOutputFormat::Json = format {
+        return print_json(rows);
+    }
+
     let table = tabled::Table::new(rows)
         .with(tabled::settings::Style::empty())
         .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -4474,6 +6198,7 @@ async fn cmd_db_inventory_collections_list(
 async fn cmd_db_inventory_collections_show(
     opctx: &OpContext,
     datastore: &DataStore,
+    fetch_opts: &DbFetchOptions,
     id: CollectionUuid,
     long_string_formatter: LongStringFormatter,
 ) -> Result<(), anyhow::Error> {
@@ -4482,18 +6207,126 @@ async fn cmd_db_inventory_collections_show(
         .await
         .context("reading collection")?;
 
+    let styles = fetch_opts.styles(Stream::Stdout);
     inv_collection_print(&collection).await?;
-    let nerrors = inv_collection_print_errors(&collection).await?;
-    inv_collection_print_devices(&collection, &long_string_formatter).await?;
-    inv_collection_print_sleds(&collection);
+    let nerrors = inv_collection_print_errors(&collection, &styles).await?;
+    inv_collection_print_devices(&collection, &long_string_formatter, &styles)
+        .await?;
+    inv_collection_print_sleds(&collection, &styles);
 
     if nerrors > 0 {
+        let styles = fetch_opts.styles(Stream::Stderr);
         eprintln!(
-            "warning: {} collection error{} {} reported above",
-            nerrors,
-            if nerrors == 1 { "" } else { "s" },
-            if nerrors == 1 { "was" } else { "were" },
+            "{}",
+            format!(
+                "warning: {} collection error{} {} reported above",
+                nerrors,
+                if nerrors == 1 { "" } else { "s" },
+                if nerrors == 1 { "was" } else { "were" },
+            )
+            .style(styles.warning)
+        );
+    }
+
+    Ok(())
+}
+
+async fn cmd_db_inventory_collections_diff(
+    opctx: &OpContext,
+    datastore: &DataStore,
+    id1: CollectionUuid,
+    id2: CollectionUuid,
+) -> Result<(), anyhow::Error> {
+    let collection1 = datastore
+        .inventory_collection_read(opctx, id1)
+        .await
+        .with_context(|| format!("reading collection {}", id1))?;
+    let collection2 = datastore
+        .inventory_collection_read(opctx, id2)
+        .await
+        .with_context(|| format!("reading collection {}", id2))?;
+
+    println!("from collection: {} (started {})", id1, collection1.time_started);
+    println!("to collection:   {} (started {})", id2, collection2.time_started);
+
+    // Baseboards are identified by their part and serial numbers, so these
+    // can be compared directly across collections.
+    let baseboards = |c: &Collection| -> BTreeSet<Arc<BaseboardId>> {
+        c.sps.keys().chain(c.rots.keys()).cloned().collect()
+    };
+    let baseboards1 = baseboards(&collection1);
+    let baseboards2 = baseboards(&collection2);
+
+    println!("");
+    println!("baseboards:");
+    let mut nchanges = 0;
+    for baseboard_id in baseboards2.difference(&baseboards1) {
+        println!(
+            "    + {} {}",
+            baseboard_id.part_number, baseboard_id.serial_number
         );
+        nchanges += 1;
+    }
+    for baseboard_id in baseboards1.difference(&baseboards2) {
+        println!(
+            "    - {} {}",
+            baseboard_id.part_number, baseboard_id.serial_number
+        );
+        nchanges += 1;
+    }
+    if nchanges == 0 {
+        println!("    (no baseboards added or removed)");
+    }
+
+    #[derive(Tabled)]
+    #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct CabooseChangeRow<'a> {
+        part_number: &'a str,
+        serial_number: &'a str,
+        slot: String,
+        before: String,
+        after: String,
+    }
+
+    let describe = |found: Option<&CabooseFound>| {
+        found.map_or_else(
+            || String::from("-"),
+            |found| {
+                format!(
+                    "{} (git commit {})",
+                    found.caboose.version, found.caboose.git_commit
+                )
+            },
+        )
+    };
+    let mut rows = Vec::new();
+    for baseboard_id in baseboards1.intersection(&baseboards2) {
+        for which in CabooseWhich::iter() {
+            let before = collection1.caboose_for(which, baseboard_id);
+            let after = collection2.caboose_for(which, baseboard_id);
+            if before.map(|f| &f.caboose) == after.map(|f| &f.caboose) {
+                continue;
+            }
+            rows.push(CabooseChangeRow {
+                part_number: &baseboard_id.part_number,
+                serial_number: &baseboard_id.serial_number,
+                slot: format!("{:?}", which),
+                before: describe(before),
+                after: describe(after),
+            });
+        }
+    }
+
+    println!("");
+    println!("cabooses changed:");
+    if rows.is_empty() {
+        println!("    (no caboose changes)");
+    } else {
+        let table = tabled::Table::new(rows)
+            .with(tabled::settings::Style::empty())
+            .with(tabled::settings::Padding::new(0, 1, 0, 0))
+            .to_string();
+        println!("{}", textwrap::indent(&table.to_string(), "    "));
     }
 
     Ok(())
@@ -4526,10 +6359,14 @@ async fn inv_collection_print(
 
 async fn inv_collection_print_errors(
     collection: &Collection,
+    styles: &Styles,
 ) -> Result<u32, anyhow::Error> {
     println!("errors:     {}", collection.errors.len());
     for (index, message) in collection.errors.iter().enumerate() {
-        println!("  error {}: {}", index, message);
+        println!(
+            "{}",
+            format!("  error {}: {}", index, message).style(styles.error)
+        );
     }
 
     Ok(collection
@@ -4542,6 +6379,7 @@ async fn inv_collection_print_errors(
 async fn inv_collection_print_devices(
     collection: &Collection,
     long_string_formatter: &LongStringFormatter,
+    styles: &Styles,
 ) -> Result<(), anyhow::Error> {
     // Assemble a list of baseboard ids, sorted first by device type (sled,
     // switch, power), then by slot number.  This is the order in which we will
@@ -4693,8 +6531,12 @@ async fn inv_collection_print_devices(
         // SP, it couldn't communicate with its RoT.
         let sp = collection.sps.get(*sp_missing_rot).unwrap();
         println!(
-            "warning: found SP with no RoT: {:?} slot {}",
-            sp.sp_type, sp.sp_slot
+            "{}",
+            format!(
+                "warning: found SP with no RoT: {:?} slot {}",
+                sp.sp_type, sp.sp_slot
+            )
+            .style(styles.warning)
         );
     }
 
@@ -4708,16 +6550,20 @@ async fn inv_collection_print_devices(
         // no SP, since we get the RoT information from the SP in the first
         // place.
         println!(
-            "error: found RoT with no SP: \
-            hw_baseboard_id {:?} -- this is a bug",
-            rot_missing_sp
+            "{}",
+            format!(
+                "error: found RoT with no SP: \
+                hw_baseboard_id {:?} -- this is a bug",
+                rot_missing_sp
+            )
+            .style(styles.error)
         );
     }
 
     Ok(())
 }
 
-fn inv_collection_print_sleds(collection: &Collection) {
+fn inv_collection_print_sleds(collection: &Collection, styles: &Styles) {
     println!("SLED AGENTS");
     for sled in collection.sled_agents.values() {
         println!(
@@ -4768,7 +6614,10 @@ fn inv_collection_print_sleds(collection: &Collection) {
                 );
             }
         } else {
-            println!("  warning: no zone information found");
+            println!(
+                "{}",
+                "  warning: no zone information found".style(styles.warning)
+            );
         }
     }
 }
@@ -4898,7 +6747,7 @@ async fn cmd_db_migrations_list(
 
     check_limit(&migrations, fetch_opts.fetch_limit, || "listing migrations");
 
-    #[derive(Tabled)]
+    #[derive(Serialize, Tabled)]
     #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
     struct Vmms {
         src_state: MigrationState,
@@ -4929,13 +6778,14 @@ async fn cmd_db_migrations_list(
     let table = if args.verbose {
         // If verbose mode is enabled, include the migration's ID as well as the
         // source and target updated timestamps.
-        #[derive(Tabled)]
+        #[derive(Serialize, Tabled)]
         #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
         struct VerboseMigrationRow {
             created: chrono::DateTime<Utc>,
             id: Uuid,
             instance: Uuid,
             #[tabled(inline)]
+            #[serde(flatten)]
             vmms: Vmms,
             #[tabled(display_with = "display_option_blank")]
             src_updated: Option<chrono::DateTime<Utc>>,
@@ -4955,6 +6805,10 @@ async fn cmd_db_migrations_list(
             deleted: m.time_deleted,
         });
 
+        if let DbOutputFormat::Json = fetch_opts.format {
+            return print_json(rows);
+        }
+
         tabled::Table::new(rows)
             .with(tabled::settings::Style::empty())
             .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -4962,11 +6816,12 @@ async fn cmd_db_migrations_list(
     } else if args.instance_ids.len() == 1 {
         // If only the migrations for a single instance are shown, we omit the
         // instance ID row for conciseness sake.
-        #[derive(Tabled)]
+        #[derive(Serialize, Tabled)]
         #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
         struct SingleInstanceMigrationRow {
             created: chrono::DateTime<Utc>,
             #[tabled(inline)]
+            #[serde(flatten)]
             vmms: Vmms,
         }
         let rows = migrations.into_iter().map(|m| SingleInstanceMigrationRow {
@@ -4974,6 +6829,10 @@ async fn cmd_db_migrations_list(
             vmms: Vmms::from(&m),
         });
 
+        if let DbOutputFormat::Json = fetch_opts.format {
+            return print_json(rows);
+        }
+
         tabled::Table::new(rows)
             .with(tabled::settings::Style::empty())
             .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -4981,12 +6840,13 @@ async fn cmd_db_migrations_list(
     } else {
         // Otherwise, the default format includes the instance ID, but omits
         // most of the timestamps for brevity.
-        #[derive(Tabled)]
+        #[derive(Serialize, Tabled)]
         #[tabled(rename_all = "SCREAMING_SNAKE_CASE")]
         struct MigrationRow {
             created: chrono::DateTime<Utc>,
             instance: Uuid,
             #[tabled(inline)]
+            #[serde(flatten)]
             vmms: Vmms,
         }
 
@@ -4996,6 +6856,10 @@ async fn cmd_db_migrations_list(
             vmms: Vmms::from(&m),
         });
 
+        if let DbOutputFormat::Json = fetch_opts.format {
+            return print_json(rows);
+        }
+
         tabled::Table::new(rows)
             .with(tabled::settings::Style::empty())
             .with(tabled::settings::Padding::new(0, 1, 0, 0))
@@ -1030,6 +1030,33 @@ fn print_task_details(bgtask: &BackgroundTask, details: &serde_json::Value) {
 
             /// how many region replacements could not be started
             region_replacement_started_err: usize,
+
+            /// distinct errors hit during the activation, with how many
+            /// times each occurred
+            #[serde(default)]
+            region_replacement_errors: Vec<RegionReplacementError>,
+
+            /// how many further distinct errors were not reported
+            #[serde(default)]
+            region_replacement_errors_dropped: usize,
+
+            /// how many regions were examined, and how long the datastore
+            /// queries took
+            #[serde(default)]
+            region_replacement_scan: Option<RegionReplacementScan>,
+        }
+
+        #[derive(Deserialize)]
+        struct RegionReplacementScan {
+            regions_scanned: usize,
+            find_regions_ms: u64,
+            get_requested_ms: Option<u64>,
+        }
+
+        #[derive(Deserialize)]
+        struct RegionReplacementError {
+            message: String,
+            count: usize,
         }
 
         match serde_json::from_value::<TaskSuccess>(details.clone()) {
@@ -1046,6 +1073,30 @@ fn print_task_details(bgtask: &BackgroundTask, details: &serde_json::Value) {
                     "    number of region replacement start errors: {}",
                     success.region_replacement_started_err
                 );
+                if let Some(scan) = &success.region_replacement_scan {
+                    println!(
+                        "    number of regions scanned: {}",
+                        scan.regions_scanned
+                    );
+                    println!(
+                        "    time to find regions to replace: {}ms",
+                        scan.find_regions_ms
+                    );
+                    if let Some(ms) = scan.get_requested_ms {
+                        println!(
+                            "    time to list requested replacements: {ms}ms"
+                        );
+                    }
+                }
+                for error in &success.region_replacement_errors {
+                    println!("    error (x{}): {}", error.count, error.message);
+                }
+                if success.region_replacement_errors_dropped > 0 {
+                    println!(
+                        "    {} more distinct errors not shown",
+                        success.region_replacement_errors_dropped
+                    );
+                }
             }
         };
     } else if name == "instance_watcher" {
@@ -10,11 +10,15 @@
 use dropshot::Method;
 use expectorate::assert_contents;
 use http::StatusCode;
+use nexus_test_utils::resource_helpers::create_default_ip_pool;
+use nexus_test_utils::resource_helpers::create_instance_with;
+use nexus_test_utils::resource_helpers::create_project;
 use nexus_test_utils::{OXIMETER_UUID, PRODUCER_UUID};
 use nexus_test_utils_macros::nexus_test;
 use nexus_types::deployment::Blueprint;
 use nexus_types::deployment::SledFilter;
 use nexus_types::deployment::UnstableReconfiguratorState;
+use nexus_types::external_api::params;
 use omicron_test_utils::dev::test_cmds::path_to_executable;
 use omicron_test_utils::dev::test_cmds::redact_extra;
 use omicron_test_utils::dev::test_cmds::run_command;
@@ -75,6 +79,26 @@ async fn test_omdb_usage_errors() {
         &["db"],
         &["db", "--help"],
         &["db", "disks"],
+        // Time ranges that can't match anything
+        &[
+            "db",
+            "disks",
+            "list",
+            "--created-after",
+            "2024-06-02T00:00:00Z",
+            "--created-before",
+            "2024-06-01T00:00:00Z",
+        ],
+        &[
+            "db",
+            "instances",
+            "--created-after",
+            "2024-06-01T00:00:00Z",
+            "--created-before",
+            "2024-06-01T00:00:00Z",
+        ],
+        // JSON output for a command that doesn't support it
+        &["db", "dns", "show", "--format", "json"],
         &["db", "dns"],
         &["db", "dns", "diff"],
         &["db", "dns", "names"],
@@ -156,12 +180,15 @@ async fn test_omdb_success_cases(cptestctx: &ControlPlaneTestContext) {
     let invocations: &[&[&str]] = &[
         &["db", "disks", "list"],
         &["db", "dns", "show"],
+        &["db", "dns", "show", "--history", "2"],
         &["db", "dns", "diff", "external", "2"],
         &["db", "dns", "names", "external", "2"],
         &["db", "instances"],
+        &["db", "network", "list-nics"],
         &["db", "reconfigurator-save", tmppath.as_str()],
         &["db", "sleds"],
         &["db", "sleds", "-F", "discretionary"],
+        &["db", "snapshots", "list"],
         &["mgs", "inventory"],
         &["nexus", "background-tasks", "doc"],
         &["nexus", "background-tasks", "show"],
@@ -216,21 +243,21 @@ async fn test_omdb_success_cases(cptestctx: &ControlPlaneTestContext) {
     if initial_blueprint.cockroachdb_setting_preserve_downgrade.is_set() {
         redactions.variable_length("cockroachdb_version", &crdb_version);
     }
+    let omdb_env = [
+        ("OMDB_DB_URL", postgres_url.to_string()),
+        ("OMDB_NEXUS_URL", nexus_internal_url.clone()),
+        ("OMDB_MGS_URL", mgs_url.clone()),
+        ("OMDB_OXIMETER_URL", ox_url.clone()),
+        ("OMDB_CLICKHOUSE_URL", ch_url.clone()),
+    ];
     for args in invocations {
         println!("running commands with args: {:?}", args);
-        let p = postgres_url.to_string();
-        let u = nexus_internal_url.clone();
-        let g = mgs_url.clone();
-        let ox = ox_url.clone();
-        let ch = ch_url.clone();
+        let env = omdb_env.clone();
         do_run_extra(
             &mut output,
             move |exec| {
-                exec.env("OMDB_DB_URL", &p)
-                    .env("OMDB_NEXUS_URL", &u)
-                    .env("OMDB_MGS_URL", &g)
-                    .env("OMDB_OXIMETER_URL", &ox)
-                    .env("OMDB_CLICKHOUSE_URL", &ch)
+                env.into_iter()
+                    .fold(exec, |exec, (name, value)| exec.env(name, value))
             },
             &cmd_path,
             args,
@@ -239,6 +266,49 @@ async fn test_omdb_success_cases(cptestctx: &ControlPlaneTestContext) {
         .await;
     }
 
+    // Some commands need an instance to look at.  Create one only now so that
+    // it doesn't show up in the output of the commands above (e.g., as a saga
+    // in `nexus sagas list`).
+    let client = &cptestctx.external_client;
+    create_default_ip_pool(client).await;
+    create_project(client, "test-project").await;
+    let instance = create_instance_with(
+        client,
+        "test-project",
+        "test-instance",
+        &params::InstanceNetworkInterfaceAttachment::None,
+        Vec::new(),
+        Vec::new(),
+        false,
+    )
+    .await;
+    let instance_id = instance.identity.id.to_string();
+    let instance_updated = instance.runtime.time_run_state_updated.to_string();
+    let mut instance_redactions = redactions.clone();
+    instance_redactions.variable_length("instance_updated", &instance_updated);
+
+    let invocations: &[&[&str]] = &[
+        &["db", "instances", "info", &instance_id],
+        &["db", "instances", "info", "--events", &instance_id],
+        &["db", "instances", "--created-after", "2000-01-01T00:00:00Z"],
+        &["db", "instances", "--created-before", "2000-01-01T00:00:00Z"],
+    ];
+    for args in invocations {
+        println!("running commands with args: {:?}", args);
+        let env = omdb_env.clone();
+        do_run_extra(
+            &mut output,
+            move |exec| {
+                env.into_iter()
+                    .fold(exec, |exec, (name, value)| exec.env(name, value))
+            },
+            &cmd_path,
+            args,
+            Some(&instance_redactions),
+        )
+        .await;
+    }
+
     assert_contents("tests/successes.out", &output);
 
     // The `reconfigurator-save` output is not easy to compare as a string.  But
@@ -12,6 +12,7 @@ use clap::ValueEnum;
 use clap::{Args, Parser, Subcommand};
 use dns_service_client::DnsDiff;
 use indexmap::IndexMap;
+use nexus_inventory::InventoryError;
 use nexus_reconfigurator_execution::blueprint_external_dns_config;
 use nexus_reconfigurator_execution::blueprint_internal_dns_config;
 use nexus_reconfigurator_planning::blueprint_builder::BlueprintBuilder;
@@ -23,12 +24,15 @@ use nexus_reconfigurator_planning::system::{
 use nexus_sled_agent_shared::inventory::OmicronZonesConfig;
 use nexus_sled_agent_shared::inventory::SledRole;
 use nexus_sled_agent_shared::inventory::ZoneKind;
+use nexus_types::deployment::BlueprintOrCollectionZoneConfig;
+use nexus_types::deployment::BlueprintZoneDisposition;
 use nexus_types::deployment::BlueprintZoneFilter;
 use nexus_types::deployment::OmicronZoneNic;
 use nexus_types::deployment::PlanningInput;
 use nexus_types::deployment::SledFilter;
 use nexus_types::deployment::{Blueprint, UnstableReconfiguratorState};
 use nexus_types::internal_api::params::DnsConfigParams;
+use nexus_types::internal_api::params::DnsConfigZone;
 use nexus_types::inventory::Collection;
 use omicron_common::api::external::Generation;
 use omicron_common::api::external::Name;
@@ -37,10 +41,18 @@ use omicron_uuid_kinds::GenericUuid;
 use omicron_uuid_kinds::OmicronZoneUuid;
 use omicron_uuid_kinds::SledUuid;
 use omicron_uuid_kinds::VnicUuid;
+use reedline::MenuBuilder;
 use reedline::{Reedline, Signal};
+use serde_json::json;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::io::BufRead;
-use swrite::{swriteln, SWrite};
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::sync::Mutex;
+use swrite::{swrite, swriteln, SWrite};
 use tabled::Tabled;
 use uuid::Uuid;
 
@@ -73,10 +85,57 @@ struct ReconfiguratorSim {
     /// External DNS zone name configured
     external_dns_zone_name: String,
 
+    /// blueprint most recently executed with `blueprint-execute`
+    target_blueprint: Option<Uuid>,
+
     /// Policy overrides
     num_nexus: Option<u16>,
 
     log: slog::Logger,
+
+    /// current verbosity of `log`, adjustable with `set log-level`
+    log_level: LogLevelHandle,
+
+    /// whether to report how long each command took
+    show_timing: bool,
+
+    /// whether `blueprint-plan` fails if the planner could not satisfy the
+    /// policy (e.g., too few sleds for the target number of Nexus zones)
+    ///
+    /// Off by default, in which case the planner's best-effort blueprint is
+    /// kept and the shortfall is only reported in the log.
+    strict_planning: bool,
+}
+
+/// Shared handle to the minimum level of messages emitted by the REPL logger
+///
+/// The logger's drain consults this on every record, so changing it takes
+/// effect immediately for every logger derived from the root one.
+#[derive(Clone, Debug)]
+struct LogLevelHandle(Arc<AtomicUsize>);
+
+impl LogLevelHandle {
+    fn new(level: slog::Level) -> LogLevelHandle {
+        LogLevelHandle(Arc::new(AtomicUsize::new(level.as_usize())))
+    }
+
+    fn get(&self) -> slog::Level {
+        slog::Level::from_usize(self.0.load(Ordering::Relaxed))
+            .expect("stored log level is valid")
+    }
+
+    fn set(&self, level: slog::Level) {
+        self.0.store(level.as_usize(), Ordering::Relaxed);
+    }
+
+    /// Wraps `log` in a drain that filters records based on this level
+    fn filtered_logger(&self, log: slog::Logger) -> slog::Logger {
+        let handle = self.clone();
+        let drain = slog::Filter::new(log, move |record: &slog::Record| {
+            record.level().is_at_least(handle.get())
+        });
+        slog::Logger::root(slog::Drain::fuse(drain), slog::o!())
+    }
 }
 
 impl ReconfiguratorSim {
@@ -134,9 +193,10 @@ impl ReconfiguratorSim {
         //   executing and newer if other blueprints have changed DNS in the
         //   meantime).
         //
-        // But in this CLI, there's no execution at all.  As a result, there's
-        // no way to really choose between these -- and it doesn't really
-        // matter, either.  We'll just pick the parent blueprint's.
+        // In this CLI, execution only happens when the user asks for it with
+        // `blueprint-execute`, so there's no way to really choose between
+        // these -- and it doesn't really matter, either.  We'll just pick the
+        // parent blueprint's.
         builder.set_internal_dns_version(parent_blueprint.internal_dns_version);
         builder.set_external_dns_version(parent_blueprint.external_dns_version);
 
@@ -170,6 +230,15 @@ impl ReconfiguratorSim {
 #[derive(Parser, Debug)]
 struct CmdReconfiguratorSim {
     input_file: Option<Utf8PathBuf>,
+
+    /// only check that every command in the input file parses, without
+    /// running any of them
+    #[clap(long, requires = "input_file")]
+    check: bool,
+
+    /// print how long each command took to run
+    #[clap(long)]
+    time: bool,
 }
 
 // REPL implementation
@@ -177,11 +246,22 @@ struct CmdReconfiguratorSim {
 fn main() -> anyhow::Result<()> {
     let cmd = CmdReconfiguratorSim::parse();
 
-    let log = dropshot::ConfigLogging::StderrTerminal {
-        level: dropshot::ConfigLoggingLevel::Debug,
+    if cmd.check {
+        // `requires` ensures that an input file was provided.
+        let input_file = cmd.input_file.as_ref().unwrap();
+        return check_file(input_file);
     }
-    .to_logger("reconfigurator-sim")
-    .context("creating logger")?;
+
+    // The underlying logger accepts everything; `log_level` controls what
+    // actually gets emitted so that it can be changed at runtime.
+    let log_level = LogLevelHandle::new(slog::Level::Debug);
+    let log = log_level.filtered_logger(
+        dropshot::ConfigLogging::StderrTerminal {
+            level: dropshot::ConfigLoggingLevel::Trace,
+        }
+        .to_logger("reconfigurator-sim")
+        .context("creating logger")?,
+    );
 
     let mut sim = ReconfiguratorSim {
         system: SystemDescription::new(),
@@ -190,9 +270,13 @@ fn main() -> anyhow::Result<()> {
         internal_dns: BTreeMap::new(),
         external_dns: BTreeMap::new(),
         log,
+        log_level,
+        show_timing: cmd.time,
         silo_names: vec!["example-silo".parse().unwrap()],
         external_dns_zone_name: String::from("oxide.example"),
+        target_blueprint: None,
         num_nexus: None,
+        strict_planning: false,
     };
 
     if let Some(input_file) = cmd.input_file {
@@ -202,15 +286,22 @@ fn main() -> anyhow::Result<()> {
         for maybe_buffer in bufread.lines() {
             let buffer = maybe_buffer
                 .with_context(|| format!("read {:?}", &input_file))?;
+            // Lines starting with "#" are comments.  Echo them so that they
+            // still annotate the output, but don't try to run them.
+            if buffer.trim_start().starts_with('#') {
+                println!("{}", buffer);
+                continue;
+            }
             println!("> {}", buffer);
-            match process_entry(&mut sim, buffer) {
+            match process_entry(&mut sim, buffer, false) {
                 LoopResult::Continue => (),
                 LoopResult::Bail(error) => return Err(error),
             }
             println!("");
         }
     } else {
-        let mut ed = Reedline::create();
+        let known_ids = KnownIds::default();
+        let mut ed = line_editor(known_ids.clone());
         let prompt = reedline::DefaultPrompt::new(
             reedline::DefaultPromptSegment::Empty,
             reedline::DefaultPromptSegment::Empty,
@@ -218,10 +309,11 @@ fn main() -> anyhow::Result<()> {
         loop {
             match ed.read_line(&prompt) {
                 Ok(Signal::Success(buffer)) => {
-                    match process_entry(&mut sim, buffer) {
+                    match process_entry(&mut sim, buffer, true) {
                         LoopResult::Continue => (),
                         LoopResult::Bail(error) => return Err(error),
                     }
+                    known_ids.update(&sim);
                 }
                 Ok(Signal::CtrlD) | Ok(Signal::CtrlC) => break,
                 Err(error) => {
@@ -234,6 +326,160 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Returns the line editor used for the interactive REPL
+///
+/// This completes command names and the ids in `known_ids` on Tab and, if we
+/// can find a place to put it, keeps command history across sessions.
+fn line_editor(known_ids: KnownIds) -> Reedline {
+    let mut keybindings = reedline::default_emacs_keybindings();
+    keybindings.add_binding(
+        reedline::KeyModifiers::NONE,
+        reedline::KeyCode::Tab,
+        reedline::ReedlineEvent::UntilFound(vec![
+            reedline::ReedlineEvent::Menu(String::from(COMPLETION_MENU)),
+            reedline::ReedlineEvent::MenuNext,
+        ]),
+    );
+    let completion_menu =
+        reedline::ColumnarMenu::default().with_name(COMPLETION_MENU);
+    let mut ed = Reedline::create()
+        .with_completer(Box::new(ReplCompleter { known_ids }))
+        .with_menu(reedline::ReedlineMenu::EngineCompleter(Box::new(
+            completion_menu,
+        )))
+        .with_edit_mode(Box::new(reedline::Emacs::new(keybindings)));
+
+    match history_file() {
+        Ok(path) => match reedline::FileBackedHistory::with_file(
+            HISTORY_SIZE,
+            path.clone().into(),
+        ) {
+            Ok(history) => ed = ed.with_history(Box::new(history)),
+            Err(error) => {
+                eprintln!("warning: not saving history to {path}: {error}");
+            }
+        },
+        Err(error) => eprintln!("warning: not saving history: {error:#}"),
+    }
+
+    ed
+}
+
+const COMPLETION_MENU: &str = "completion_menu";
+
+/// number of commands kept in the history file
+const HISTORY_SIZE: usize = 1000;
+
+/// Returns the path of the file used to keep REPL history across sessions,
+/// creating its parent directory if needed
+fn history_file() -> anyhow::Result<Utf8PathBuf> {
+    let dir = history_dir(
+        std::env::var("XDG_DATA_HOME").ok(),
+        std::env::var("HOME").ok(),
+    )?;
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("create directory {dir:?}"))?;
+    Ok(dir.join("history.txt"))
+}
+
+/// Returns the directory in which to keep REPL history, given the values of
+/// the `XDG_DATA_HOME` and `HOME` environment variables
+///
+/// This follows the XDG convention for user data: `$XDG_DATA_HOME` if set,
+/// otherwise `$HOME/.local/share`.
+fn history_dir(
+    xdg_data_home: Option<String>,
+    home: Option<String>,
+) -> anyhow::Result<Utf8PathBuf> {
+    let data_dir = match (xdg_data_home, home) {
+        (Some(dir), _) if !dir.is_empty() => Utf8PathBuf::from(dir),
+        (_, Some(home)) if !home.is_empty() => {
+            Utf8PathBuf::from(home).join(".local").join("share")
+        }
+        _ => bail!("neither XDG_DATA_HOME nor HOME is set"),
+    };
+    Ok(data_dir.join("reconfigurator-cli"))
+}
+
+/// Ids of blueprints and collections that the REPL can complete
+///
+/// The completer is owned by the line editor, so it can't look at the REPL
+/// state directly.  Instead, the REPL updates this after each command.
+#[derive(Clone, Debug, Default)]
+struct KnownIds(Arc<Mutex<Vec<String>>>);
+
+impl KnownIds {
+    fn update(&self, sim: &ReconfiguratorSim) {
+        let ids = sim
+            .blueprints
+            .keys()
+            .map(|id| id.to_string())
+            .chain(sim.collections.keys().map(|id| id.to_string()))
+            .collect();
+        *self.0.lock().unwrap() = ids;
+    }
+
+    fn all(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Completes REPL command names (and the names of subcommands of commands that
+/// have them) and the ids of known blueprints and collections
+struct ReplCompleter {
+    known_ids: KnownIds,
+}
+
+impl reedline::Completer for ReplCompleter {
+    fn complete(
+        &mut self,
+        line: &str,
+        pos: usize,
+    ) -> Vec<reedline::Suggestion> {
+        let line = &line[..pos];
+        let word_start = line
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        let word = &line[word_start..];
+        let previous_words: Vec<_> =
+            line[..word_start].split_whitespace().collect();
+
+        let command = TopLevelArgs::command();
+        let candidates = match previous_words.as_slice() {
+            [] => subcommand_names(&command),
+            [name] => match command.find_subcommand(name) {
+                Some(subcommand) if subcommand.has_subcommands() => {
+                    subcommand_names(subcommand)
+                }
+                _ => self.known_ids.all(),
+            },
+            _ => self.known_ids.all(),
+        };
+
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .map(|value| reedline::Suggestion {
+                value,
+                span: reedline::Span::new(word_start, pos),
+                append_whitespace: true,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+fn subcommand_names(command: &clap::Command) -> Vec<String> {
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect()
+}
+
 /// Describes next steps after evaluating one "line" of user input
 ///
 /// This could just be `Result`, but it's easy to misuse that here because
@@ -248,8 +494,83 @@ enum LoopResult {
     Bail(anyhow::Error),
 }
 
+/// Error returned by commands that check some condition (like
+/// `assert-diff-empty`) when that condition does not hold
+///
+/// When reading commands from a file, this is fatal so that scripts can use
+/// these commands to check their results.  Interactively, it's reported like
+/// any other error.
+#[derive(Debug)]
+struct AssertionFailed(String);
+
+impl std::fmt::Display for AssertionFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "assertion failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for AssertionFailed {}
+
+/// Parses one "line" of user input as a REPL command
+fn parse_entry(entry: &str) -> Result<Commands, clap::Error> {
+    // Split the line the way a shell would, so that arguments containing
+    // whitespace can be quoted.
+    let parts = shell_words::split(entry).map_err(|error| {
+        TopLevelArgs::command().error(
+            clap::error::ErrorKind::InvalidValue,
+            format!("failed to parse command line: {}", error),
+        )
+    })?;
+    TopLevelArgs::command()
+        .multicall(true)
+        .try_get_matches_from(parts)
+        .and_then(|matches| TopLevelArgs::from_arg_matches(&matches))
+        .map(|TopLevelArgs { command }| command)
+}
+
+/// Checks that every command in `input_file` parses, without running any of
+/// them
+///
+/// Fails with the first line that doesn't parse.
+fn check_file(input_file: &Utf8PathBuf) -> anyhow::Result<()> {
+    let file = std::fs::File::open(input_file)
+        .with_context(|| format!("open {:?}", input_file))?;
+    let bufread = std::io::BufReader::new(file);
+    for (i, maybe_buffer) in bufread.lines().enumerate() {
+        let buffer =
+            maybe_buffer.with_context(|| format!("read {:?}", input_file))?;
+        let trimmed = buffer.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Err(error) = parse_entry(&buffer) {
+            // Asking for help is fine in a script; it just prints something.
+            if error.kind() != clap::error::ErrorKind::DisplayHelp {
+                bail!(
+                    "{}:{}: {:?}: {}",
+                    input_file,
+                    i + 1,
+                    buffer,
+                    error.render()
+                );
+            }
+        }
+    }
+
+    println!("ok");
+    Ok(())
+}
+
 /// Processes one "line" of user input.
-fn process_entry(sim: &mut ReconfiguratorSim, entry: String) -> LoopResult {
+///
+/// `interactive` indicates whether the input came from a user at the REPL (as
+/// opposed to a file).
+fn process_entry(
+    sim: &mut ReconfiguratorSim,
+    entry: String,
+    interactive: bool,
+) -> LoopResult {
     // If no input was provided, take another lap (print the prompt and accept
     // another line).  This gets handled specially because otherwise clap would
     // treat this as a usage error and print a help message, which isn't what we
@@ -258,17 +579,7 @@ fn process_entry(sim: &mut ReconfiguratorSim, entry: String) -> LoopResult {
         return LoopResult::Continue;
     }
 
-    // Parse the line of input as a REPL command.
-    //
-    // Using `split_whitespace()` like this is going to be a problem if we ever
-    // want to support arguments with whitespace in them (using quotes).  But
-    // it's good enough for now.
-    let parts = entry.split_whitespace();
-    let parsed_command = TopLevelArgs::command()
-        .multicall(true)
-        .try_get_matches_from(parts)
-        .and_then(|matches| TopLevelArgs::from_arg_matches(&matches));
-    let command = match parsed_command {
+    let command = match parse_entry(&entry) {
         Err(error) => {
             // We failed to parse the command.  Print the error.
             return match error.print() {
@@ -281,42 +592,59 @@ fn process_entry(sim: &mut ReconfiguratorSim, entry: String) -> LoopResult {
                 ),
             };
         }
-        Ok(TopLevelArgs { command }) => command,
+        Ok(command) => command,
     };
 
     // Dispatch to the command's handler.
+    let start = std::time::Instant::now();
     let cmd_result = match command {
         Commands::SledList => cmd_sled_list(sim),
         Commands::SledAdd(args) => cmd_sled_add(sim, args),
         Commands::SledShow(args) => cmd_sled_show(sim, args),
-        Commands::SiloList => cmd_silo_list(sim),
+        Commands::SledRemove(args) => cmd_sled_remove(sim, args),
+        Commands::SiloList(args) => cmd_silo_list(sim, args),
         Commands::SiloAdd(args) => cmd_silo_add(sim, args),
         Commands::SiloRemove(args) => cmd_silo_remove(sim, args),
+        Commands::SiloRename(args) => cmd_silo_rename(sim, args),
+        Commands::SiloLoad(args) => cmd_silo_load(sim, args),
         Commands::InventoryList => cmd_inventory_list(sim),
-        Commands::InventoryGenerate => cmd_inventory_generate(sim),
+        Commands::InventoryGenerate(args) => cmd_inventory_generate(sim, args),
         Commands::BlueprintList => cmd_blueprint_list(sim),
         Commands::BlueprintEdit(args) => cmd_blueprint_edit(sim, args),
+        Commands::BlueprintCopy(args) => cmd_blueprint_copy(sim, args),
         Commands::BlueprintPlan(args) => cmd_blueprint_plan(sim, args),
+        Commands::ExplainPlan(args) => cmd_explain_plan(sim, args),
         Commands::BlueprintShow(args) => cmd_blueprint_show(sim, args),
         Commands::BlueprintDiff(args) => cmd_blueprint_diff(sim, args),
+        Commands::AssertDiffEmpty(args) => cmd_assert_diff_empty(sim, args),
+        Commands::BlueprintValidate(args) => cmd_blueprint_validate(sim, args),
         Commands::BlueprintDiffDns(args) => cmd_blueprint_diff_dns(sim, args),
         Commands::BlueprintDiffInventory(args) => {
             cmd_blueprint_diff_inventory(sim, args)
         }
         Commands::BlueprintSave(args) => cmd_blueprint_save(sim, args),
+        Commands::BlueprintExecute(args) => cmd_blueprint_execute(sim, args),
         Commands::Show => cmd_show(sim),
         Commands::Set(args) => cmd_set(sim, args),
         Commands::Load(args) => cmd_load(sim, args),
         Commands::FileContents(args) => cmd_file_contents(args),
         Commands::Save(args) => cmd_save(sim, args),
     };
+    let elapsed = start.elapsed();
 
     match cmd_result {
+        Err(error) if !interactive && error.is::<AssertionFailed>() => {
+            return LoopResult::Bail(error);
+        }
         Err(error) => println!("error: {:#}", error),
         Ok(Some(s)) => println!("{}", s),
         Ok(None) => (),
     }
 
+    if sim.show_timing {
+        println!("(took {}ms)", elapsed.as_millis());
+    }
+
     LoopResult::Continue
 }
 
@@ -337,35 +665,62 @@ enum Commands {
     SledAdd(SledAddArgs),
     /// show details about one sled
     SledShow(SledArgs),
+    /// remove a sled (and its zpools) from the system
+    SledRemove(SledArgs),
 
     /// list silos
-    SiloList,
+    SiloList(SiloListArgs),
     /// add a silo
     SiloAdd(SiloAddRemoveArgs),
     /// remove a silo
     SiloRemove(SiloAddRemoveArgs),
+    /// rename a silo
+    SiloRename(SiloRenameArgs),
+    /// replace the set of silos with the names listed in a file
+    SiloLoad(SiloLoadArgs),
 
     /// list all inventory collections
     InventoryList,
     /// generates an inventory collection from the configured sleds
-    InventoryGenerate,
+    InventoryGenerate(InventoryGenerateArgs),
 
     /// list all blueprints
     BlueprintList,
     /// run planner to generate a new blueprint
     BlueprintPlan(BlueprintPlanArgs),
+    /// run planner and explain the changes it would make (without saving the
+    /// resulting blueprint)
+    ExplainPlan(BlueprintPlanArgs),
     /// edit contents of a blueprint directly
     BlueprintEdit(BlueprintEditArgs),
+    /// copy a blueprint to a new one (with a new id) whose parent is the
+    /// original
+    BlueprintCopy(BlueprintCopyArgs),
     /// show details about a blueprint
     BlueprintShow(BlueprintArgs),
     /// show differences between two blueprints
     BlueprintDiff(BlueprintDiffArgs),
+    /// fail if there are any differences between two blueprints (including
+    /// their DNS contents)
+    ///
+    /// When commands are read from a file, a non-empty diff causes the program
+    /// to exit with an error.
+    AssertDiffEmpty(AssertDiffEmptyArgs),
+    /// check a blueprint for internal consistency against the configured
+    /// system, reporting each problem found
+    ///
+    /// When commands are read from a file, any problem causes the program to
+    /// exit with an error.
+    BlueprintValidate(BlueprintValidateArgs),
     /// show differences between a blueprint and a particular DNS version
     BlueprintDiffDns(BlueprintDiffDnsArgs),
     /// show differences between a blueprint and an inventory collection
     BlueprintDiffInventory(BlueprintDiffInventoryArgs),
     /// write one blueprint to a file
     BlueprintSave(BlueprintSaveArgs),
+    /// simulate executing a blueprint: make it the target and write new
+    /// internal and external DNS generations reflecting its contents
+    BlueprintExecute(BlueprintExecuteArgs),
 
     /// show system properties
     Show,
@@ -393,12 +748,47 @@ struct SledArgs {
     sled_id: SledUuid,
 }
 
+#[derive(Debug, Args)]
+struct InventoryGenerateArgs {
+    /// number of synthetic collection errors to record
+    #[arg(long, default_value_t = 0)]
+    errors: u32,
+
+    /// report each sled's Omicron zones as deployed by this blueprint
+    /// (default: report no zones on any sled)
+    #[arg(long)]
+    from_blueprint: Option<Uuid>,
+}
+
+#[derive(Debug, Args)]
+struct SiloListArgs {
+    /// print only the number of silos
+    #[arg(long)]
+    count: bool,
+}
+
 #[derive(Debug, Args)]
 struct SiloAddRemoveArgs {
     /// name of the silo
     silo_name: Name,
 }
 
+#[derive(Debug, Args)]
+struct SiloRenameArgs {
+    /// current name of the silo
+    old_name: Name,
+    /// new name of the silo
+    new_name: Name,
+}
+
+#[derive(Debug, Args)]
+struct SiloLoadArgs {
+    /// file containing one silo name per line
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    filename: Utf8PathBuf,
+}
+
 #[derive(Debug, Args)]
 struct InventoryArgs {
     /// id of the inventory collection to use in planning
@@ -411,6 +801,14 @@ struct BlueprintPlanArgs {
     parent_blueprint_id: Uuid,
     /// id of the inventory collection to use in planning
     collection_id: CollectionUuid,
+    /// internal DNS version to plan against (default: the parent
+    /// blueprint's)
+    #[arg(long)]
+    internal_dns_version: Option<u32>,
+    /// external DNS version to plan against (default: the parent
+    /// blueprint's)
+    #[arg(long)]
+    external_dns_version: Option<u32>,
 }
 
 #[derive(Debug, Args)]
@@ -438,12 +836,29 @@ enum BlueprintEditCommands {
     AddCockroach { sled_id: SledUuid },
     /// expunge a particular zone from a particular sled
     ExpungeZone { sled_id: SledUuid, zone_id: OmicronZoneUuid },
+    /// expunge all zones on a particular sled that aren't already expunged
+    ExpungeSled { sled_id: SledUuid },
+}
+
+#[derive(Debug, Args)]
+struct BlueprintCopyArgs {
+    /// id of the blueprint to copy
+    blueprint_id: Uuid,
 }
 
 #[derive(Debug, Args)]
 struct BlueprintArgs {
     /// id of the blueprint
     blueprint_id: Uuid,
+    /// print the blueprint as JSON rather than as text
+    #[arg(long)]
+    json: bool,
+    /// write the blueprint to this file rather than printing it
+    #[arg(long)]
+    output: Option<Utf8PathBuf>,
+    /// overwrite the output file if it already exists
+    #[arg(long, requires = "output")]
+    force: bool,
 }
 
 #[derive(Debug, Args)]
@@ -470,6 +885,12 @@ struct BlueprintDiffInventoryArgs {
     blueprint_id: Uuid,
 }
 
+#[derive(Debug, Args)]
+struct BlueprintExecuteArgs {
+    /// id of the blueprint
+    blueprint_id: Uuid,
+}
+
 #[derive(Debug, Args)]
 struct BlueprintSaveArgs {
     /// id of the blueprint
@@ -484,14 +905,63 @@ struct BlueprintDiffArgs {
     blueprint1_id: Uuid,
     /// id of the second blueprint
     blueprint2_id: Uuid,
+    /// print only counts of what changed, rather than the full diff
+    #[arg(long)]
+    summary: bool,
+    /// print the diff as a JSON object rather than as text
+    #[arg(long, conflicts_with = "summary")]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct AssertDiffEmptyArgs {
+    /// id of the first blueprint
+    blueprint1_id: Uuid,
+    /// id of the second blueprint
+    blueprint2_id: Uuid,
+}
+
+#[derive(Debug, Args)]
+struct BlueprintValidateArgs {
+    /// id of the blueprint
+    blueprint_id: Uuid,
 }
 
 #[derive(Debug, Subcommand)]
 enum SetArgs {
-    /// target number of Nexus instances (for planning)
-    NumNexus { num_nexus: u16 },
+    /// target number of Nexus instances (for planning), or "default" to use
+    /// the planner's default
+    NumNexus { num_nexus: CliNumNexus },
     /// system's external DNS zone name (suffix)
     ExternalDnsZoneName { zone_name: String },
+    /// minimum level of log messages to print (e.g., "info", "debug")
+    LogLevel { level: String },
+    /// whether `blueprint-plan` fails when the policy can't be satisfied
+    StrictPlanning {
+        #[arg(action = clap::ArgAction::Set)]
+        strict: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+enum CliNumNexus {
+    Default,
+    Count(u16),
+}
+
+impl FromStr for CliNumNexus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "default" {
+            Ok(CliNumNexus::Default)
+        } else {
+            let count = s.parse().with_context(|| {
+                format!("expected a count or \"default\", found {:?}", s)
+            })?;
+            Ok(CliNumNexus::Count(count))
+        }
+    }
 }
 
 #[derive(Debug, Args)]
@@ -502,6 +972,11 @@ struct LoadArgs {
     /// id of inventory collection to use for sled details
     /// (may be omitted only if the file contains only one collection)
     collection_id: Option<CollectionUuid>,
+
+    /// merge DNS generations and silo names with those already loaded rather
+    /// than replacing them
+    #[arg(long)]
+    merge: bool,
 }
 
 #[derive(Debug, Args)]
@@ -520,9 +995,19 @@ struct SaveArgs {
 
 fn cmd_silo_list(
     sim: &mut ReconfiguratorSim,
+    args: SiloListArgs,
 ) -> anyhow::Result<Option<String>> {
+    if args.count {
+        return Ok(Some(format!("{}\n", sim.silo_names.len())));
+    }
+
+    // Sort for display only.  The stored order is left alone so that saved
+    // state is unchanged.
+    let mut silo_names: Vec<_> = sim.silo_names.iter().collect();
+    silo_names.sort();
+
     let mut s = String::new();
-    for silo_name in &sim.silo_names {
+    for silo_name in silo_names {
         swriteln!(s, "{}", silo_name);
     }
     Ok(Some(s))
@@ -552,6 +1037,58 @@ fn cmd_silo_remove(
     Ok(None)
 }
 
+fn cmd_silo_rename(
+    sim: &mut ReconfiguratorSim,
+    args: SiloRenameArgs,
+) -> anyhow::Result<Option<String>> {
+    if sim.silo_names.contains(&args.new_name) {
+        bail!("silo already exists: {:?}", &args.new_name);
+    }
+
+    let silo_name = sim
+        .silo_names
+        .iter_mut()
+        .find(|n| **n == args.old_name)
+        .ok_or_else(|| anyhow!("no such silo: {:?}", &args.old_name))?;
+    *silo_name = args.new_name;
+    Ok(None)
+}
+
+fn cmd_silo_load(
+    sim: &mut ReconfiguratorSim,
+    args: SiloLoadArgs,
+) -> anyhow::Result<Option<String>> {
+    let input_path = &args.filename;
+    let contents = std::fs::read_to_string(input_path)
+        .with_context(|| format!("read {:?}", input_path))?;
+
+    // Parse the whole file before changing anything so that a bad line leaves
+    // the existing silos alone.
+    let mut silo_names: Vec<Name> = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let silo_name: Name = line.parse().map_err(|error| {
+            anyhow!("{:?} line {}: {:?}: {}", input_path, i + 1, line, error)
+        })?;
+        if silo_names.contains(&silo_name) {
+            bail!(
+                "{:?} line {}: duplicate silo name {:?}",
+                input_path,
+                i + 1,
+                line
+            );
+        }
+        silo_names.push(silo_name);
+    }
+
+    let nsilos = silo_names.len();
+    sim.silo_names = silo_names;
+    Ok(Some(format!("loaded {} silo names from {:?}", nsilos, input_path)))
+}
+
 fn cmd_sled_list(
     sim: &mut ReconfiguratorSim,
 ) -> anyhow::Result<Option<String>> {
@@ -619,6 +1156,50 @@ fn cmd_sled_show(
     Ok(Some(s))
 }
 
+fn cmd_sled_remove(
+    sim: &mut ReconfiguratorSim,
+    args: SledArgs,
+) -> anyhow::Result<Option<String>> {
+    let sled_id = args.sled_id;
+    let _ = sim.system.sled_remove(sled_id)?;
+
+    let mut s = String::new();
+    swriteln!(s, "removed sled {}", sled_id);
+
+    // Point out anything that now refers to a sled that no longer exists.
+    let stale_collections: Vec<_> = sim
+        .collections
+        .values()
+        .filter(|c| c.sled_agents.contains_key(&sled_id))
+        .map(|c| c.id.to_string())
+        .collect();
+    if !stale_collections.is_empty() {
+        swriteln!(
+            s,
+            "collections still referencing sled: {}",
+            stale_collections.join(", ")
+        );
+    }
+    let stale_blueprints: Vec<_> = sim
+        .blueprints
+        .values()
+        .filter(|b| {
+            b.sled_state.contains_key(&sled_id)
+                || b.blueprint_zones.contains_key(&sled_id)
+                || b.blueprint_disks.contains_key(&sled_id)
+        })
+        .map(|b| b.id.to_string())
+        .collect();
+    if !stale_blueprints.is_empty() {
+        swriteln!(
+            s,
+            "blueprints still referencing sled: {}",
+            stale_blueprints.join(", ")
+        );
+    }
+    Ok(Some(s))
+}
+
 fn cmd_inventory_list(
     sim: &mut ReconfiguratorSim,
 ) -> anyhow::Result<Option<String>> {
@@ -650,30 +1231,55 @@ fn cmd_inventory_list(
 
 fn cmd_inventory_generate(
     sim: &mut ReconfiguratorSim,
+    args: InventoryGenerateArgs,
 ) -> anyhow::Result<Option<String>> {
     let mut builder =
         sim.system.to_collection_builder().context("generating inventory")?;
-    // For an inventory we just generated from thin air, pretend like each sled
-    // has no zones on it.
+    // If we were given a blueprint, report each sled's zones as that
+    // blueprint would have deployed them.  Otherwise, for an inventory we
+    // just generated from thin air, pretend like each sled has no zones on it.
+    let blueprint = args
+        .from_blueprint
+        .map(|blueprint_id| sim.blueprint_lookup(blueprint_id))
+        .transpose()?;
     let planning_input =
         sim.system.to_planning_input_builder().unwrap().build();
-    for sled_id in planning_input.all_sled_ids(SledFilter::Commissioned) {
-        builder
-            .found_sled_omicron_zones(
-                "fake sled agent",
-                sled_id,
-                OmicronZonesConfig {
+    let zones = planning_input
+        .all_sled_ids(SledFilter::Commissioned)
+        .map(|sled_id| {
+            let zones = blueprint
+                .and_then(|blueprint| blueprint.blueprint_zones.get(&sled_id))
+                .map(|zones| {
+                    zones.to_omicron_zones_config(
+                        BlueprintZoneFilter::ShouldBeRunning,
+                    )
+                })
+                .unwrap_or_else(|| OmicronZonesConfig {
                     generation: Generation::new(),
                     zones: vec![],
-                },
-            )
-            .context("recording Omicron zones")?;
+                });
+            (sled_id, zones)
+        })
+        .collect();
+    nexus_inventory::examples::with_omicron_zones(&mut builder, zones)
+        .context("recording Omicron zones")?;
+    for i in 0..args.errors {
+        builder.found_error(InventoryError::from(anyhow!(
+            "synthetic collection error {}",
+            i + 1
+        )));
     }
     let inventory = builder.build();
-    let rv = format!(
+    let mut rv = format!(
         "generated inventory collection {} from configured sleds",
         inventory.id
     );
+    if args.errors > 0 {
+        swrite!(rv, " (with {} synthetic errors)", args.errors);
+    }
+    if let Some(blueprint) = blueprint {
+        swrite!(rv, " (with zones from blueprint {})", blueprint.id);
+    }
     sim.collections.insert(inventory.id, inventory);
     Ok(Some(rv))
 }
@@ -710,7 +1316,35 @@ fn cmd_blueprint_plan(
         .get(&collection_id)
         .ok_or_else(|| anyhow!("no such collection: {}", collection_id))?;
     let creator = "reconfigurator-sim";
-    let planning_input = sim.planning_input(parent_blueprint)?;
+    let mut planning_input = sim.planning_input(parent_blueprint)?;
+
+    // By default, we plan against the parent blueprint's DNS versions (see
+    // `ReconfiguratorSim::planning_input()`).  The caller may override these
+    // to simulate DNS having moved on (or not yet caught up) relative to the
+    // parent.
+    if args.internal_dns_version.is_some()
+        || args.external_dns_version.is_some()
+    {
+        let mut builder = planning_input.into_builder();
+        if let Some(version) = args.internal_dns_version {
+            builder.set_internal_dns_version(dns_version_override(
+                CliDnsGroup::Internal,
+                version,
+                parent_blueprint.internal_dns_version,
+                &sim.internal_dns,
+            )?);
+        }
+        if let Some(version) = args.external_dns_version {
+            builder.set_external_dns_version(dns_version_override(
+                CliDnsGroup::External,
+                version,
+                parent_blueprint.external_dns_version,
+                &sim.external_dns,
+            )?);
+        }
+        planning_input = builder.build();
+    }
+
     let planner = Planner::new_based_on(
         sim.log.clone(),
         parent_blueprint,
@@ -720,6 +1354,16 @@ fn cmd_blueprint_plan(
     )
     .context("creating planner")?;
     let blueprint = planner.plan().context("generating blueprint")?;
+    if sim.strict_planning {
+        let shortfalls = planning_shortfalls(&planning_input, &blueprint);
+        if !shortfalls.is_empty() {
+            bail!(
+                "strict planning: generated blueprint does not satisfy the \
+                 policy: {}",
+                shortfalls.join("; ")
+            );
+        }
+    }
     let rv = format!(
         "generated blueprint {} based on parent blueprint {}",
         blueprint.id, parent_blueprint_id,
@@ -728,6 +1372,126 @@ fn cmd_blueprint_plan(
     Ok(Some(rv))
 }
 
+/// Validates a DNS version override for `blueprint-plan`
+///
+/// As in a real system, we can't plan against a DNS version that doesn't exist
+/// yet.  The newest version we know about is either the parent blueprint's or
+/// the newest one that we've loaded.
+fn dns_version_override(
+    dns_group: CliDnsGroup,
+    version: u32,
+    parent_version: Generation,
+    known: &BTreeMap<Generation, DnsConfigParams>,
+) -> anyhow::Result<Generation> {
+    let version = Generation::from(version);
+    let latest =
+        known.keys().last().map_or(parent_version, |&g| g.max(parent_version));
+    if version > latest {
+        bail!(
+            "{:?} DNS version {} is newer than the latest known version ({})",
+            dns_group,
+            version,
+            latest
+        );
+    }
+    Ok(version)
+}
+
+/// Returns a description of each way in which `blueprint` falls short of the
+/// zone counts targeted by `input`
+fn planning_shortfalls(
+    input: &PlanningInput,
+    blueprint: &Blueprint,
+) -> Vec<String> {
+    let targets = [
+        (ZoneKind::Nexus, input.target_nexus_zone_count()),
+        (ZoneKind::InternalDns, input.target_internal_dns_zone_count()),
+        (ZoneKind::CockroachDb, input.target_cockroachdb_zone_count()),
+        (ZoneKind::BoundaryNtp, input.target_boundary_ntp_zone_count()),
+    ];
+
+    let mut shortfalls = Vec::new();
+    for (kind, target) in targets {
+        let count = blueprint
+            .all_omicron_zones(BlueprintZoneFilter::ShouldBeRunning)
+            .filter(|(_, zone)| zone.zone_type.kind() == kind)
+            .count();
+        if count < target {
+            shortfalls.push(format!(
+                "{} {} zones (wanted {})",
+                count,
+                kind.report_str(),
+                target
+            ));
+        }
+    }
+    shortfalls
+}
+
+fn cmd_explain_plan(
+    sim: &mut ReconfiguratorSim,
+    args: BlueprintPlanArgs,
+) -> anyhow::Result<Option<String>> {
+    let parent_blueprint_id = args.parent_blueprint_id;
+    let collection_id = args.collection_id;
+    let parent_blueprint = sim.blueprint_lookup(parent_blueprint_id)?;
+    let collection = sim
+        .collections
+        .get(&collection_id)
+        .ok_or_else(|| anyhow!("no such collection: {}", collection_id))?;
+    let creator = "reconfigurator-sim";
+    let planning_input = sim.planning_input(parent_blueprint)?;
+    let planner = Planner::new_based_on(
+        sim.log.clone(),
+        parent_blueprint,
+        &planning_input,
+        creator,
+        collection,
+    )
+    .context("creating planner")?;
+    let (_, rationale) =
+        planner.plan_with_rationale().context("generating blueprint")?;
+
+    let mut s = String::new();
+    if rationale.is_empty() {
+        swriteln!(
+            s,
+            "planner made no changes to parent blueprint {}",
+            parent_blueprint_id
+        );
+    } else {
+        swriteln!(
+            s,
+            "planner changes to parent blueprint {} ({}):",
+            parent_blueprint_id,
+            rationale.len()
+        );
+        for reason in rationale {
+            swriteln!(s, "    {}", reason);
+        }
+    }
+    Ok(Some(s))
+}
+
+fn cmd_blueprint_copy(
+    sim: &mut ReconfiguratorSim,
+    args: BlueprintCopyArgs,
+) -> anyhow::Result<Option<String>> {
+    let blueprint_id = args.blueprint_id;
+    let mut new_blueprint = sim.blueprint_lookup(blueprint_id)?.clone();
+    new_blueprint.id = Uuid::new_v4();
+    new_blueprint.parent_blueprint_id = Some(blueprint_id);
+    new_blueprint.creator = String::from("reconfigurator-cli");
+    new_blueprint.comment = format!("copy of blueprint {}", blueprint_id);
+
+    let rv = format!(
+        "blueprint {} copied from blueprint {}",
+        new_blueprint.id, blueprint_id
+    );
+    sim.blueprint_insert_new(new_blueprint);
+    Ok(Some(rv))
+}
+
 fn cmd_blueprint_edit(
     sim: &mut ReconfiguratorSim,
     args: BlueprintEditArgs,
@@ -779,6 +1543,22 @@ fn cmd_blueprint_edit(
                 .context("failed to expunge zone")?;
             format!("expunged zone {zone_id} from sled {sled_id}")
         }
+        BlueprintEditCommands::ExpungeSled { sled_id } => {
+            let zone_ids: Vec<_> = builder
+                .current_sled_zones(sled_id, BlueprintZoneFilter::All)
+                .filter(|z| z.disposition != BlueprintZoneDisposition::Expunged)
+                .map(|z| z.id)
+                .collect();
+            if zone_ids.is_empty() {
+                bail!("sled {sled_id} has no zones that aren't expunged");
+            }
+            for zone_id in &zone_ids {
+                builder.sled_expunge_zone(sled_id, *zone_id).with_context(
+                    || format!("failed to expunge zone {zone_id}"),
+                )?;
+            }
+            format!("expunged {} zones from sled {sled_id}", zone_ids.len())
+        }
     };
 
     let mut new_blueprint = builder.build();
@@ -805,7 +1585,34 @@ fn cmd_blueprint_show(
     args: BlueprintArgs,
 ) -> anyhow::Result<Option<String>> {
     let blueprint = sim.blueprint_lookup(args.blueprint_id)?;
-    Ok(Some(format!("{}", blueprint.display())))
+    let output_str = if args.json {
+        serde_json::to_string_pretty(&blueprint)
+            .context("serializing blueprint")?
+    } else {
+        format!("{}", blueprint.display())
+    };
+    let Some(output_path) = args.output else {
+        return Ok(Some(output_str));
+    };
+
+    if args.force {
+        std::fs::write(&output_path, &output_str)
+            .with_context(|| format!("write {:?}", output_path))?;
+    } else {
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&output_path)
+            .with_context(|| {
+                format!("create {:?} (use --force to overwrite)", output_path)
+            })?;
+        file.write_all(output_str.as_bytes())
+            .with_context(|| format!("write {:?}", output_path))?;
+    }
+    Ok(Some(format!(
+        "wrote blueprint {} to {:?}",
+        args.blueprint_id, output_path
+    )))
 }
 
 fn cmd_blueprint_diff(
@@ -819,43 +1626,318 @@ fn cmd_blueprint_diff(
     let blueprint2 = sim.blueprint_lookup(blueprint2_id)?;
 
     let sled_diff = blueprint2.diff_since_blueprint(&blueprint1);
-    swriteln!(rv, "{}", sled_diff.display());
+    let dns_configs = BlueprintDnsConfigs::new(sim, blueprint1, blueprint2)?;
+
+    if args.summary {
+        let zones = &sled_diff.zones;
+        let zones_added: usize =
+            zones.added.values().map(|z| z.zones.len()).sum();
+        let zones_removed: usize =
+            zones.removed.values().map(|z| z.zones.len()).sum();
+        let zones_modified: usize =
+            zones.modified.values().map(|z| z.zones.len()).sum();
+        swriteln!(
+            rv,
+            "zones: {} added, {} removed, {} modified",
+            zones_added,
+            zones_removed,
+            zones_modified,
+        );
+        let dns_diff = dns_configs.internal_diff()?;
+        swriteln!(
+            rv,
+            "internal DNS names: {} added, {} removed",
+            dns_diff.names_added().count(),
+            dns_diff.names_removed().count(),
+        );
+        let dns_diff = dns_configs.external_diff()?;
+        swriteln!(
+            rv,
+            "external DNS names: {} added, {} removed",
+            dns_diff.names_added().count(),
+            dns_diff.names_removed().count(),
+        );
+        return Ok(Some(rv));
+    }
 
-    // Diff'ing DNS is a little trickier.  First, compute what DNS should be for
-    // each blueprint.  To do that we need to construct a list of sleds suitable
-    // for the executor.
-    let sleds_by_id = make_sleds_by_id(&sim)?;
-    let internal_dns_config1 = blueprint_internal_dns_config(
-        &blueprint1,
-        &sleds_by_id,
-        &Default::default(),
-    );
-    let internal_dns_config2 = blueprint_internal_dns_config(
-        &blueprint2,
-        &sleds_by_id,
-        &Default::default(),
-    );
-    let dns_diff = DnsDiff::new(&internal_dns_config1, &internal_dns_config2)
-        .context("failed to assemble DNS diff")?;
-    swriteln!(rv, "internal DNS:\n{}", dns_diff);
+    if args.json {
+        let zone_summary = |zones: &[BlueprintOrCollectionZoneConfig]| {
+            zones
+                .iter()
+                .map(|zone| {
+                    json!({
+                        "id": zone.id(),
+                        "kind": zone.kind().report_str(),
+                        "disposition": zone.disposition(),
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        let zones_by_sled = |zones: Vec<(&SledUuid, &[_])>| {
+            zones
+                .into_iter()
+                .map(|(sled_id, zones)| {
+                    (sled_id.to_string(), json!(zone_summary(zones)))
+                })
+                .collect::<serde_json::Map<_, _>>()
+        };
+        let zones_modified = sled_diff
+            .zones
+            .modified
+            .iter()
+            .map(|(sled_id, d)| {
+                let zones = d
+                    .zones
+                    .iter()
+                    .map(|m| {
+                        json!({
+                            "id": m.zone.id(),
+                            "kind": m.zone.kind().report_str(),
+                            "prior_disposition": m.prior_disposition,
+                            "disposition": m.zone.disposition(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                (sled_id.to_string(), json!(zones))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let dns_names = |dns_diff: &DnsDiff| {
+            json!({
+                "added": dns_diff
+                    .names_added()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+                "removed": dns_diff
+                    .names_removed()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+                "changed": dns_diff
+                    .names_changed()
+                    .map(|(name, _, _)| name)
+                    .collect::<Vec<_>>(),
+            })
+        };
 
-    let external_dns_config1 = blueprint_external_dns_config(
-        &blueprint1,
-        &sim.silo_names,
-        sim.external_dns_zone_name.clone(),
-    );
-    let external_dns_config2 = blueprint_external_dns_config(
-        &blueprint2,
-        &sim.silo_names,
-        sim.external_dns_zone_name.clone(),
-    );
-    let dns_diff = DnsDiff::new(&external_dns_config1, &external_dns_config2)
-        .context("failed to assemble external DNS diff")?;
+        let output = json!({
+            "before": blueprint1_id,
+            "after": blueprint2_id,
+            "sleds_added": sled_diff.sleds_added,
+            "sleds_removed": sled_diff.sleds_removed,
+            "sleds_modified": sled_diff.sleds_modified,
+            "zones": {
+                "added": zones_by_sled(
+                    sled_diff
+                        .zones
+                        .added
+                        .iter()
+                        .map(|(sled_id, d)| (sled_id, d.zones.as_slice()))
+                        .collect()
+                ),
+                "removed": zones_by_sled(
+                    sled_diff
+                        .zones
+                        .removed
+                        .iter()
+                        .map(|(sled_id, d)| (sled_id, d.zones.as_slice()))
+                        .collect()
+                ),
+                "modified": zones_modified,
+            },
+            "internal_dns": dns_names(&dns_configs.internal_diff()?),
+            "external_dns": dns_names(&dns_configs.external_diff()?),
+        });
+        swriteln!(
+            rv,
+            "{}",
+            serde_json::to_string_pretty(&output)
+                .context("serializing diff")?
+        );
+        return Ok(Some(rv));
+    }
+
+    swriteln!(rv, "{}", sled_diff.display());
+    let dns_diff = dns_configs.internal_diff()?;
+    swriteln!(rv, "internal DNS:\n{}", dns_diff);
+    let dns_diff = dns_configs.external_diff()?;
     swriteln!(rv, "external DNS:\n{}", dns_diff);
 
     Ok(Some(rv))
 }
 
+fn cmd_assert_diff_empty(
+    sim: &mut ReconfiguratorSim,
+    args: AssertDiffEmptyArgs,
+) -> anyhow::Result<Option<String>> {
+    let blueprint1_id = args.blueprint1_id;
+    let blueprint2_id = args.blueprint2_id;
+    let blueprint1 = sim.blueprint_lookup(blueprint1_id)?;
+    let blueprint2 = sim.blueprint_lookup(blueprint2_id)?;
+
+    let mut differences = Vec::new();
+    let sled_diff = blueprint2.diff_since_blueprint(&blueprint1);
+    if !sled_diff.sleds_added.is_empty()
+        || !sled_diff.sleds_removed.is_empty()
+        || !sled_diff.sleds_modified.is_empty()
+    {
+        differences.push(format!(
+            "sleds (added: {}, removed: {}, modified: {})",
+            sled_diff.sleds_added.len(),
+            sled_diff.sleds_removed.len(),
+            sled_diff.sleds_modified.len(),
+        ));
+    }
+
+    let dns_configs = BlueprintDnsConfigs::new(sim, blueprint1, blueprint2)?;
+    let internal_diff = dns_configs.internal_diff()?;
+    if !internal_diff.is_empty() {
+        differences.push(String::from("internal DNS"));
+    }
+    let external_diff = dns_configs.external_diff()?;
+    if !external_diff.is_empty() {
+        differences.push(String::from("external DNS"));
+    }
+
+    if differences.is_empty() {
+        return Ok(Some(String::from("ok.")));
+    }
+
+    // Include the full diff so that a failing script shows what changed.
+    let mut message = format!(
+        "blueprints {} and {} differ: {}\n\n",
+        blueprint1_id,
+        blueprint2_id,
+        differences.join(", ")
+    );
+    swriteln!(message, "{}", sled_diff.display());
+    swriteln!(message, "internal DNS:\n{}", internal_diff);
+    swrite!(message, "external DNS:\n{}", external_diff);
+    Err(anyhow!(AssertionFailed(message)))
+}
+
+fn cmd_blueprint_validate(
+    sim: &mut ReconfiguratorSim,
+    args: BlueprintValidateArgs,
+) -> anyhow::Result<Option<String>> {
+    let blueprint = sim.blueprint_lookup(args.blueprint_id)?;
+    let planning_input = sim
+        .system
+        .to_planning_input_builder()
+        .context("generating planning input")?
+        .build();
+
+    let mut problems = Vec::new();
+    let mut underlay_ips = BTreeMap::new();
+    for (sled_id, zone) in
+        blueprint.all_omicron_zones(BlueprintZoneFilter::ShouldBeRunning)
+    {
+        let Some(resources) = planning_input.sled_resources(&sled_id) else {
+            problems.push(format!(
+                "zone {} ({}): unknown sled {}",
+                zone.id,
+                zone.zone_type.kind().report_str(),
+                sled_id
+            ));
+            continue;
+        };
+
+        // A zone's durable dataset is usually on the same zpool as its
+        // filesystem, so only report each missing zpool once.
+        let zpools: BTreeSet<_> = zone
+            .filesystem_pool
+            .iter()
+            .chain(zone.zone_type.durable_zpool())
+            .collect();
+        for zpool in zpools {
+            if !resources.zpools.contains_key(&zpool.id()) {
+                problems.push(format!(
+                    "zone {} ({}): zpool {} not found on sled {}",
+                    zone.id,
+                    zone.zone_type.kind().report_str(),
+                    zpool,
+                    sled_id
+                ));
+            }
+        }
+
+        if let Some(other_zone_id) =
+            underlay_ips.insert(zone.underlay_address, zone.id)
+        {
+            problems.push(format!(
+                "zone {} ({}): underlay address {} is also used by zone {}",
+                zone.id,
+                zone.zone_type.kind().report_str(),
+                zone.underlay_address,
+                other_zone_id
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(Some(String::from("ok.")))
+    } else {
+        Err(anyhow!(AssertionFailed(format!(
+            "blueprint {} has {} problem{}:\n{}",
+            blueprint.id,
+            problems.len(),
+            if problems.len() == 1 { "" } else { "s" },
+            problems.join("\n")
+        ))))
+    }
+}
+
+/// Internal and external DNS contents computed for two blueprints that are
+/// being compared
+struct BlueprintDnsConfigs {
+    internal1: DnsConfigZone,
+    internal2: DnsConfigZone,
+    external1: DnsConfigZone,
+    external2: DnsConfigZone,
+}
+
+impl BlueprintDnsConfigs {
+    fn new(
+        sim: &ReconfiguratorSim,
+        blueprint1: &Blueprint,
+        blueprint2: &Blueprint,
+    ) -> anyhow::Result<BlueprintDnsConfigs> {
+        // Diff'ing DNS is a little trickier than diff'ing blueprints.  First,
+        // compute what DNS should be for each blueprint.  To do that we need
+        // to construct a list of sleds suitable for the executor.
+        let sleds_by_id = make_sleds_by_id(sim)?;
+        let internal1 = blueprint_internal_dns_config(
+            blueprint1,
+            &sleds_by_id,
+            &Default::default(),
+        );
+        let internal2 = blueprint_internal_dns_config(
+            blueprint2,
+            &sleds_by_id,
+            &Default::default(),
+        );
+        let external1 = blueprint_external_dns_config(
+            blueprint1,
+            &sim.silo_names,
+            sim.external_dns_zone_name.clone(),
+        );
+        let external2 = blueprint_external_dns_config(
+            blueprint2,
+            &sim.silo_names,
+            sim.external_dns_zone_name.clone(),
+        );
+        Ok(BlueprintDnsConfigs { internal1, internal2, external1, external2 })
+    }
+
+    fn internal_diff(&self) -> anyhow::Result<DnsDiff<'_>> {
+        DnsDiff::new(&self.internal1, &self.internal2)
+            .context("failed to assemble DNS diff")
+    }
+
+    fn external_diff(&self) -> anyhow::Result<DnsDiff<'_>> {
+        DnsDiff::new(&self.external1, &self.external2)
+            .context("failed to assemble external DNS diff")
+    }
+}
+
 fn make_sleds_by_id(
     sim: &ReconfiguratorSim,
 ) -> Result<
@@ -952,6 +2034,105 @@ fn cmd_blueprint_save(
     Ok(Some(format!("saved blueprint {} to {:?}", blueprint_id, output_path)))
 }
 
+fn cmd_blueprint_execute(
+    sim: &mut ReconfiguratorSim,
+    args: BlueprintExecuteArgs,
+) -> anyhow::Result<Option<String>> {
+    let blueprint_id = args.blueprint_id;
+    let blueprint = sim.blueprint_lookup(blueprint_id)?;
+
+    let sleds_by_id = make_sleds_by_id(sim)?;
+    let internal_dns_zone = blueprint_internal_dns_config(
+        blueprint,
+        &sleds_by_id,
+        &Default::default(),
+    );
+    let external_dns_zone = blueprint_external_dns_config(
+        blueprint,
+        &sim.silo_names,
+        sim.external_dns_zone_name.clone(),
+    );
+
+    // As in a real system, DNS is only updated if the blueprint was generated
+    // against the current DNS generation.  Check both groups before changing
+    // either one.
+    let internal_update = dns_update_for_execution(
+        CliDnsGroup::Internal,
+        &sim.internal_dns,
+        blueprint.internal_dns_version,
+        internal_dns_zone,
+    )?;
+    let external_update = dns_update_for_execution(
+        CliDnsGroup::External,
+        &sim.external_dns,
+        blueprint.external_dns_version,
+        external_dns_zone,
+    )?;
+
+    let mut s = String::new();
+    for (dns_group, update, configs) in [
+        (CliDnsGroup::Internal, internal_update, &mut sim.internal_dns),
+        (CliDnsGroup::External, external_update, &mut sim.external_dns),
+    ] {
+        match update {
+            Some(config) => {
+                let generation = Generation::try_from(config.generation)
+                    .context("new DNS generation")?;
+                swriteln!(
+                    s,
+                    "{:?} DNS: created generation {}",
+                    dns_group,
+                    generation
+                );
+                configs.insert(generation, config);
+            }
+            None => swriteln!(s, "{:?} DNS: unchanged", dns_group),
+        }
+    }
+
+    sim.target_blueprint = Some(blueprint_id);
+    swriteln!(s, "blueprint {} is now the target", blueprint_id);
+    Ok(Some(s))
+}
+
+/// Returns the DNS configuration that executing a blueprint would write for
+/// one DNS group, or `None` if DNS already matches the blueprint
+///
+/// Like real execution, this fails if the blueprint was generated against a DNS
+/// generation other than the latest one.  If there are no DNS generations at
+/// all, the blueprint's generation is taken to be the current one.
+fn dns_update_for_execution(
+    dns_group: CliDnsGroup,
+    configs: &BTreeMap<Generation, DnsConfigParams>,
+    blueprint_version: Generation,
+    blueprint_zone: DnsConfigZone,
+) -> anyhow::Result<Option<DnsConfigParams>> {
+    if let Some((&current_version, current_config)) = configs.last_key_value() {
+        if current_version != blueprint_version {
+            bail!(
+                "blueprint was generated against {:?} DNS version {}, but \
+                 the current version is {}",
+                dns_group,
+                blueprint_version,
+                current_version,
+            );
+        }
+
+        let current_zone = current_config.sole_zone()?;
+        let diff = DnsDiff::new(&current_zone, &blueprint_zone)
+            .context("failed to assemble DNS diff")?;
+        if diff.is_empty() {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(DnsConfigParams {
+        generation: u64::from(blueprint_version.next()),
+        time_created: nexus_inventory::now_db_precision(),
+        zones: vec![blueprint_zone],
+    }))
+}
+
 fn cmd_save(
     sim: &mut ReconfiguratorSim,
     args: SaveArgs,
@@ -993,6 +2174,16 @@ fn cmd_show(sim: &mut ReconfiguratorSim) -> anyhow::Result<Option<String>> {
             None => String::from("default"),
         }
     );
+    swriteln!(s, "log level: {}", sim.log_level.get().as_str());
+    swriteln!(s, "strict planning: {}", sim.strict_planning);
+    swriteln!(
+        s,
+        "target blueprint: {}",
+        match sim.target_blueprint {
+            Some(id) => id.to_string(),
+            None => String::from("none"),
+        }
+    );
     Ok(Some(s))
 }
 
@@ -1036,18 +2227,40 @@ fn cmd_set(
     args: SetArgs,
 ) -> anyhow::Result<Option<String>> {
     Ok(Some(match args {
-        SetArgs::NumNexus { num_nexus } => {
+        SetArgs::NumNexus { num_nexus: CliNumNexus::Count(num_nexus) } => {
             let rv = format!("{:?} -> {}", sim.num_nexus, num_nexus);
             sim.num_nexus = Some(num_nexus);
             sim.system.target_nexus_zone_count(usize::from(num_nexus));
             rv
         }
+        SetArgs::NumNexus { num_nexus: CliNumNexus::Default } => {
+            let rv = format!("{:?} -> default", sim.num_nexus);
+            sim.num_nexus = None;
+            sim.system.reset_target_nexus_zone_count();
+            rv
+        }
         SetArgs::ExternalDnsZoneName { zone_name } => {
             let rv =
                 format!("{:?} -> {:?}", sim.external_dns_zone_name, zone_name);
             sim.external_dns_zone_name = zone_name;
             rv
         }
+        SetArgs::LogLevel { level } => {
+            let new_level = slog::Level::from_str(&level)
+                .map_err(|()| anyhow!("unsupported log level: {:?}", level))?;
+            let rv = format!(
+                "{} -> {}",
+                sim.log_level.get().as_str(),
+                new_level.as_str()
+            );
+            sim.log_level.set(new_level);
+            rv
+        }
+        SetArgs::StrictPlanning { strict } => {
+            let rv = format!("{} -> {}", sim.strict_planning, strict);
+            sim.strict_planning = strict;
+            rv
+        }
     }))
 }
 
@@ -1211,9 +2424,29 @@ fn cmd_load(
         loaded.planning_input.service_ip_pool_ranges()
     );
 
-    sim.internal_dns = loaded.internal_dns;
-    sim.external_dns = loaded.external_dns;
-    sim.silo_names = loaded.silo_names;
+    if args.merge {
+        merge_dns_generations(
+            &mut s,
+            CliDnsGroup::Internal,
+            &mut sim.internal_dns,
+            loaded.internal_dns,
+        );
+        merge_dns_generations(
+            &mut s,
+            CliDnsGroup::External,
+            &mut sim.external_dns,
+            loaded.external_dns,
+        );
+        for silo_name in loaded.silo_names {
+            if !sim.silo_names.contains(&silo_name) {
+                sim.silo_names.push(silo_name);
+            }
+        }
+    } else {
+        sim.internal_dns = loaded.internal_dns;
+        sim.external_dns = loaded.external_dns;
+        sim.silo_names = loaded.silo_names;
+    }
 
     let nnames = loaded.external_dns_zone_names.len();
     if nnames > 0 {
@@ -1233,6 +2466,36 @@ fn cmd_load(
     Ok(Some(s))
 }
 
+/// Merges DNS generations loaded from a file into those already loaded
+///
+/// Generations that we don't have yet are added.  For generations that we
+/// already have, we keep what's already loaded, warning if the file's contents
+/// differ.
+fn merge_dns_generations(
+    s: &mut String,
+    dns_group: CliDnsGroup,
+    existing: &mut BTreeMap<Generation, DnsConfigParams>,
+    loaded: BTreeMap<Generation, DnsConfigParams>,
+) {
+    for (generation, config) in loaded {
+        match existing.get(&generation) {
+            None => {
+                existing.insert(generation, config);
+            }
+            Some(existing_config) if existing_config.zones == config.zones => {}
+            Some(_) => {
+                swriteln!(
+                    s,
+                    "warn: {:?} DNS generation {}: loaded contents conflict \
+                     with those already loaded (keeping existing)",
+                    dns_group,
+                    generation
+                );
+            }
+        }
+    }
+}
+
 fn cmd_file_contents(args: FileContentsArgs) -> anyhow::Result<Option<String>> {
     let loaded = read_file(&args.filename)?;
 
@@ -1281,3 +2544,132 @@ fn cmd_file_contents(args: FileContentsArgs) -> anyhow::Result<Option<String>> {
 
     Ok(Some(s))
 }
+
+#[cfg(test)]
+mod test {
+    use super::history_dir;
+    use super::KnownIds;
+    use super::ReplCompleter;
+    use super::TopLevelArgs;
+    use camino::Utf8PathBuf;
+    use clap::CommandFactory;
+    use reedline::Completer;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_history_dir() {
+        // XDG_DATA_HOME takes precedence over HOME.
+        assert_eq!(
+            history_dir(Some("/xdg".to_string()), Some("/home/me".to_string()))
+                .unwrap(),
+            Utf8PathBuf::from("/xdg/reconfigurator-cli"),
+        );
+
+        // An unset or empty XDG_DATA_HOME falls back to HOME.
+        let expected =
+            Utf8PathBuf::from("/home/me/.local/share/reconfigurator-cli");
+        assert_eq!(
+            history_dir(None, Some("/home/me".to_string())).unwrap(),
+            expected,
+        );
+        assert_eq!(
+            history_dir(Some(String::new()), Some("/home/me".to_string()))
+                .unwrap(),
+            expected,
+        );
+
+        // With neither, there's nowhere to put the history.
+        for (xdg_data_home, home) in [
+            (None, None),
+            (Some(String::new()), None),
+            (None, Some(String::new())),
+        ] {
+            let error = history_dir(xdg_data_home, home).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "neither XDG_DATA_HOME nor HOME is set"
+            );
+        }
+    }
+
+    fn completions(
+        completer: &mut ReplCompleter,
+        line: &str,
+    ) -> BTreeSet<String> {
+        completer
+            .complete(line, line.len())
+            .into_iter()
+            .map(|suggestion| suggestion.value)
+            .collect()
+    }
+
+    #[test]
+    fn test_completer_commands() {
+        let mut completer = ReplCompleter { known_ids: KnownIds::default() };
+
+        // With nothing typed, we should offer exactly the commands that clap
+        // accepts.
+        let command = TopLevelArgs::command();
+        let expected: BTreeSet<_> = command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect();
+        assert!(expected.contains("sled-list"));
+        assert!(expected.contains("blueprint-edit"));
+        assert_eq!(completions(&mut completer, ""), expected);
+
+        // A prefix narrows that down.
+        let expected: BTreeSet<_> = expected
+            .into_iter()
+            .filter(|name| name.starts_with("silo-"))
+            .collect();
+        assert_eq!(
+            expected,
+            [
+                "silo-add",
+                "silo-list",
+                "silo-load",
+                "silo-remove",
+                "silo-rename"
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        );
+        assert_eq!(completions(&mut completer, "silo-"), expected);
+
+        // Commands with their own subcommands complete those next.
+        let expected: BTreeSet<_> = command
+            .find_subcommand("blueprint-edit")
+            .unwrap()
+            .get_subcommands()
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect();
+        assert!(expected.contains("expunge-zone"));
+        assert_eq!(completions(&mut completer, "blueprint-edit "), expected);
+    }
+
+    #[test]
+    fn test_completer_ids() {
+        let known_ids = KnownIds::default();
+        let id1 = "29b5d6b1-24b6-4d8e-a4b4-a1ac0b7a3e67".to_string();
+        let id2 = "a0d8c7de-e7f4-4b6e-8d4c-3b7c1b8e2e51".to_string();
+        *known_ids.0.lock().unwrap() = vec![id1.clone(), id2.clone()];
+        let mut completer = ReplCompleter { known_ids };
+
+        // Arguments to commands complete known ids.
+        assert_eq!(
+            completions(&mut completer, "blueprint-show "),
+            [id1.clone(), id2.clone()].into_iter().collect(),
+        );
+        assert_eq!(
+            completions(&mut completer, "blueprint-diff 29b5 a0"),
+            [id2].into_iter().collect(),
+        );
+
+        // An unknown prefix completes nothing.
+        assert!(completions(&mut completer, "blueprint-show ff").is_empty());
+        assert!(completions(&mut completer, "frobnicate").is_empty());
+    }
+}
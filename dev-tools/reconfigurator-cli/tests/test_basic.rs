@@ -12,16 +12,22 @@ use nexus_test_utils::resource_helpers::DiskTestBuilder;
 use nexus_test_utils::SLED_AGENT_UUID;
 use nexus_test_utils_macros::nexus_test;
 use nexus_types::deployment::Blueprint;
+use nexus_types::deployment::BlueprintZoneDisposition;
+use nexus_types::deployment::BlueprintZoneFilter;
 use nexus_types::deployment::UnstableReconfiguratorState;
+use nexus_types::inventory::Collection;
 use omicron_common::api::external::Error;
+use omicron_common::zpool_name::ZpoolName;
 use omicron_test_utils::dev::poll::wait_for_condition;
 use omicron_test_utils::dev::poll::CondCheckError;
 use omicron_test_utils::dev::test_cmds::assert_exit_code;
 use omicron_test_utils::dev::test_cmds::path_to_executable;
 use omicron_test_utils::dev::test_cmds::redact_variable;
 use omicron_test_utils::dev::test_cmds::run_command;
+use omicron_test_utils::dev::test_cmds::EXIT_FAILURE;
 use omicron_test_utils::dev::test_cmds::EXIT_SUCCESS;
 use omicron_uuid_kinds::SledUuid;
+use serde_json::json;
 use slog::debug;
 use std::io::BufReader;
 use std::io::BufWriter;
@@ -29,8 +35,10 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use subprocess::Exec;
+use subprocess::ExitStatus;
 use swrite::swriteln;
 use swrite::SWrite;
+use uuid::Uuid;
 
 fn path_to_cli() -> PathBuf {
     path_to_executable(env!("CARGO_BIN_EXE_reconfigurator-cli"))
@@ -47,6 +55,30 @@ fn test_basic() {
     assert_contents("tests/output/cmd-stderr", &stderr_text);
 }
 
+// Check a valid script and an invalid one with `--check`.
+#[test]
+fn test_check() {
+    let exec =
+        Exec::cmd(path_to_cli()).arg("--check").arg("tests/input/cmds.txt");
+    let (exit_status, stdout_text, stderr_text) = run_command(exec);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    assert_eq!(stdout_text, "ok\n");
+
+    let tmpdir = camino_tempfile::tempdir().expect("failed to create tmpdir");
+    let bad_script = tmpdir.path().join("bad.txt");
+    std::fs::write(&bad_script, "# a comment\nsled-list\nsled-frobnicate\n")
+        .expect("failed to write script");
+    let exec = Exec::cmd(path_to_cli()).arg("--check").arg(&bad_script);
+    let (exit_status, stdout_text, stderr_text) = run_command(exec);
+    assert_exit_code(exit_status, EXIT_FAILURE, &stderr_text);
+    assert_eq!(stdout_text, "");
+    assert!(
+        stderr_text.contains(&format!("{}:3:", bad_script)),
+        "stderr does not name the bad line:\n{}",
+        stderr_text
+    );
+}
+
 type ControlPlaneTestContext =
     nexus_test_utils::ControlPlaneTestContext<omicron_nexus::Server>;
 
@@ -56,25 +88,8 @@ type ControlPlaneTestContext =
 // import it back.
 #[nexus_test]
 async fn test_blueprint_edit(cptestctx: &ControlPlaneTestContext) {
-    // Setup
-    //
-    // Add a zpool to all sleds, just to ensure that all new zones can find
-    // a transient filesystem wherever they end up being placed.
-    DiskTestBuilder::new(&cptestctx)
-        .on_all_sleds()
-        .with_zpool_count(1)
-        .build()
-        .await;
-
-    let nexus = &cptestctx.server.server_context().nexus;
-    let datastore = nexus.datastore();
+    let (state1, collection) = live_reconfigurator_state(cptestctx).await;
     let log = &cptestctx.logctx.log;
-    let opctx = OpContext::for_background(
-        log.clone(),
-        Arc::new(authz::Authz::new(log)),
-        authn::Context::internal_api(),
-        datastore.clone(),
-    );
     let tmpdir = camino_tempfile::tempdir().expect("failed to create tmpdir");
     // Save the path and prevent the temporary directory from being cleaned up
     // automatically.  We want to be preserve the contents if this test fails.
@@ -87,63 +102,9 @@ async fn test_blueprint_edit(cptestctx: &ControlPlaneTestContext) {
 
     println!("temporary directory: {}", tmpdir_path);
 
-    // Wait until Nexus has successfully completed an inventory collection.
-    // We don't need it directly but we want it to be present in the saved
-    // reconfigurator state.
-    let collection = wait_for_condition(
-        || async {
-            let result =
-                datastore.inventory_get_latest_collection(&opctx).await;
-            let log_result = match &result {
-                Ok(Some(_)) => Ok("found"),
-                Ok(None) => Ok("not found"),
-                Err(error) => Err(error),
-            };
-            debug!(
-                log,
-                "attempt to fetch latest inventory collection";
-                "result" => ?log_result,
-            );
-
-            match result {
-                Ok(None) => Err(CondCheckError::NotYet),
-                Ok(Some(c)) => Ok(c),
-                Err(Error::ServiceUnavailable { .. }) => {
-                    Err(CondCheckError::NotYet)
-                }
-                Err(error) => Err(CondCheckError::Failed(error)),
-            }
-        },
-        &Duration::from_millis(50),
-        &Duration::from_secs(30),
-    )
-    .await
-    .expect("took too long to find first inventory collection");
-
-    // Assemble state that we can load into reconfigurator-cli.
-    let state1 = nexus_reconfigurator_preparation::reconfigurator_state_load(
-        &opctx, datastore,
-    )
-    .await
-    .expect("failed to assemble reconfigurator state");
-
-    // Smoke check the initial state.
     let sled_id: SledUuid = SLED_AGENT_UUID.parse().unwrap();
-    assert!(state1.planning_input.sled_resources(&sled_id).is_some());
-    assert!(!state1.planning_input.service_ip_pool_ranges().is_empty());
-    assert!(!state1.silo_names.is_empty());
-    assert!(!state1.external_dns_zone_names.is_empty());
-    // We waited for the first inventory collection already.
-    assert!(state1.collections.iter().any(|c| c.id == collection.id));
-    assert!(!state1.collections.is_empty());
-    // Test suite setup establishes the initial blueprint.
-    assert!(!state1.blueprints.is_empty());
-    // Setup requires that internal and external DNS be configured so we should
-    // have at least the current DNS generations here.
-    assert!(!state1.internal_dns.is_empty());
-    assert!(!state1.external_dns.is_empty());
 
-    // unwrap: we checked above that this list was non-empty.
+    // unwrap: `live_reconfigurator_state()` checked that this was non-empty.
     let blueprint = state1.blueprints.first().unwrap();
 
     // Write a reconfigurator-cli script to load the file, edit the
@@ -243,6 +204,646 @@ async fn test_blueprint_edit(cptestctx: &ControlPlaneTestContext) {
         .unwrap();
 }
 
+// Runs reconfigurator-cli commands that inspect and compare blueprints against
+// the blueprint from a running system, rather than one made up by the CLI.
+#[nexus_test]
+async fn test_live_blueprint_commands(cptestctx: &ControlPlaneTestContext) {
+    let (state, collection) = live_reconfigurator_state(cptestctx).await;
+    let tmpdir = camino_tempfile::tempdir().expect("failed to create tmpdir");
+    let tmpdir_path = tmpdir.path();
+    let saved_state1_path = tmpdir_path.join("reconfigurator-state1.json");
+    let saved_state2_path = tmpdir_path.join("reconfigurator-state2.json");
+    write_json(&saved_state1_path, &state).unwrap();
+
+    // unwrap: `live_reconfigurator_state()` checked that this was non-empty.
+    let blueprint1 = state.blueprints.first().unwrap();
+    let sled_id: SledUuid = SLED_AGENT_UUID.parse().unwrap();
+
+    // Make a second blueprint that adds a Nexus zone to the first, so that we
+    // have two real blueprints that differ.  Save the result so that the
+    // scripts below can refer to both blueprints.
+    let mut s = String::new();
+    swriteln!(s, "load {} {}", saved_state1_path, collection.id);
+    swriteln!(s, "blueprint-edit {} add-nexus {}", blueprint1.id, sled_id);
+    swriteln!(s, "save {}", saved_state2_path);
+    let (exit_status, _, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    let state2: UnstableReconfiguratorState =
+        read_json(&saved_state2_path).unwrap();
+    let blueprint2 = state2.blueprints.last().unwrap();
+    assert_eq!(blueprint2.parent_blueprint_id, Some(blueprint1.id));
+    let new_nexus_id = blueprint2
+        .all_omicron_zones(BlueprintZoneFilter::All)
+        .map(|(_, zone)| zone.id)
+        .find(|zone_id| {
+            !blueprint1
+                .all_omicron_zones(BlueprintZoneFilter::All)
+                .any(|(_, zone)| zone.id == *zone_id)
+        })
+        .expect("new blueprint has no new zone");
+    let load = format!("load {} {}", saved_state2_path, collection.id);
+
+    // assert-diff-empty succeeds when comparing a blueprint with itself...
+    let mut s = String::new();
+    swriteln!(s, "{}", load);
+    swriteln!(s, "assert-diff-empty {} {}", blueprint1.id, blueprint1.id);
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    assert!(
+        stdout_text.contains(&format!(
+            "> assert-diff-empty {} {}\nok.\n",
+            blueprint1.id, blueprint1.id
+        )),
+        "unexpected output:\n{}",
+        stdout_text
+    );
+
+    // ... and fails the script when the blueprints differ, showing the diff.
+    let mut s = String::new();
+    swriteln!(s, "{}", load);
+    swriteln!(s, "assert-diff-empty {} {}", blueprint1.id, blueprint2.id);
+    let (exit_status, _, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_FAILURE, &stderr_text);
+    assert!(
+        stderr_text.contains(&format!(
+            "assertion failed: blueprints {} and {} differ: sleds (added: 0, \
+             removed: 0, modified: 1), internal DNS",
+            blueprint1.id, blueprint2.id
+        )),
+        "unexpected error:\n{}",
+        stderr_text
+    );
+    assert!(
+        stderr_text.contains(&new_nexus_id.to_string()),
+        "diff does not show the new zone:\n{}",
+        stderr_text
+    );
+
+    // blueprint-diff --summary counts the added zone and its DNS name.  The
+    // new Nexus zone only changes the records of existing external DNS names.
+    let mut s = String::new();
+    swriteln!(s, "{}", load);
+    swriteln!(
+        s,
+        "blueprint-diff --summary {} {}",
+        blueprint1.id,
+        blueprint2.id
+    );
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    assert!(
+        stdout_text.contains(
+            "zones: 1 added, 0 removed, 0 modified\n\
+             internal DNS names: 1 added, 0 removed\n\
+             external DNS names: 0 added, 0 removed\n"
+        ),
+        "unexpected output:\n{}",
+        stdout_text
+    );
+
+    // blueprint-copy makes a new blueprint with the same contents.
+    let saved_state3_path = tmpdir_path.join("reconfigurator-state3.json");
+    let mut s = String::new();
+    swriteln!(s, "{}", load);
+    swriteln!(s, "blueprint-copy {}", blueprint1.id);
+    swriteln!(s, "save {}", saved_state3_path);
+    let (exit_status, _, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    let state3: UnstableReconfiguratorState =
+        read_json(&saved_state3_path).unwrap();
+    assert_eq!(state3.blueprints.len(), state2.blueprints.len() + 1);
+    let mut copy = state3.blueprints.last().unwrap().clone();
+    assert!(state2.blueprints.iter().all(|b| b.id != copy.id));
+    assert_eq!(copy.parent_blueprint_id, Some(blueprint1.id));
+    assert_eq!(copy.creator, "reconfigurator-cli");
+    assert_eq!(copy.comment, format!("copy of blueprint {}", blueprint1.id));
+    // Apart from its metadata, the copy is identical to the original.
+    copy.id = blueprint1.id;
+    copy.parent_blueprint_id = blueprint1.parent_blueprint_id;
+    copy.creator.clone_from(&blueprint1.creator);
+    copy.comment.clone_from(&blueprint1.comment);
+    assert_eq!(&copy, blueprint1);
+
+    // blueprint-diff --json reports the added zone and DNS name.
+    let command =
+        format!("blueprint-diff --json {} {}", blueprint1.id, blueprint2.id);
+    let mut s = String::new();
+    swriteln!(s, "{}", load);
+    swriteln!(s, "{}", command);
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    let diff = command_json_output(&stdout_text, &command);
+    assert_eq!(diff["before"], json!(blueprint1.id));
+    assert_eq!(diff["after"], json!(blueprint2.id));
+    assert_eq!(diff["sleds_added"], json!([]));
+    assert_eq!(diff["sleds_removed"], json!([]));
+    assert_eq!(diff["sleds_modified"], json!([sled_id]));
+    assert_eq!(
+        diff["zones"]["added"],
+        json!({
+            sled_id.to_string(): [{
+                "id": new_nexus_id,
+                "kind": "nexus",
+                "disposition": "in_service",
+            }],
+        })
+    );
+    assert_eq!(diff["zones"]["removed"], json!({}));
+    assert_eq!(diff["zones"]["modified"], json!({}));
+    assert_eq!(
+        diff["internal_dns"]["added"],
+        json!([format!("{}.host", new_nexus_id)])
+    );
+    assert_eq!(diff["internal_dns"]["removed"], json!([]));
+    assert_eq!(diff["external_dns"]["added"], json!([]));
+    assert_eq!(diff["external_dns"]["removed"], json!([]));
+    assert_ne!(diff["external_dns"]["changed"], json!([]));
+
+    // blueprint-show --json prints the blueprint itself.
+    let command = format!("blueprint-show --json {}", blueprint2.id);
+    let mut s = String::new();
+    swriteln!(s, "{}", load);
+    swriteln!(s, "{}", command);
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    let shown: Blueprint =
+        serde_json::from_value(command_json_output(&stdout_text, &command))
+            .expect("failed to parse blueprint");
+    assert_eq!(&shown, blueprint2);
+
+    // inventory-generate --from-blueprint reports each sled's zones as the
+    // blueprint would have deployed them, including the new Nexus zone.
+    let saved_state4_path = tmpdir_path.join("reconfigurator-state4.json");
+    let mut s = String::new();
+    swriteln!(s, "{}", load);
+    swriteln!(s, "inventory-generate --from-blueprint {}", blueprint2.id);
+    swriteln!(s, "save {}", saved_state4_path);
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    assert!(
+        stdout_text.contains(&format!(
+            "(with zones from blueprint {})",
+            blueprint2.id
+        )),
+        "unexpected output:\n{}",
+        stdout_text
+    );
+    let state4: UnstableReconfiguratorState =
+        read_json(&saved_state4_path).unwrap();
+    assert_eq!(state4.collections.len(), state2.collections.len() + 1);
+    let generated = state4.collections.last().unwrap();
+    assert!(!blueprint2.blueprint_zones.is_empty());
+    for (sled_id, zones) in &blueprint2.blueprint_zones {
+        let found = generated.omicron_zones.get(sled_id).unwrap_or_else(|| {
+            panic!("no zones reported for sled {}", sled_id)
+        });
+        assert_eq!(
+            found.zones,
+            zones.to_omicron_zones_config(BlueprintZoneFilter::ShouldBeRunning)
+        );
+    }
+    assert!(generated.omicron_zones[&sled_id]
+        .zones
+        .zones
+        .iter()
+        .any(|zone| zone.id == new_nexus_id));
+
+    // blueprint-plan can plan against the latest DNS versions rather than the
+    // parent blueprint's, but not against versions that don't exist yet.
+    let internal_version = *state.internal_dns.keys().last().unwrap();
+    let external_version = *state.external_dns.keys().last().unwrap();
+    let saved_state5_path = tmpdir_path.join("reconfigurator-state5.json");
+    let mut s = String::new();
+    swriteln!(s, "{}", load);
+    swriteln!(
+        s,
+        "blueprint-plan --internal-dns-version {} --external-dns-version {} \
+         {} {}",
+        internal_version,
+        external_version,
+        blueprint1.id,
+        collection.id
+    );
+    swriteln!(
+        s,
+        "blueprint-plan --external-dns-version {} {} {}",
+        external_version.next(),
+        blueprint1.id,
+        collection.id
+    );
+    swriteln!(s, "save {}", saved_state5_path);
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    assert!(
+        stdout_text.contains(&format!(
+            "error: External DNS version {} is newer than the latest known \
+             version ({})",
+            external_version.next(),
+            external_version
+        )),
+        "unexpected output:\n{}",
+        stdout_text
+    );
+    let state5: UnstableReconfiguratorState =
+        read_json(&saved_state5_path).unwrap();
+    assert_eq!(state5.blueprints.len(), state2.blueprints.len() + 1);
+    let planned = state5.blueprints.last().unwrap();
+    assert_eq!(planned.parent_blueprint_id, Some(blueprint1.id));
+    assert_eq!(planned.internal_dns_version, internal_version);
+    assert_eq!(planned.external_dns_version, external_version);
+
+    // Comparing the planned blueprint with the DNS version it was planned
+    // against shows that version's records.
+    let mut s = String::new();
+    swriteln!(s, "load {} {}", saved_state5_path, collection.id);
+    swriteln!(
+        s,
+        "blueprint-diff-dns external {} {}",
+        external_version,
+        planned.id
+    );
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    let external_zone = state.external_dns[&external_version]
+        .sole_zone()
+        .expect("external DNS has one zone");
+    assert!(
+        stdout_text.contains(&format!("{:?}", external_zone.zone_name)),
+        "unexpected output:\n{}",
+        stdout_text
+    );
+    for name in external_zone.records.keys() {
+        assert!(
+            stdout_text.contains(&format!("name: {}", name)),
+            "DNS diff does not show name {:?}:\n{}",
+            name,
+            stdout_text
+        );
+    }
+
+    // blueprint-validate accepts a consistent blueprint.  Test setup makes up
+    // the zpools for the zones it deploys, so start from the edited blueprint,
+    // drop the zones with durable datasets, and put the rest on zpools that
+    // the system knows about.
+    let mut valid = blueprint2.clone();
+    valid.id = Uuid::new_v4();
+    valid.parent_blueprint_id = Some(blueprint2.id);
+    for (zones_sled_id, zones) in &mut valid.blueprint_zones {
+        let zpool_id = *state
+            .planning_input
+            .sled_resources(zones_sled_id)
+            .expect("blueprint refers to unknown sled")
+            .zpools
+            .keys()
+            .next()
+            .expect("sled has no zpools");
+        zones.zones.retain(|zone| zone.zone_type.durable_zpool().is_none());
+        for zone in &mut zones.zones {
+            zone.filesystem_pool = Some(ZpoolName::new_external(zpool_id));
+        }
+    }
+
+    // Break a copy of that blueprint by giving two zones the same underlay
+    // address.  There are at least two zones left on `sled_id`: the original
+    // Nexus zone and the one we added.
+    let mut broken = valid.clone();
+    broken.id = Uuid::new_v4();
+    broken.parent_blueprint_id = Some(valid.id);
+    let zones = &mut broken.blueprint_zones.get_mut(&sled_id).unwrap().zones;
+    zones[1].underlay_address = zones[0].underlay_address;
+    let expected_problem = format!(
+        "assertion failed: blueprint {} has 1 problem:\n\
+         zone {} ({}): underlay address {} is also used by zone {}\n",
+        broken.id,
+        zones[1].id,
+        zones[1].zone_type.kind().report_str(),
+        zones[1].underlay_address,
+        zones[0].id,
+    );
+
+    let saved_state6_path = tmpdir_path.join("reconfigurator-state6.json");
+    let mut state6 = state2.clone();
+    state6.blueprints.push(valid.clone());
+    state6.blueprints.push(broken.clone());
+    write_json(&saved_state6_path, &state6).unwrap();
+    let load6 = format!("load {} {}", saved_state6_path, collection.id);
+
+    let mut s = String::new();
+    swriteln!(s, "{}", load6);
+    swriteln!(s, "blueprint-validate {}", valid.id);
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    assert!(
+        stdout_text
+            .contains(&format!("> blueprint-validate {}\nok.\n", valid.id)),
+        "unexpected output:\n{}",
+        stdout_text
+    );
+
+    // The broken blueprint fails the script, reporting exactly the problem we
+    // introduced.
+    let mut s = String::new();
+    swriteln!(s, "{}", load6);
+    swriteln!(s, "blueprint-validate {}", broken.id);
+    let (exit_status, _, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_FAILURE, &stderr_text);
+    assert!(
+        stderr_text.contains(&expected_problem),
+        "expected problem:\n{}\nfound:\n{}",
+        expected_problem,
+        stderr_text
+    );
+
+    // blueprint-edit can expunge one real zone, or every zone on a real sled.
+    // Use the edited blueprint so that there's a zone (the Nexus zone we
+    // added) that we know about.
+    let other_zone_ids: Vec<_> = blueprint2.blueprint_zones[&sled_id]
+        .zones
+        .iter()
+        .filter(|zone| zone.id != new_nexus_id)
+        .map(|zone| zone.id)
+        .collect();
+    let saved_state7_path = tmpdir_path.join("reconfigurator-state7.json");
+    let mut s = String::new();
+    swriteln!(s, "{}", load);
+    swriteln!(
+        s,
+        "blueprint-edit {} expunge-zone {} {}",
+        blueprint2.id,
+        sled_id,
+        new_nexus_id
+    );
+    swriteln!(s, "blueprint-edit {} expunge-sled {}", blueprint2.id, sled_id);
+    swriteln!(s, "save {}", saved_state7_path);
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    assert!(
+        stdout_text.contains(&format!(
+            "expunged zone {} from sled {}",
+            new_nexus_id, sled_id
+        )),
+        "unexpected output:\n{}",
+        stdout_text
+    );
+    assert!(
+        stdout_text.contains(&format!(
+            "expunged {} zones from sled {}",
+            other_zone_ids.len() + 1,
+            sled_id
+        )),
+        "unexpected output:\n{}",
+        stdout_text
+    );
+    let state7: UnstableReconfiguratorState =
+        read_json(&saved_state7_path).unwrap();
+    assert_eq!(state7.blueprints.len(), state2.blueprints.len() + 2);
+    let zone_expunged = &state7.blueprints[state2.blueprints.len()];
+    let sled_expunged = &state7.blueprints[state2.blueprints.len() + 1];
+    let disposition = |blueprint: &Blueprint, zone_id| {
+        blueprint.blueprint_zones[&sled_id]
+            .zones
+            .iter()
+            .find(|zone| zone.id == zone_id)
+            .unwrap_or_else(|| panic!("zone {} not found", zone_id))
+            .disposition
+    };
+
+    // Expunging the zone changes only that zone's disposition.
+    assert_eq!(zone_expunged.parent_blueprint_id, Some(blueprint2.id));
+    assert_eq!(
+        disposition(zone_expunged, new_nexus_id),
+        BlueprintZoneDisposition::Expunged
+    );
+    for zone_id in &other_zone_ids {
+        assert_eq!(
+            disposition(zone_expunged, *zone_id),
+            disposition(blueprint2, *zone_id)
+        );
+    }
+
+    // Expunging the sled expunges all of its zones.
+    assert_eq!(sled_expunged.parent_blueprint_id, Some(blueprint2.id));
+    for zone_id in other_zone_ids.iter().chain([&new_nexus_id]) {
+        assert_eq!(
+            disposition(sled_expunged, *zone_id),
+            BlueprintZoneDisposition::Expunged
+        );
+    }
+
+    // The diff for the expunged zone shows just that zone as modified.
+    let command =
+        format!("blueprint-diff --json {} {}", blueprint2.id, zone_expunged.id);
+    let mut s = String::new();
+    swriteln!(s, "load {} {}", saved_state7_path, collection.id);
+    swriteln!(s, "{}", command);
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    let diff = command_json_output(&stdout_text, &command);
+    assert_eq!(diff["zones"]["added"], json!({}));
+    assert_eq!(diff["zones"]["removed"], json!({}));
+    assert_eq!(
+        diff["zones"]["modified"],
+        json!({
+            sled_id.to_string(): [{
+                "id": new_nexus_id,
+                "kind": "nexus",
+                "prior_disposition": "in_service",
+                "disposition": "expunged",
+            }],
+        })
+    );
+    assert_eq!(
+        diff["internal_dns"]["removed"],
+        json!([format!("{}.host", new_nexus_id)])
+    );
+
+    // blueprint-execute writes new DNS generations for a blueprint that was
+    // planned against the current ones.  Add a Nexus zone to the blueprint we
+    // planned against the latest DNS versions above so that both internal and
+    // external DNS are sure to change.
+    let saved_state8_path = tmpdir_path.join("reconfigurator-state8.json");
+    let mut s = String::new();
+    swriteln!(s, "load {} {}", saved_state5_path, collection.id);
+    swriteln!(s, "blueprint-edit {} add-nexus {}", planned.id, sled_id);
+    swriteln!(s, "save {}", saved_state8_path);
+    let (exit_status, _, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    let state8: UnstableReconfiguratorState =
+        read_json(&saved_state8_path).unwrap();
+    let to_execute = state8.blueprints.last().unwrap();
+    assert_eq!(to_execute.parent_blueprint_id, Some(planned.id));
+    assert_eq!(to_execute.internal_dns_version, internal_version);
+    assert_eq!(to_execute.external_dns_version, external_version);
+    let executed_nexus_id = to_execute
+        .all_omicron_zones(BlueprintZoneFilter::All)
+        .map(|(_, zone)| zone.id)
+        .find(|zone_id| {
+            !planned
+                .all_omicron_zones(BlueprintZoneFilter::All)
+                .any(|(_, zone)| zone.id == *zone_id)
+        })
+        .expect("new blueprint has no new zone");
+
+    // Executing it a second time fails because DNS has moved on since the
+    // blueprint was generated.
+    let saved_state9_path = tmpdir_path.join("reconfigurator-state9.json");
+    let mut s = String::new();
+    swriteln!(s, "load {} {}", saved_state8_path, collection.id);
+    swriteln!(s, "blueprint-execute {}", to_execute.id);
+    swriteln!(s, "blueprint-execute {}", to_execute.id);
+    swriteln!(s, "save {}", saved_state9_path);
+    let (exit_status, stdout_text, stderr_text) = run_script(tmpdir_path, &s);
+    assert_exit_code(exit_status, EXIT_SUCCESS, &stderr_text);
+    let expected = format!(
+        "Internal DNS: created generation {}\n\
+         External DNS: created generation {}\n\
+         blueprint {} is now the target\n",
+        internal_version.next(),
+        external_version.next(),
+        to_execute.id,
+    );
+    assert!(
+        stdout_text.contains(&expected),
+        "expected:\n{}\nfound:\n{}",
+        expected,
+        stdout_text
+    );
+    let expected = format!(
+        "error: blueprint was generated against Internal DNS version {}, but \
+         the current version is {}",
+        internal_version,
+        internal_version.next(),
+    );
+    assert!(
+        stdout_text.contains(&expected),
+        "expected:\n{}\nfound:\n{}",
+        expected,
+        stdout_text
+    );
+
+    // The saved state has exactly one new generation of each DNS group, and
+    // the new internal DNS generation includes the new Nexus zone.
+    let state9: UnstableReconfiguratorState =
+        read_json(&saved_state9_path).unwrap();
+    assert_eq!(state9.internal_dns.len(), state.internal_dns.len() + 1);
+    assert_eq!(state9.external_dns.len(), state.external_dns.len() + 1);
+    let internal_zone = state9.internal_dns[&internal_version.next()]
+        .sole_zone()
+        .expect("internal DNS has one zone");
+    assert!(internal_zone
+        .records
+        .contains_key(&format!("{}.host", executed_nexus_id)));
+    assert!(state9.external_dns.contains_key(&external_version.next()));
+}
+
+/// Sets up a running system for reconfigurator-cli tests and returns its
+/// reconfigurator state, along with the inventory collection that the state
+/// is known to include
+async fn live_reconfigurator_state(
+    cptestctx: &ControlPlaneTestContext,
+) -> (UnstableReconfiguratorState, Collection) {
+    // Add a zpool to all sleds, just to ensure that all new zones can find
+    // a transient filesystem wherever they end up being placed.
+    DiskTestBuilder::new(&cptestctx)
+        .on_all_sleds()
+        .with_zpool_count(1)
+        .build()
+        .await;
+
+    let nexus = &cptestctx.server.server_context().nexus;
+    let datastore = nexus.datastore();
+    let log = &cptestctx.logctx.log;
+    let opctx = OpContext::for_background(
+        log.clone(),
+        Arc::new(authz::Authz::new(log)),
+        authn::Context::internal_api(),
+        datastore.clone(),
+    );
+
+    // Wait until Nexus has successfully completed an inventory collection.
+    // We don't need it directly but we want it to be present in the saved
+    // reconfigurator state.
+    let collection = wait_for_condition(
+        || async {
+            let result =
+                datastore.inventory_get_latest_collection(&opctx).await;
+            let log_result = match &result {
+                Ok(Some(_)) => Ok("found"),
+                Ok(None) => Ok("not found"),
+                Err(error) => Err(error),
+            };
+            debug!(
+                log,
+                "attempt to fetch latest inventory collection";
+                "result" => ?log_result,
+            );
+
+            match result {
+                Ok(None) => Err(CondCheckError::NotYet),
+                Ok(Some(c)) => Ok(c),
+                Err(Error::ServiceUnavailable { .. }) => {
+                    Err(CondCheckError::NotYet)
+                }
+                Err(error) => Err(CondCheckError::Failed(error)),
+            }
+        },
+        &Duration::from_millis(50),
+        &Duration::from_secs(30),
+    )
+    .await
+    .expect("took too long to find first inventory collection");
+
+    // Assemble state that we can load into reconfigurator-cli.
+    let state = nexus_reconfigurator_preparation::reconfigurator_state_load(
+        &opctx, datastore,
+    )
+    .await
+    .expect("failed to assemble reconfigurator state");
+
+    // Smoke check the initial state.
+    let sled_id: SledUuid = SLED_AGENT_UUID.parse().unwrap();
+    assert!(state.planning_input.sled_resources(&sled_id).is_some());
+    assert!(!state.planning_input.service_ip_pool_ranges().is_empty());
+    assert!(!state.silo_names.is_empty());
+    assert!(!state.external_dns_zone_names.is_empty());
+    // We waited for the first inventory collection already.
+    assert!(state.collections.iter().any(|c| c.id == collection.id));
+    assert!(!state.collections.is_empty());
+    // Test suite setup establishes the initial blueprint.
+    assert!(!state.blueprints.is_empty());
+    // Setup requires that internal and external DNS be configured so we should
+    // have at least the current DNS generations here.
+    assert!(!state.internal_dns.is_empty());
+    assert!(!state.external_dns.is_empty());
+
+    (state, collection)
+}
+
+/// Runs the reconfigurator-cli commands in `script`, using `dir` for the
+/// script file
+///
+/// Returns the exit status, stdout, and stderr.
+fn run_script(dir: &Utf8Path, script: &str) -> (ExitStatus, String, String) {
+    let script_path = dir.join("cmds");
+    std::fs::write(&script_path, script)
+        .with_context(|| format!("write {}", &script_path))
+        .unwrap();
+    run_command(Exec::cmd(path_to_cli()).arg(&script_path))
+}
+
+/// Returns the JSON value that a script printed in response to `command`
+fn command_json_output(stdout_text: &str, command: &str) -> serde_json::Value {
+    let echo = format!("> {}\n", command);
+    let start = stdout_text.find(&echo).unwrap_or_else(|| {
+        panic!("no output for {:?}:\n{}", command, stdout_text)
+    }) + echo.len();
+    serde_json::Deserializer::from_str(&stdout_text[start..])
+        .into_iter::<serde_json::Value>()
+        .next()
+        .expect("no JSON output")
+        .expect("failed to parse JSON output")
+}
+
 fn read_json<T: for<'a> serde::Deserialize<'a>>(
     path: &Utf8Path,
 ) -> Result<T, anyhow::Error> {
@@ -10,8 +10,13 @@ use crate::{execute, ExecutionError, PFEXEC};
 use omicron_common::api::external::MacAddr;
 use omicron_common::vlan::VlanID;
 use serde::{Deserialize, Serialize};
+use slog::{info, Logger};
+use std::collections::BTreeMap;
+use std::os::unix::process::ExitStatusExt;
 use std::str::FromStr;
 use std::str::Utf8Error;
+use std::sync::Mutex;
+use std::time::Duration;
 
 pub const VNIC_PREFIX: &str = "ox";
 pub const VNIC_PREFIX_CONTROL: &str = "oxControl";
@@ -40,6 +45,20 @@ pub const CHELSIO_LINK_PREFIX: &str = "cxgbe";
 /// The prefix for OPTE link names
 pub const OPTE_LINK_PREFIX: &str = "opte";
 
+/// Expected error message when operating on a link that doesn't exist.
+const LINK_NOT_FOUND: &str = "object not found";
+
+/// The smallest MTU accepted by [`Dladm::set_mtu`] (the IPv6 minimum).
+pub const MIN_MTU: usize = 1280;
+
+/// The largest MTU accepted by [`Dladm::set_mtu`].
+pub const MAX_MTU: usize = 9000;
+
+/// Fragments of `dladm` error output indicating a failure that may succeed if
+/// the command is retried.
+const TRANSIENT_FAILURES: &[&str] =
+    &["device busy", "resource temporarily unavailable"];
+
 /// Errors returned from [`Dladm::find_physical`].
 #[derive(thiserror::Error, Debug)]
 pub enum FindPhysicalLinkError {
@@ -51,6 +70,9 @@ pub enum FindPhysicalLinkError {
 
     #[error("Unexpected non-UTF-8 link name")]
     NonUtf8Output(Utf8Error),
+
+    #[error("Failed to parse physical link: {0}")]
+    ParseFailure(String),
 }
 
 /// Errors returned from [`Dladm::get_mac`].
@@ -73,7 +95,16 @@ pub struct CreateVnicError {
     name: String,
     link: String,
     #[source]
-    err: ExecutionError,
+    err: CreateVnicErrorKind,
+}
+
+#[derive(thiserror::Error, Debug)]
+enum CreateVnicErrorKind {
+    #[error(transparent)]
+    Execution(#[from] ExecutionError),
+
+    #[error(transparent)]
+    SetMtu(#[from] SetMtuError),
 }
 
 /// Errors returned from [`Dladm::get_vnics`].
@@ -84,6 +115,29 @@ pub struct GetVnicError {
     err: ExecutionError,
 }
 
+/// Errors returned from [`Dladm::delete_vlan`].
+#[derive(thiserror::Error, Debug)]
+pub enum DeleteVlanError {
+    #[error(transparent)]
+    GetVlans(#[from] GetVnicError),
+
+    #[error(transparent)]
+    Delete(#[from] DeleteVnicError),
+}
+
+/// The configuration of a single VNIC, as reported by
+/// [`Dladm::get_vnic_info`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VnicInfo {
+    pub name: String,
+    /// The link the VNIC is created over
+    pub over: String,
+    pub mac: MacAddr,
+    pub mtu: usize,
+    /// The VLAN ID, if the VNIC is tagged
+    pub vlan: Option<VlanID>,
+}
+
 /// Errors returned from [`Dladm::get_simulated_tfports`].
 #[derive(thiserror::Error, Debug)]
 #[error("Failed to get simnets: {err}")]
@@ -101,7 +155,8 @@ pub struct DeleteVnicError {
     err: ExecutionError,
 }
 
-/// Errors returned from [`Dladm::get_linkprop`].
+/// Errors returned from [`Dladm::get_linkprop`] and
+/// [`Dladm::show_linkprop_all`].
 #[derive(thiserror::Error, Debug)]
 #[error(
     "Failed to get link property \"{prop_name}\" on vnic {link_name}: {err}"
@@ -113,6 +168,16 @@ pub struct GetLinkpropError {
     err: ExecutionError,
 }
 
+/// Whether a link property is at its default value or has been explicitly set,
+/// as reported by [`Dladm::get_linkprop_with_source`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkpropSource {
+    /// The effective value is the property's default
+    Default,
+    /// The effective value differs from the property's default
+    Set,
+}
+
 /// Errors returned from [`Dladm::set_linkprop`].
 #[derive(thiserror::Error, Debug)]
 #[error("Failed to set link property \"{prop_name}\" to \"{prop_value}\" on vnic {link_name}: {err}")]
@@ -124,6 +189,19 @@ pub struct SetLinkpropError {
     err: ExecutionError,
 }
 
+/// Errors returned from [`Dladm::set_mtu`].
+#[derive(thiserror::Error, Debug)]
+pub enum SetMtuError {
+    #[error(
+        "MTU {mtu} for vnic {link_name} is outside the supported range \
+        {MIN_MTU}..={MAX_MTU}"
+    )]
+    OutOfRange { link_name: String, mtu: usize },
+
+    #[error(transparent)]
+    SetLinkprop(#[from] SetLinkpropError),
+}
+
 /// Errors returned from [`Dladm::reset_linkprop`].
 #[derive(thiserror::Error, Debug)]
 #[error(
@@ -146,6 +224,35 @@ impl ToString for PhysicalLink {
     }
 }
 
+/// The operational state of a datalink, as reported by `dladm`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkState {
+    Up,
+    Down,
+    Unknown,
+}
+
+impl LinkState {
+    fn from_dladm(state: &str) -> Self {
+        match state {
+            "up" => LinkState::Up,
+            "down" => LinkState::Down,
+            _ => LinkState::Unknown,
+        }
+    }
+}
+
+/// A physical datalink, as reported by [`Dladm::list_physical_detailed`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhysicalLinkInfo {
+    pub link: PhysicalLink,
+    /// The media type of the link (e.g., "Ethernet")
+    pub media: String,
+    pub state: LinkState,
+    /// The link speed in Mb/s, which is zero if the link is down
+    pub speed: u64,
+}
+
 /// The name of an etherstub
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Etherstub(pub String);
@@ -171,9 +278,124 @@ impl VnicSource for PhysicalLink {
     }
 }
 
+/// Logger to which mutating commands are reported when running in "dry run"
+/// mode, or `None` if commands should be executed normally.
+///
+/// See [`Dladm::set_dry_run`].
+static DRY_RUN_LOG: Mutex<Option<Logger>> = Mutex::new(None);
+
+/// Executes a command that modifies data links on the system.
+///
+/// In dry-run mode, this logs the command and returns a successful, empty
+/// `Output` without spawning a subprocess.
+fn execute_mutating(
+    command: &mut std::process::Command,
+) -> Result<std::process::Output, ExecutionError> {
+    if let Some(log) = DRY_RUN_LOG.lock().unwrap().as_ref() {
+        let command_str = std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|s| s.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+        info!(
+            log,
+            "dladm dry run: skipping command";
+            "command" => command_str,
+        );
+        return Ok(std::process::Output {
+            status: std::process::ExitStatus::from_raw(0),
+            stdout: vec![],
+            stderr: vec![],
+        });
+    }
+    execute(command)
+}
+
+/// How a mutating command that fails transiently is retried.
+#[derive(Clone, Copy, Debug)]
+struct RetryPolicy {
+    /// Number of times the command is attempted before a transient failure
+    /// is reported
+    attempts: u32,
+    /// Delay before the first retry; doubled for each subsequent retry
+    backoff: Duration,
+}
+
+impl RetryPolicy {
+    const DEFAULT: RetryPolicy =
+        RetryPolicy { attempts: 3, backoff: Duration::from_millis(100) };
+}
+
+/// Returns true if `err` is a failure that may succeed if retried.
+///
+/// Deterministic failures, such as the link already existing or not being
+/// found, are never considered transient.
+fn is_transient_failure(err: &ExecutionError) -> bool {
+    match err {
+        ExecutionError::CommandFailure(info) => {
+            let stderr = info.stderr.to_lowercase();
+            TRANSIENT_FAILURES.iter().any(|msg| stderr.contains(msg))
+        }
+        _ => false,
+    }
+}
+
+/// Executes a command that modifies data links on the system, retrying with
+/// a short backoff if it fails transiently.
+///
+/// See [`execute_mutating`].
+fn execute_mutating_with_retry(
+    command: &mut std::process::Command,
+    policy: RetryPolicy,
+) -> Result<std::process::Output, ExecutionError> {
+    let attempts = policy.attempts.max(1);
+    let mut backoff = policy.backoff;
+    let mut attempt = 1;
+    loop {
+        match execute_mutating(command) {
+            Err(err) if attempt < attempts && is_transient_failure(&err) => {
+                sleep_before_retry(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Waits before retrying a command.
+///
+/// `Dladm` is synchronous, but is called from async code in sled-agent. On a
+/// multi-threaded tokio runtime, the worker's other tasks are handed off
+/// before sleeping, so that they aren't stalled by the backoff.
+fn sleep_before_retry(duration: Duration) {
+    use tokio::runtime::{Handle, RuntimeFlavor};
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| std::thread::sleep(duration))
+        }
+        _ => std::thread::sleep(duration),
+    }
+}
+
 /// Wraps commands for interacting with data links.
 pub struct Dladm {}
 
+impl Dladm {
+    /// Enables or disables "dry run" mode.
+    ///
+    /// While a logger is set, commands that would modify data links (creating
+    /// or deleting VNICs and etherstubs, setting link properties, etc.) are
+    /// logged at info level instead of being executed, and are reported as
+    /// successful.  Commands that only read state (e.g., [`Dladm::get_vnics`])
+    /// are still executed, since their output cannot be faked.
+    ///
+    /// Passing `None` restores normal execution.
+    pub fn set_dry_run(log: Option<Logger>) {
+        *DRY_RUN_LOG.lock().unwrap() = log;
+    }
+}
+
 #[cfg_attr(any(test, feature = "testing"), mockall::automock, allow(dead_code))]
 impl Dladm {
     /// Creates an etherstub, or returns one which already exists.
@@ -183,7 +405,7 @@ impl Dladm {
         }
         let mut command = std::process::Command::new(PFEXEC);
         let cmd = command.args(&[DLADM, "create-etherstub", "-t", name]);
-        execute(cmd)?;
+        execute_mutating(cmd)?;
         Ok(Etherstub(name.to_string()))
     }
 
@@ -207,20 +429,18 @@ impl Dladm {
             BOOTSTRAP_ETHERSTUB_NAME => (BOOTSTRAP_ETHERSTUB_VNIC_NAME, 1500),
             _ => unreachable!(),
         };
-        if let Ok(vnic) = Self::get_etherstub_vnic(vnic_name) {
-            return Ok(vnic);
+        let exists =
+            Self::vnic_exists(vnic_name).map_err(|e| CreateVnicError {
+                name: vnic_name.to_string(),
+                link: source.name().to_string(),
+                err: CreateVnicErrorKind::Execution(e.err),
+            })?;
+        if !exists {
+            Self::create_vnic(source, vnic_name, None, None, mtu)?;
         }
-        Self::create_vnic(source, vnic_name, None, None, mtu)?;
         Ok(EtherstubVnic(vnic_name.to_string()))
     }
 
-    fn get_etherstub_vnic(name: &str) -> Result<EtherstubVnic, ExecutionError> {
-        let mut command = std::process::Command::new(PFEXEC);
-        let cmd = command.args(&[DLADM, "show-vnic", name]);
-        execute(cmd)?;
-        Ok(EtherstubVnic(name.to_string()))
-    }
-
     // Return the name of the IP interface over the etherstub VNIC, if it
     // exists.
     fn get_etherstub_vnic_interface(
@@ -239,13 +459,13 @@ impl Dladm {
         if Self::get_etherstub_vnic_interface(name).is_ok() {
             let mut cmd = std::process::Command::new(PFEXEC);
             let cmd = cmd.args(&[IPADM, "delete-if", name]);
-            execute(cmd)?;
+            execute_mutating(cmd)?;
         }
 
-        if Self::get_etherstub_vnic(name).is_ok() {
+        if Self::vnic_exists(name).map_err(|e| e.err)? {
             let mut cmd = std::process::Command::new(PFEXEC);
             let cmd = cmd.args(&[DLADM, "delete-vnic", name]);
-            execute(cmd)?;
+            execute_mutating(cmd)?;
         }
         Ok(())
     }
@@ -255,7 +475,7 @@ impl Dladm {
         if Self::get_etherstub(name).is_ok() {
             let mut cmd = std::process::Command::new(PFEXEC);
             let cmd = cmd.args(&[DLADM, "delete-etherstub", name]);
-            execute(cmd)?;
+            execute_mutating(cmd)?;
         }
         Ok(())
     }
@@ -275,15 +495,35 @@ impl Dladm {
         }
     }
 
-    /// Returns the name of the first observed physical data link.
+    /// Returns the name of the first observed physical data link that is up,
+    /// or of the first observed physical data link if none are up.
     pub fn find_physical() -> Result<PhysicalLink, FindPhysicalLinkError> {
-        // TODO: This is arbitrary, but we're currently grabbing the first
-        // physical device. Should we have a more sophisticated method for
-        // selection?
-        Self::list_physical()?
-            .into_iter()
-            .next()
-            .ok_or_else(|| FindPhysicalLinkError::NoPhysicalLinkFound)
+        // TODO: This is still somewhat arbitrary. Preferring a link that is up
+        // avoids picking, e.g., an unplugged management NIC that happens to
+        // be enumerated first, but we may want a more sophisticated method
+        // for selection.
+        //
+        // A link whose details can't be parsed is not known to be up, but it
+        // is still a candidate, so that one bad line of output doesn't fail
+        // the whole lookup.
+        let stdout = Self::show_phys_detailed()?;
+        let mut first = None;
+        for line in stdout.lines() {
+            let (link, state) = match parse_physical_link_info(line) {
+                Ok(info) => (info.link, info.state),
+                Err(_) => match split_parsable_fields(line).first() {
+                    Some(name) if !name.is_empty() => {
+                        (PhysicalLink(name.clone()), LinkState::Unknown)
+                    }
+                    _ => continue,
+                },
+            };
+            if state == LinkState::Up {
+                return Ok(link);
+            }
+            first.get_or_insert(link);
+        }
+        first.ok_or(FindPhysicalLinkError::NoPhysicalLinkFound)
     }
 
     /// List the extant physical data links on the system.
@@ -303,6 +543,37 @@ impl Dladm {
             })
     }
 
+    /// List the extant physical data links on the system, along with their
+    /// state, speed, and media type.
+    ///
+    /// Note that this returns _all_ links.
+    pub fn list_physical_detailed(
+    ) -> Result<Vec<PhysicalLinkInfo>, FindPhysicalLinkError> {
+        Self::show_phys_detailed()?
+            .lines()
+            .map(|line| {
+                parse_physical_link_info(line)
+                    .map_err(FindPhysicalLinkError::ParseFailure)
+            })
+            .collect()
+    }
+
+    // Return the output of `dladm show-phys -p -o LINK,STATE,SPEED,MEDIA`
+    fn show_phys_detailed() -> Result<String, FindPhysicalLinkError> {
+        let mut command = std::process::Command::new(PFEXEC);
+        let cmd = command.args(&[
+            DLADM,
+            "show-phys",
+            "-p",
+            "-o",
+            "LINK,STATE,SPEED,MEDIA",
+        ]);
+        let output = execute(cmd)?;
+        String::from_utf8(output.stdout).map_err(|err| {
+            FindPhysicalLinkError::NonUtf8Output(err.utf8_error())
+        })
+    }
+
     /// Returns the MAC address of a physical link.
     pub fn get_mac(link: &PhysicalLink) -> Result<MacAddr, GetMacError> {
         let mut command = std::process::Command::new(PFEXEC);
@@ -322,15 +593,7 @@ impl Dladm {
             .map(|s| s.trim())
             .ok_or_else(|| GetMacError::NotFound(link.clone()))?
             .to_string();
-
-        // Ensure the MAC address is zero-padded, so it may be parsed as a
-        // MacAddr. This converts segments like ":a" to ":0a".
-        let name = name
-            .split(':')
-            .map(|segment| format!("{:0>2}", segment))
-            .collect::<Vec<String>>()
-            .join(":");
-        let mac = MacAddr::from_str(&name)?;
+        let mac = MacAddr::from_str(&zero_pad_mac(&name))?;
         Ok(mac)
     }
 
@@ -348,6 +611,36 @@ impl Dladm {
         vlan: Option<VlanID>,
         mtu: usize,
     ) -> Result<(), CreateVnicError> {
+        Self::create_vnic_with_macs(
+            source,
+            vnic_name,
+            mac.as_slice(),
+            vlan,
+            mtu,
+        )
+    }
+
+    /// Creates a new VNIC atop a physical device, with any number of unicast
+    /// MAC addresses.
+    ///
+    /// This is the same as [`Dladm::create_vnic`], except that `macs` may
+    /// contain secondary MAC addresses after the primary one. If `macs` is
+    /// empty, `dladm` chooses the MAC address.
+    pub fn create_vnic_with_macs<T: VnicSource + 'static>(
+        source: &T,
+        vnic_name: &str,
+        macs: &[MacAddr],
+        vlan: Option<VlanID>,
+        mtu: usize,
+    ) -> Result<(), CreateVnicError> {
+        let make_err = |err| CreateVnicError {
+            name: vnic_name.to_string(),
+            link: source.name().to_string(),
+            err,
+        };
+        check_mtu(vnic_name, mtu)
+            .map_err(|err| make_err(CreateVnicErrorKind::SetMtu(err)))?;
+
         let mut command = std::process::Command::new(PFEXEC);
         let mut args = vec![
             DLADM.to_string(),
@@ -357,9 +650,14 @@ impl Dladm {
             source.name().to_string(),
         ];
 
-        if let Some(mac) = mac {
+        if !macs.is_empty() {
             args.push("-m".to_string());
-            args.push(mac.0.to_string());
+            args.push(
+                macs.iter()
+                    .map(|mac| mac.0.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
         }
 
         if let Some(vlan) = vlan {
@@ -373,31 +671,15 @@ impl Dladm {
         args.push(vnic_name.to_string());
 
         let cmd = command.args(&args);
-        execute(cmd).map_err(|err| CreateVnicError {
-            name: vnic_name.to_string(),
-            link: source.name().to_string(),
-            err,
-        })?;
+        execute_mutating_with_retry(cmd, RetryPolicy::DEFAULT)
+            .map_err(|err| make_err(CreateVnicErrorKind::Execution(err)))?;
 
         // In certain situations, `create-vnic -p mtu=N` does not actually set
         // the mtu to N. Set it here using `set-linkprop`.
         //
         // See https://www.illumos.org/issues/15695 for the illumos bug.
-        let mut command = std::process::Command::new(PFEXEC);
-        let prop = format!("mtu={}", mtu);
-        let cmd = command.args(&[
-            DLADM,
-            "set-linkprop",
-            "-t",
-            "-p",
-            &prop,
-            vnic_name,
-        ]);
-        execute(cmd).map_err(|err| CreateVnicError {
-            name: vnic_name.to_string(),
-            link: source.name().to_string(),
-            err,
-        })?;
+        Self::set_mtu(vnic_name, mtu)
+            .map_err(|err| make_err(CreateVnicErrorKind::SetMtu(err)))?;
 
         Ok(())
     }
@@ -422,6 +704,117 @@ impl Dladm {
         Ok(vnics)
     }
 
+    /// Returns the names of all VNICs created over the datalink `source_name`.
+    ///
+    /// Unlike [`Dladm::get_vnics`], this is not limited to VNICs that may be
+    /// managed by the Sled Agent.
+    pub fn list_vnics_over(
+        source_name: &str,
+    ) -> Result<Vec<String>, GetVnicError> {
+        let mut command = std::process::Command::new(PFEXEC);
+        let cmd = command.args(&[DLADM, "show-vnic", "-p", "-o", "LINK,OVER"]);
+        let output = execute(cmd).map_err(|err| GetVnicError { err })?;
+
+        let mut vnics = vec![];
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            match split_parsable_fields(line).as_slice() {
+                [link, over] => {
+                    if over == source_name {
+                        vnics.push(link.clone());
+                    }
+                }
+                _ => {
+                    return Err(GetVnicError {
+                        err: ExecutionError::ParseFailure(format!(
+                            "unexpected show-vnic output: {line:?}"
+                        )),
+                    });
+                }
+            }
+        }
+        Ok(vnics)
+    }
+
+    /// Returns the name and VLAN ID of every VLAN-tagged VNIC.
+    pub fn get_vlans() -> Result<Vec<(String, VlanID)>, GetVnicError> {
+        let mut command = std::process::Command::new(PFEXEC);
+        let cmd = command.args(&[DLADM, "show-vnic", "-p", "-o", "LINK,VID"]);
+        let output = execute(cmd).map_err(|err| GetVnicError { err })?;
+
+        let mut vlans = vec![];
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if let Some(vlan) = parse_vlan(line).map_err(|msg| {
+                GetVnicError { err: ExecutionError::ParseFailure(msg) }
+            })? {
+                vlans.push(vlan);
+            }
+        }
+        Ok(vlans)
+    }
+
+    /// Removes every VNIC tagged with the VLAN `vid`, returning their names.
+    ///
+    /// As with [`Dladm::delete_vnic`], VNICs that disappear while this runs
+    /// are not treated as an error.
+    pub fn delete_vlan(vid: VlanID) -> Result<Vec<String>, DeleteVlanError> {
+        let names: Vec<String> = Self::get_vlans()?
+            .into_iter()
+            .filter(|(_, found)| *found == vid)
+            .map(|(name, _)| name)
+            .collect();
+        for name in &names {
+            Self::delete_vnic(name)?;
+        }
+        Ok(names)
+    }
+
+    /// Returns whether the named VNIC exists.
+    ///
+    /// A VNIC that is not found is reported as `Ok(false)`; any other failure
+    /// to query it is returned as an error.
+    pub fn vnic_exists(name: &str) -> Result<bool, GetVnicError> {
+        let mut command = std::process::Command::new(PFEXEC);
+        let cmd = command.args(&[DLADM, "show-vnic", name]);
+        match execute(cmd) {
+            Ok(_) => Ok(true),
+            Err(ExecutionError::CommandFailure(info))
+                if info.stderr.contains(LINK_NOT_FOUND) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(GetVnicError { err }),
+        }
+    }
+
+    /// Returns the configuration of the named VNIC, or `None` if it does not
+    /// exist.
+    pub fn get_vnic_info(name: &str) -> Result<Option<VnicInfo>, GetVnicError> {
+        let mut command = std::process::Command::new(PFEXEC);
+        let cmd = command.args(&[
+            DLADM,
+            "show-vnic",
+            "-p",
+            "-o",
+            "LINK,OVER,MACADDRESS,MTU,VID",
+            name,
+        ]);
+        let output = match execute(cmd) {
+            Ok(output) => output,
+            Err(ExecutionError::CommandFailure(info))
+                if info.stderr.contains(LINK_NOT_FOUND) =>
+            {
+                return Ok(None);
+            }
+            Err(err) => return Err(GetVnicError { err }),
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let line = stdout.lines().next().unwrap_or_default();
+        parse_vnic_info(line).map(Some).map_err(|msg| GetVnicError {
+            err: ExecutionError::ParseFailure(msg),
+        })
+    }
+
     /// Returns simnet links masquerading as tfport devices
     pub fn get_simulated_tfports() -> Result<Vec<String>, GetSimnetError> {
         let mut command = std::process::Command::new(PFEXEC);
@@ -442,12 +835,20 @@ impl Dladm {
     }
 
     /// Remove a vnic from the sled.
+    ///
+    /// Succeeds if the vnic does not exist, so that teardown can be retried.
     pub fn delete_vnic(name: &str) -> Result<(), DeleteVnicError> {
         let mut command = std::process::Command::new(PFEXEC);
         let cmd = command.args(&[DLADM, "delete-vnic", name]);
-        execute(cmd)
-            .map_err(|err| DeleteVnicError { name: name.to_string(), err })?;
-        Ok(())
+        match execute_mutating_with_retry(cmd, RetryPolicy::DEFAULT) {
+            Ok(_) => Ok(()),
+            Err(ExecutionError::CommandFailure(info))
+                if info.stderr.contains(LINK_NOT_FOUND) =>
+            {
+                Ok(())
+            }
+            Err(err) => Err(DeleteVnicError { name: name.to_string(), err }),
+        }
     }
 
     /// Get a link property value on a VNIC
@@ -473,6 +874,91 @@ impl Dladm {
         })?;
         Ok(String::from_utf8_lossy(&result.stdout).into_owned())
     }
+
+    /// Get a link property value on a VNIC, along with whether that value is
+    /// the property's default or has been explicitly set
+    pub fn get_linkprop_with_source(
+        vnic: &str,
+        prop_name: &str,
+    ) -> Result<(String, LinkpropSource), GetLinkpropError> {
+        let mut command = std::process::Command::new(PFEXEC);
+        let cmd = command.args(&[
+            DLADM,
+            "show-linkprop",
+            "-c",
+            "-o",
+            "value,default,possible",
+            "-p",
+            prop_name,
+            vnic,
+        ]);
+        let make_err = |err| GetLinkpropError {
+            link_name: vnic.to_string(),
+            prop_name: prop_name.to_string(),
+            err,
+        };
+        let result = execute(cmd).map_err(make_err)?;
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        let line = stdout.trim_end_matches('\n');
+        let fields = split_parsable_fields(line);
+        let (value, default) = match fields.as_slice() {
+            [value, default, ..] => (value.clone(), default),
+            _ => {
+                return Err(make_err(ExecutionError::ParseFailure(format!(
+                    "unexpected show-linkprop output: {:?}",
+                    line
+                ))))
+            }
+        };
+        let source = if value == *default {
+            LinkpropSource::Default
+        } else {
+            LinkpropSource::Set
+        };
+        Ok((value, source))
+    }
+
+    /// Get the values of all link properties on a VNIC, keyed by property name
+    ///
+    /// This runs `dladm show-linkprop` once, rather than once per property as
+    /// [`Dladm::get_linkprop`] would.  Properties with no value are included
+    /// with an empty string.
+    pub fn show_linkprop_all(
+        vnic: &str,
+    ) -> Result<BTreeMap<String, String>, GetLinkpropError> {
+        let mut command = std::process::Command::new(PFEXEC);
+        let cmd = command.args(&[
+            DLADM,
+            "show-linkprop",
+            "-c",
+            "-o",
+            "property,value",
+            vnic,
+        ]);
+        let make_err = |err| GetLinkpropError {
+            link_name: vnic.to_string(),
+            prop_name: String::from("all"),
+            err,
+        };
+        let result = execute(cmd).map_err(make_err)?;
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        let mut props = BTreeMap::new();
+        for line in stdout.lines().filter(|line| !line.is_empty()) {
+            let fields = split_parsable_fields(line);
+            match fields.as_slice() {
+                [property, value] => {
+                    props.insert(property.clone(), value.clone());
+                }
+                _ => {
+                    return Err(make_err(ExecutionError::ParseFailure(
+                        format!("unexpected show-linkprop output: {:?}", line),
+                    )))
+                }
+            }
+        }
+        Ok(props)
+    }
+
     /// Set a link property on a VNIC
     pub fn set_linkprop(
         vnic: &str,
@@ -483,7 +969,7 @@ impl Dladm {
         let prop = format!("{}={}", prop_name, prop_value);
         let cmd =
             command.args(&[DLADM, "set-linkprop", "-t", "-p", &prop, vnic]);
-        execute(cmd).map_err(|err| SetLinkpropError {
+        execute_mutating(cmd).map_err(|err| SetLinkpropError {
             link_name: vnic.to_string(),
             prop_name: prop_name.to_string(),
             prop_value: prop_value.to_string(),
@@ -492,6 +978,38 @@ impl Dladm {
         Ok(())
     }
 
+    /// Get the MTU of a link
+    pub fn get_mtu(link: &str) -> Result<usize, GetLinkpropError> {
+        let value = Self::get_linkprop(link, "mtu")?;
+        value.trim().parse().map_err(|e| GetLinkpropError {
+            link_name: link.to_string(),
+            prop_name: String::from("mtu"),
+            err: ExecutionError::ParseFailure(format!(
+                "invalid MTU {value:?}: {e}"
+            )),
+        })
+    }
+
+    /// Set the MTU of a link
+    ///
+    /// The MTU must be within [`MIN_MTU`]..=[`MAX_MTU`].
+    pub fn set_mtu(link: &str, mtu: usize) -> Result<(), SetMtuError> {
+        check_mtu(link, mtu)?;
+        let mut command = std::process::Command::new(PFEXEC);
+        let prop = format!("mtu={mtu}");
+        let cmd =
+            command.args(&[DLADM, "set-linkprop", "-t", "-p", &prop, link]);
+        execute_mutating_with_retry(cmd, RetryPolicy::DEFAULT).map_err(
+            |err| SetLinkpropError {
+                link_name: link.to_string(),
+                prop_name: String::from("mtu"),
+                prop_value: mtu.to_string(),
+                err,
+            },
+        )?;
+        Ok(())
+    }
+
     /// Reset a link property on a VNIC
     pub fn reset_linkprop(
         vnic: &str,
@@ -506,7 +1024,7 @@ impl Dladm {
             prop_name,
             vnic,
         ]);
-        execute(cmd).map_err(|err| ResetLinkpropError {
+        execute_mutating(cmd).map_err(|err| ResetLinkpropError {
             link_name: vnic.to_string(),
             prop_name: prop_name.to_string(),
             err,
@@ -514,3 +1032,691 @@ impl Dladm {
         Ok(())
     }
 }
+
+// Ensure that `mtu` is a plausible MTU for `link_name`.
+fn check_mtu(link_name: &str, mtu: usize) -> Result<(), SetMtuError> {
+    if !(MIN_MTU..=MAX_MTU).contains(&mtu) {
+        return Err(SetMtuError::OutOfRange {
+            link_name: link_name.to_string(),
+            mtu,
+        });
+    }
+    Ok(())
+}
+
+/// Split a line of `dladm -c` (parsable) output into its fields
+///
+/// Fields are separated by `:`, and any literal `:` or `\` within a field is
+/// escaped with a backslash (e.g., MAC addresses).
+fn split_parsable_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    fields.last_mut().unwrap().push(escaped);
+                }
+            }
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+// Ensure a MAC address is zero-padded, so it may be parsed as a `MacAddr`.
+// This converts segments like ":a" to ":0a".
+fn zero_pad_mac(mac: &str) -> String {
+    mac.split(':')
+        .map(|segment| format!("{:0>2}", segment))
+        .collect::<Vec<String>>()
+        .join(":")
+}
+
+// Parse a line of `dladm show-vnic -p -o LINK,VID` output, returning `None` for
+// untagged VNICs.
+fn parse_vlan(line: &str) -> Result<Option<(String, VlanID)>, String> {
+    let fields = split_parsable_fields(line);
+    let [name, vid] = fields.as_slice() else {
+        return Err(format!("unexpected show-vnic output: {line:?}"));
+    };
+    match vid.as_str() {
+        "" | "0" => Ok(None),
+        vid => {
+            let vid = VlanID::from_str(vid)
+                .map_err(|e| format!("invalid VLAN ID {vid:?}: {e}"))?;
+            Ok(Some((name.clone(), vid)))
+        }
+    }
+}
+
+// Parse a line of `dladm show-phys -p -o LINK,STATE,SPEED,MEDIA` output
+fn parse_physical_link_info(line: &str) -> Result<PhysicalLinkInfo, String> {
+    let fields = split_parsable_fields(line);
+    let [link, state, speed, media] = fields.as_slice() else {
+        return Err(format!("unexpected show-phys output: {line:?}"));
+    };
+    let speed =
+        speed.parse().map_err(|e| format!("invalid speed {speed:?}: {e}"))?;
+    Ok(PhysicalLinkInfo {
+        link: PhysicalLink(link.clone()),
+        media: media.clone(),
+        state: LinkState::from_dladm(state),
+        speed,
+    })
+}
+
+// Parse a line of `dladm show-vnic -p -o LINK,OVER,MACADDRESS,MTU,VID` output
+fn parse_vnic_info(line: &str) -> Result<VnicInfo, String> {
+    let fields = split_parsable_fields(line);
+    let [name, over, mac, mtu, vid] = fields.as_slice() else {
+        return Err(format!("unexpected show-vnic output: {line:?}"));
+    };
+    let mac = MacAddr::from_str(&zero_pad_mac(mac))
+        .map_err(|e| format!("invalid MAC address {mac:?}: {e}"))?;
+    let mtu = mtu.parse().map_err(|e| format!("invalid MTU {mtu:?}: {e}"))?;
+    let vlan = match vid.as_str() {
+        "" | "0" => None,
+        vid => Some(
+            VlanID::from_str(vid)
+                .map_err(|e| format!("invalid VLAN ID {vid:?}: {e}"))?,
+        ),
+    };
+    Ok(VnicInfo { name: name.clone(), over: over.clone(), mac, mtu, vlan })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::process::Output;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    // The mocked `execute` is global; keep tests that use it from overlapping.
+    static MOCK_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_dry_run_does_not_execute_mutating_commands() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        // No subprocess may be spawned while in dry-run mode.
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().never();
+
+        let log = Logger::root(slog::Discard, slog::o!());
+        Dladm::set_dry_run(Some(log));
+        Dladm::create_vnic(
+            &Etherstub("mystub".to_string()),
+            "oxTest0",
+            None,
+            None,
+            1500,
+        )
+        .expect("dry-run create_vnic should succeed");
+        Dladm::delete_vnic("oxTest0")
+            .expect("dry-run delete_vnic should succeed");
+        Dladm::set_dry_run(None);
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_delete_vnic_is_idempotent() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(2).returning(|cmd| {
+            let args: Vec<_> =
+                cmd.get_args().map(|a| a.to_string_lossy()).collect();
+            let stderr: &[u8] = match args.last().unwrap().as_ref() {
+                "oxGone0" => b"dladm: vnic deletion failed: object not found\n",
+                "oxBusy0" => b"dladm: vnic deletion failed: link busy\n",
+                other => panic!("unexpected link {other}"),
+            };
+            let output = Output {
+                status: std::process::ExitStatus::from_raw(1 << 8),
+                stdout: vec![],
+                stderr: stderr.to_vec(),
+            };
+            Err(crate::inner::output_to_exec_error(cmd, &output))
+        });
+
+        Dladm::delete_vnic("oxGone0")
+            .expect("deleting an absent vnic should succeed");
+        Dladm::delete_vnic("oxBusy0")
+            .expect_err("other failures should still be reported");
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_create_vnic_retries_transient_failure() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        // The first `create-vnic` fails because the device is busy; the retry
+        // and the following `set-linkprop` succeed.
+        let mut create_attempts = 0;
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(3).returning(move |cmd| {
+            let args: Vec<_> =
+                cmd.get_args().map(|a| a.to_string_lossy()).collect();
+            let failed = args[1] == "create-vnic" && {
+                create_attempts += 1;
+                create_attempts == 1
+            };
+            let code = if failed { 1 } else { 0 };
+            let output = Output {
+                status: std::process::ExitStatus::from_raw(code << 8),
+                stdout: vec![],
+                stderr: if failed {
+                    b"dladm: vnic creation failed: device busy\n".to_vec()
+                } else {
+                    vec![]
+                },
+            };
+            if failed {
+                Err(crate::inner::output_to_exec_error(cmd, &output))
+            } else {
+                Ok(output)
+            }
+        });
+
+        Dladm::create_vnic(
+            &Etherstub("mystub".to_string()),
+            "oxTest0",
+            None,
+            None,
+            1500,
+        )
+        .expect("create_vnic should succeed after retrying");
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_retry_gives_up_after_policy_attempts() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        // Every attempt fails transiently, so the command is run exactly as
+        // many times as the policy allows.
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(2).returning(|cmd| {
+            let output = Output {
+                status: std::process::ExitStatus::from_raw(1 << 8),
+                stdout: vec![],
+                stderr: b"dladm: vnic creation failed: device busy\n".to_vec(),
+            };
+            Err(crate::inner::output_to_exec_error(cmd, &output))
+        });
+
+        let mut command = std::process::Command::new(PFEXEC);
+        let cmd = command.args(&[DLADM, "create-vnic", "oxTest0"]);
+        let policy = RetryPolicy { attempts: 2, backoff: Duration::ZERO };
+        let err = execute_mutating_with_retry(cmd, policy)
+            .expect_err("transient failures should be reported eventually");
+        assert!(is_transient_failure(&err));
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_find_physical_prefers_up_links() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(2).returning(|_| {
+            Ok(Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: b"igb0:down:0:Ethernet\n\
+                    cxgbe0:up:100000:Ethernet\n\
+                    cxgbe1:unknown:0:Ethernet\n"
+                    .to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        assert_eq!(
+            Dladm::list_physical_detailed().unwrap(),
+            [
+                PhysicalLinkInfo {
+                    link: PhysicalLink(String::from("igb0")),
+                    media: String::from("Ethernet"),
+                    state: LinkState::Down,
+                    speed: 0,
+                },
+                PhysicalLinkInfo {
+                    link: PhysicalLink(String::from("cxgbe0")),
+                    media: String::from("Ethernet"),
+                    state: LinkState::Up,
+                    speed: 100000,
+                },
+                PhysicalLinkInfo {
+                    link: PhysicalLink(String::from("cxgbe1")),
+                    media: String::from("Ethernet"),
+                    state: LinkState::Unknown,
+                    speed: 0,
+                },
+            ],
+        );
+        assert_eq!(
+            Dladm::find_physical().unwrap(),
+            PhysicalLink(String::from("cxgbe0")),
+        );
+        execute_ctx.checkpoint();
+
+        // A link with unparseable details fails the detailed listing, but
+        // not the lookup, and is still a candidate if no link is up.
+        execute_ctx.expect().times(2).returning(|_| {
+            Ok(Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: b"igb0:down:bogus:Ethernet\n\
+                    cxgbe0:down:0:Ethernet\n"
+                    .to_vec(),
+                stderr: vec![],
+            })
+        });
+        assert!(matches!(
+            Dladm::list_physical_detailed(),
+            Err(FindPhysicalLinkError::ParseFailure(_))
+        ));
+        assert_eq!(
+            Dladm::find_physical().unwrap(),
+            PhysicalLink(String::from("igb0")),
+        );
+        execute_ctx.checkpoint();
+        execute_ctx.expect().times(1).returning(|_| {
+            Ok(Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: b"igb0:down:bogus:Ethernet\n\
+                    cxgbe0:up:100000:Ethernet\n"
+                    .to_vec(),
+                stderr: vec![],
+            })
+        });
+        assert_eq!(
+            Dladm::find_physical().unwrap(),
+            PhysicalLink(String::from("cxgbe0")),
+        );
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_get_and_delete_vlans() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let deleted = Arc::new(Mutex::new(Vec::new()));
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(4).returning({
+            let deleted = deleted.clone();
+            move |cmd| {
+                let args: Vec<_> =
+                    cmd.get_args().map(|a| a.to_string_lossy()).collect();
+                let stdout: &[u8] = match args[1].as_ref() {
+                    "show-vnic" => {
+                        b"oxControl0:0\n\
+                        oxTagged0:300\n\
+                        oxTagged1:400\n\
+                        oxTagged2:300\n"
+                    }
+                    "delete-vnic" => {
+                        deleted.lock().unwrap().push(args[2].to_string());
+                        b""
+                    }
+                    other => panic!("unexpected subcommand {other}"),
+                };
+                Ok(Output {
+                    status: std::process::ExitStatus::from_raw(0),
+                    stdout: stdout.to_vec(),
+                    stderr: vec![],
+                })
+            }
+        });
+
+        let vlan300 = VlanID::new(300).unwrap();
+        let vlan400 = VlanID::new(400).unwrap();
+        assert_eq!(
+            Dladm::get_vlans().unwrap(),
+            [
+                (String::from("oxTagged0"), vlan300),
+                (String::from("oxTagged1"), vlan400),
+                (String::from("oxTagged2"), vlan300),
+            ],
+        );
+        assert_eq!(
+            Dladm::delete_vlan(vlan300).unwrap(),
+            ["oxTagged0", "oxTagged2"],
+        );
+        assert_eq!(*deleted.lock().unwrap(), ["oxTagged0", "oxTagged2"]);
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_list_vnics_over() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(2).returning(|_| {
+            Ok(Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: b"oxControl0:underlay_stub0\n\
+                    oxBootstrap0:bootstrap_stub0\n\
+                    oxControl1:underlay_stub0\n\
+                    vopte0:cxgbe0\n"
+                    .to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        assert_eq!(
+            Dladm::list_vnics_over(UNDERLAY_ETHERSTUB_NAME).unwrap(),
+            ["oxControl0", "oxControl1"],
+        );
+        assert!(Dladm::list_vnics_over("cxgbe1").unwrap().is_empty());
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_vnic_exists() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(3).returning(|cmd| {
+            let args: Vec<_> =
+                cmd.get_args().map(|a| a.to_string_lossy()).collect();
+            let (code, stderr): (i32, &[u8]) = match args
+                .last()
+                .unwrap()
+                .as_ref()
+            {
+                "oxPresent0" => (0, b""),
+                "oxGone0" => (
+                    1,
+                    b"dladm: invalid vnic name 'oxGone0': object not found\n",
+                ),
+                "oxBroken0" => (1, b"dladm: permission denied\n"),
+                other => panic!("unexpected link {other}"),
+            };
+            let output = Output {
+                status: std::process::ExitStatus::from_raw(code << 8),
+                stdout: vec![],
+                stderr: stderr.to_vec(),
+            };
+            if output.status.success() {
+                Ok(output)
+            } else {
+                Err(crate::inner::output_to_exec_error(cmd, &output))
+            }
+        });
+
+        assert!(Dladm::vnic_exists("oxPresent0").unwrap());
+        assert!(!Dladm::vnic_exists("oxGone0").unwrap());
+        Dladm::vnic_exists("oxBroken0")
+            .expect_err("other failures should still be reported");
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_get_and_set_mtu() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(2).returning(|cmd| {
+            let args: Vec<_> =
+                cmd.get_args().map(|a| a.to_string_lossy()).collect();
+            let stdout: &[u8] = match args[1].as_ref() {
+                "show-linkprop" => b"9000\n",
+                "set-linkprop" => {
+                    assert_eq!(args[4], "mtu=1500");
+                    b""
+                }
+                other => panic!("unexpected subcommand {other}"),
+            };
+            Ok(Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: stdout.to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        assert_eq!(Dladm::get_mtu("oxTest0").unwrap(), 9000);
+        Dladm::set_mtu("oxTest0", 1500).unwrap();
+
+        // Implausible values are rejected without running anything.
+        for mtu in [0, MIN_MTU - 1, MAX_MTU + 1] {
+            match Dladm::set_mtu("oxTest0", mtu) {
+                Err(SetMtuError::OutOfRange { mtu: found, .. }) => {
+                    assert_eq!(found, mtu)
+                }
+                result => panic!("unexpected result for {mtu}: {result:?}"),
+            }
+        }
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_create_vnic_with_macs() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(4).returning({
+            let commands = commands.clone();
+            move |cmd| {
+                commands.lock().unwrap().push(crate::inner::to_string(cmd));
+                Ok(Output {
+                    status: std::process::ExitStatus::from_raw(0),
+                    stdout: vec![],
+                    stderr: vec![],
+                })
+            }
+        });
+
+        let stub = Etherstub("mystub".to_string());
+        let macs: [MacAddr; 2] = [
+            "a8:40:25:00:00:01".parse().unwrap(),
+            "a8:40:25:00:00:02".parse().unwrap(),
+        ];
+        Dladm::create_vnic_with_macs(&stub, "oxTest0", &macs, None, 1500)
+            .unwrap();
+        Dladm::create_vnic(&stub, "oxTest1", Some(macs[0]), None, 1500)
+            .unwrap();
+
+        let commands = commands.lock().unwrap();
+        assert_eq!(
+            commands[0],
+            format!(
+                "{DLADM} create-vnic -t -l mystub -m {},{} -p mtu=1500 oxTest0",
+                macs[0].0, macs[1].0,
+            ),
+        );
+        assert_eq!(
+            commands[2],
+            format!(
+                "{DLADM} create-vnic -t -l mystub -m {} -p mtu=1500 oxTest1",
+                macs[0].0,
+            ),
+        );
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_get_vnic_info() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(3).returning(|cmd| {
+            let args: Vec<_> =
+                cmd.get_args().map(|a| a.to_string_lossy()).collect();
+            let (code, stdout, stderr): (i32, &[u8], &[u8]) =
+                match args.last().unwrap().as_ref() {
+                    "oxPresent0" => (
+                        0,
+                        b"oxPresent0:underlay_stub0:2\\:8\\:20\\:a\\:b\\:c:9000:0\n",
+                        b"",
+                    ),
+                    "oxTagged0" => (
+                        0,
+                        b"oxTagged0:cxgbe0:a8\\:40\\:25\\:0\\:0\\:1:1500:300\n",
+                        b"",
+                    ),
+                    "oxGone0" => (
+                        1,
+                        b"",
+                        b"dladm: invalid vnic name 'oxGone0': object not found\n",
+                    ),
+                    other => panic!("unexpected link {other}"),
+                };
+            let output = Output {
+                status: std::process::ExitStatus::from_raw(code << 8),
+                stdout: stdout.to_vec(),
+                stderr: stderr.to_vec(),
+            };
+            if output.status.success() {
+                Ok(output)
+            } else {
+                Err(crate::inner::output_to_exec_error(cmd, &output))
+            }
+        });
+
+        assert_eq!(
+            Dladm::get_vnic_info("oxPresent0").unwrap(),
+            Some(VnicInfo {
+                name: String::from("oxPresent0"),
+                over: String::from("underlay_stub0"),
+                mac: "02:08:20:0a:0b:0c".parse().unwrap(),
+                mtu: 9000,
+                vlan: None,
+            }),
+        );
+        assert_eq!(
+            Dladm::get_vnic_info("oxTagged0").unwrap(),
+            Some(VnicInfo {
+                name: String::from("oxTagged0"),
+                over: String::from("cxgbe0"),
+                mac: "a8:40:25:00:00:01".parse().unwrap(),
+                mtu: 1500,
+                vlan: Some(VlanID::new(300).unwrap()),
+            }),
+        );
+        assert_eq!(Dladm::get_vnic_info("oxGone0").unwrap(), None);
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_get_linkprop_with_source() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(3).returning(|cmd| {
+            let args: Vec<_> =
+                cmd.get_args().map(|a| a.to_string_lossy()).collect();
+            let stdout: &[u8] = match args.last().unwrap().as_ref() {
+                "oxDefault0" => b"1500:1500:576-9000\n",
+                "oxSet0" => b"9000:1500:576-9000\n",
+                "oxMac0" => b"2\\:8\\:20\\:1\\:2\\:3::\n",
+                other => panic!("unexpected link {other}"),
+            };
+            Ok(Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: stdout.to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        assert_eq!(
+            Dladm::get_linkprop_with_source("oxDefault0", "mtu").unwrap(),
+            (String::from("1500"), LinkpropSource::Default),
+        );
+        assert_eq!(
+            Dladm::get_linkprop_with_source("oxSet0", "mtu").unwrap(),
+            (String::from("9000"), LinkpropSource::Set),
+        );
+        assert_eq!(
+            Dladm::get_linkprop_with_source("oxMac0", "mac-address").unwrap(),
+            (String::from("2:8:20:1:2:3"), LinkpropSource::Set),
+        );
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_show_linkprop_all() {
+        let _guard = MOCK_LOCK.lock().unwrap();
+        crate::USE_MOCKS.store(true, Ordering::SeqCst);
+
+        let execute_ctx = crate::execute_helper_context();
+        execute_ctx.expect().times(2).returning(|cmd| {
+            let args: Vec<_> =
+                cmd.get_args().map(|a| a.to_string_lossy()).collect();
+            let stdout: &[u8] = match args.last().unwrap().as_ref() {
+                "oxVnic0" => {
+                    b"autopush:\n\
+                      zone:\n\
+                      state:up\n\
+                      mtu:9000\n\
+                      mac-address:2\\:8\\:20\\:1\\:2\\:3\n\
+                      vlan-announce:off\n"
+                }
+                "oxBad0" => b"mtu:9000:extra\n",
+                other => panic!("unexpected link {other}"),
+            };
+            Ok(Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: stdout.to_vec(),
+                stderr: vec![],
+            })
+        });
+
+        let props = Dladm::show_linkprop_all("oxVnic0").unwrap();
+        let expected: BTreeMap<String, String> = [
+            ("autopush", ""),
+            ("zone", ""),
+            ("state", "up"),
+            ("mtu", "9000"),
+            ("mac-address", "2:8:20:1:2:3"),
+            ("vlan-announce", "off"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(props, expected);
+
+        Dladm::show_linkprop_all("oxBad0")
+            .expect_err("malformed output should be reported");
+
+        execute_ctx.checkpoint();
+        crate::USE_MOCKS.store(false, Ordering::SeqCst);
+    }
+}
@@ -7,8 +7,11 @@
 use slog::Logger;
 
 use crate::addrobj::AddrObject;
+use omicron_common::api::external::MacAddr;
 use omicron_common::api::internal::shared::NetworkInterfaceKind;
 use std::net::IpAddr;
+use std::sync::Mutex;
+use uuid::Uuid;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -40,3 +43,64 @@ pub fn delete_all_xde_devices(log: &Logger) -> Result<(), Error> {
     slog::warn!(log, "`xde` driver is a fiction on non-illumos systems");
     Ok(())
 }
+
+/// A port "created" by the [`crate::opte::PortManager`] on a non-illumos
+/// system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockPort {
+    pub id: Uuid,
+    pub kind: NetworkInterfaceKind,
+    pub name: String,
+    pub ip: IpAddr,
+    pub mac: MacAddr,
+    pub is_service: bool,
+}
+
+/// An operation performed by the [`crate::opte::PortManager`] on a
+/// non-illumos system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PortOperation {
+    Create(MockPort),
+    Release(MockPort),
+}
+
+/// In-memory record of the ports "created" and "released" by a
+/// [`crate::opte::PortManager`], so that tests can assert on them.
+#[derive(Debug, Default)]
+pub(crate) struct PortRegistry {
+    operations: Mutex<Vec<PortOperation>>,
+}
+
+impl PortRegistry {
+    pub(crate) fn record_create(&self, port: MockPort) {
+        self.operations.lock().unwrap().push(PortOperation::Create(port));
+    }
+
+    /// Records the release of the most recently created port with the given
+    /// id and kind, failing if no such port was ever created
+    pub(crate) fn record_release(
+        &self,
+        id: Uuid,
+        kind: NetworkInterfaceKind,
+    ) -> Result<(), Error> {
+        let mut operations = self.operations.lock().unwrap();
+        let port = operations
+            .iter()
+            .rev()
+            .find_map(|op| match op {
+                PortOperation::Create(port)
+                    if port.id == id && port.kind == kind =>
+                {
+                    Some(port.clone())
+                }
+                _ => None,
+            })
+            .ok_or(Error::ReleaseMissingPort(id, kind))?;
+        operations.push(PortOperation::Release(port));
+        Ok(())
+    }
+
+    pub(crate) fn operations(&self) -> Vec<PortOperation> {
+        self.operations.lock().unwrap().clone()
+    }
+}
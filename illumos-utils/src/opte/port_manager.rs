@@ -77,6 +77,11 @@ struct PortManagerInner {
 
     /// Map of all current resolved routes.
     routes: Mutex<HashMap<RouterId, RouteSet>>,
+
+    /// Record of ports created and released, since there is no `xde` driver
+    /// to observe on non-illumos systems.
+    #[cfg(not(target_os = "illumos"))]
+    registry: super::PortRegistry,
 }
 
 impl PortManagerInner {
@@ -116,6 +121,8 @@ impl PortManager {
             underlay_ip,
             ports: Mutex::new(BTreeMap::new()),
             routes: Mutex::new(Default::default()),
+            #[cfg(not(target_os = "illumos"))]
+            registry: Default::default(),
         });
 
         Self { inner }
@@ -125,6 +132,38 @@ impl PortManager {
         &self.inner.underlay_ip
     }
 
+    /// Returns every port created by this manager, in order, including those
+    /// that have since been released.
+    #[cfg(not(target_os = "illumos"))]
+    pub fn created_ports(&self) -> Vec<super::MockPort> {
+        self.port_operations()
+            .into_iter()
+            .filter_map(|op| match op {
+                super::PortOperation::Create(port) => Some(port),
+                super::PortOperation::Release(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns every port released by this manager, in order.
+    #[cfg(not(target_os = "illumos"))]
+    pub fn released_ports(&self) -> Vec<super::MockPort> {
+        self.port_operations()
+            .into_iter()
+            .filter_map(|op| match op {
+                super::PortOperation::Create(_) => None,
+                super::PortOperation::Release(port) => Some(port),
+            })
+            .collect()
+    }
+
+    /// Returns the sequence of port creations and releases performed by this
+    /// manager.
+    #[cfg(not(target_os = "illumos"))]
+    pub fn port_operations(&self) -> Vec<super::PortOperation> {
+        self.inner.registry.operations()
+    }
+
     /// Create an OPTE port
     #[cfg_attr(not(target_os = "illumos"), allow(unused_variables))]
     pub fn create_port(
@@ -307,6 +346,15 @@ impl PortManager {
                 nic.id,
                 nic.kind,
             );
+            #[cfg(not(target_os = "illumos"))]
+            self.inner.registry.record_create(super::MockPort {
+                id: nic.id,
+                kind: nic.kind,
+                name: port_name.clone(),
+                ip: nic.ip,
+                mac: nic.mac,
+                is_service,
+            });
             (port, ticket)
         };
 
@@ -887,6 +935,15 @@ impl PortTicket {
             return Err(Error::ReleaseMissingPort(self.id, self.kind));
         };
         drop(ports);
+        #[cfg(not(target_os = "illumos"))]
+        if let Err(e) = self.manager.registry.record_release(self.id, self.kind)
+        {
+            error!(
+                self.manager.log,
+                "Failed to record port release";
+                "error" => %e,
+            );
+        }
 
         // Cleanup the set of subnets we want to receive routes for.
         let mut routes = self.manager.routes.lock().unwrap();
@@ -939,3 +996,76 @@ impl Drop for PortTicket {
         let _ = self.release_inner();
     }
 }
+
+#[cfg(all(test, not(target_os = "illumos")))]
+mod test {
+    use super::*;
+    use crate::opte::MockPort;
+    use crate::opte::PortOperation;
+
+    #[test]
+    fn test_mock_port_manager_records_ports() {
+        let log = Logger::root(slog::Discard, slog::o!());
+        let manager = PortManager::new(log, Ipv6Addr::LOCALHOST);
+
+        let nic = NetworkInterface {
+            id: Uuid::new_v4(),
+            kind: NetworkInterfaceKind::Service { id: Uuid::new_v4() },
+            name: "test-nic".parse().unwrap(),
+            ip: "172.30.1.5".parse().unwrap(),
+            mac: external::MacAddr::random_system(),
+            subnet: "172.30.1.0/24".parse().unwrap(),
+            vni: external::Vni::SERVICES_VNI,
+            primary: true,
+            slot: 0,
+            transit_ips: vec![],
+        };
+        let dhcp_config = DhcpCfg {
+            hostname: None,
+            host_domain: None,
+            domain_search_list: vec![],
+            dns4_servers: vec![],
+            dns6_servers: vec![],
+        };
+        let (port, ticket) = manager
+            .create_port(PortCreateParams {
+                nic: &nic,
+                source_nat: None,
+                ephemeral_ip: None,
+                floating_ips: &[],
+                firewall_rules: &[],
+                dhcp_config,
+                is_service: true,
+            })
+            .expect("failed to create port");
+
+        let expected = MockPort {
+            id: nic.id,
+            kind: nic.kind,
+            name: port.name().to_string(),
+            ip: nic.ip,
+            mac: nic.mac,
+            is_service: true,
+        };
+        assert_eq!(manager.created_ports(), [expected.clone()]);
+        assert!(manager.released_ports().is_empty());
+
+        ticket.release();
+        assert_eq!(manager.released_ports(), [expected.clone()]);
+        assert_eq!(
+            manager.port_operations(),
+            [
+                PortOperation::Create(expected.clone()),
+                PortOperation::Release(expected),
+            ],
+        );
+
+        // Releasing a port that was never created is an error, not a panic
+        let missing = Uuid::new_v4();
+        assert!(matches!(
+            manager.inner.registry.record_release(missing, nic.kind),
+            Err(Error::ReleaseMissingPort(id, _)) if id == missing
+        ));
+        assert_eq!(manager.released_ports().len(), 1);
+    }
+}
@@ -562,6 +562,73 @@ pub struct RegionReplacementConfig {
     /// period (in seconds) for periodic activations of this background task
     #[serde_as(as = "DurationSeconds<u64>")]
     pub period_secs: Duration,
+
+    /// which sources to consult when looking for regions that need replacing
+    ///
+    /// Default: expunged physical disks only
+    #[serde(default)]
+    pub detection_mode: RegionReplacementDetectionMode,
+
+    /// upper bound (in seconds) on the random delay before starting sagas
+    ///
+    /// This desynchronizes multiple Nexus instances activating this task on
+    /// the same schedule.  Default: 0 (no delay)
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default)]
+    pub max_start_jitter_secs: Duration,
+
+    /// upper bound on the number of region replacement requests that may be
+    /// in flight (i.e., not yet complete) before this task stops creating new
+    /// ones
+    ///
+    /// Default: no limit
+    #[serde(default)]
+    pub max_in_flight_requests: Option<usize>,
+
+    /// whether to wait until a later activation before starting the saga for
+    /// a request that this task just created
+    ///
+    /// This separates creating a request from starting it, so that the start
+    /// doesn't race with other Nexus instances acting on the new request.
+    /// Default: false (start new requests in the same activation)
+    #[serde(default)]
+    pub start_cooldown: bool,
+
+    /// upper bound on the number of start sagas launched by a single
+    /// activation of this task
+    ///
+    /// Requests beyond this are left for a later activation, so that many
+    /// disks being expunged at once doesn't flood the saga executor.
+    /// Default: no limit
+    #[serde(default)]
+    pub max_starts_per_activation: Option<usize>,
+
+    /// how the region replacement start saga allocates replacement regions
+    ///
+    /// Default: random, with each region on a distinct sled
+    #[serde(default = "default_region_replacement_allocation_strategy")]
+    pub allocation_strategy: RegionAllocationStrategy,
+}
+
+fn default_region_replacement_allocation_strategy() -> RegionAllocationStrategy
+{
+    RegionAllocationStrategy::RandomWithDistinctSleds { seed: None }
+}
+
+/// Describes how the region replacement task finds regions to replace
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionReplacementDetectionMode {
+    /// Replace regions on physical disks whose policy is expunged
+    #[default]
+    ExpungedDisks,
+    /// Replace regions on zpools that are missing from the latest inventory
+    /// collection, even if their physical disks have not been expunged
+    MissingFromInventory,
+    /// Replace regions found by either of the above
+    All,
 }
 
 #[serde_as]
@@ -1054,6 +1121,16 @@ mod test {
                             },
                         region_replacement: RegionReplacementConfig {
                             period_secs: Duration::from_secs(30),
+                            detection_mode:
+                                RegionReplacementDetectionMode::ExpungedDisks,
+                            max_start_jitter_secs: Duration::ZERO,
+                            max_in_flight_requests: None,
+                            start_cooldown: false,
+                            max_starts_per_activation: None,
+                            allocation_strategy:
+                                RegionAllocationStrategy::RandomWithDistinctSleds {
+                                    seed: None,
+                                },
                         },
                         region_replacement_driver:
                             RegionReplacementDriverConfig {
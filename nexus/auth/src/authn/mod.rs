@@ -41,6 +41,7 @@ use crate::authz;
 use newtype_derive::NewtypeDisplay;
 use nexus_db_fixed_data::silo::DEFAULT_SILO;
 use nexus_types::external_api::shared::FleetRole;
+use nexus_types::external_api::shared::ProjectRole;
 use nexus_types::external_api::shared::SiloRole;
 use nexus_types::identity::Asset;
 use omicron_common::api::external::LookupType;
@@ -250,13 +251,30 @@ pub struct SiloAuthnPolicy {
     /// Describes which fleet-level roles are automatically conferred by which
     /// silo-level roles.
     mapped_fleet_roles: BTreeMap<SiloRole, BTreeSet<FleetRole>>,
+
+    /// Describes which project-level roles are automatically conferred (on
+    /// every Project in the Silo) by which silo-level roles.
+    #[serde(default)]
+    mapped_project_roles: BTreeMap<SiloRole, BTreeSet<ProjectRole>>,
 }
 
 impl SiloAuthnPolicy {
     pub fn new(
         mapped_fleet_roles: BTreeMap<SiloRole, BTreeSet<FleetRole>>,
     ) -> SiloAuthnPolicy {
-        SiloAuthnPolicy { mapped_fleet_roles }
+        SiloAuthnPolicy {
+            mapped_fleet_roles,
+            mapped_project_roles: BTreeMap::new(),
+        }
+    }
+
+    /// Returns this policy, updated so that the given silo-level roles confer
+    /// the given project-level roles
+    pub fn with_mapped_project_roles(
+        self,
+        mapped_project_roles: BTreeMap<SiloRole, BTreeSet<ProjectRole>>,
+    ) -> SiloAuthnPolicy {
+        SiloAuthnPolicy { mapped_project_roles, ..self }
     }
 
     pub fn mapped_fleet_roles(
@@ -264,6 +282,12 @@ impl SiloAuthnPolicy {
     ) -> &BTreeMap<SiloRole, BTreeSet<FleetRole>> {
         &self.mapped_fleet_roles
     }
+
+    pub fn mapped_project_roles(
+        &self,
+    ) -> &BTreeMap<SiloRole, BTreeSet<ProjectRole>> {
+        &self.mapped_project_roles
+    }
 }
 
 impl TryFrom<&nexus_db_model::Silo> for SiloAuthnPolicy {
@@ -272,9 +296,10 @@ impl TryFrom<&nexus_db_model::Silo> for SiloAuthnPolicy {
     fn try_from(
         value: &nexus_db_model::Silo,
     ) -> Result<Self, omicron_common::api::external::Error> {
-        value
-            .mapped_fleet_roles()
-            .map(|mapped_fleet_roles| SiloAuthnPolicy { mapped_fleet_roles })
+        let mapped_fleet_roles = value.mapped_fleet_roles()?;
+        let mapped_project_roles = value.mapped_project_roles()?;
+        Ok(SiloAuthnPolicy::new(mapped_fleet_roles)
+            .with_mapped_project_roles(mapped_project_roles))
     }
 }
 
@@ -9,6 +9,7 @@ use crate::authn;
 use crate::authz::SiloUser;
 use nexus_db_model::DatabaseString;
 use nexus_types::external_api::shared::FleetRole;
+use nexus_types::external_api::shared::ProjectRole;
 use omicron_common::api::external::LookupType;
 use omicron_common::api::external::ResourceType;
 use uuid::Uuid;
@@ -90,6 +91,30 @@ impl AuthenticatedActor {
             })
             .collect()
     }
+
+    /// Returns the list of Silo roles that confer the given Project roles (on
+    /// Projects within that Silo) for this actor's Silo
+    pub fn confers_project_role(&self, project_role_str: &str) -> Vec<String> {
+        let Ok(project_role) =
+            ProjectRole::from_database_string(project_role_str)
+        else {
+            return vec![];
+        };
+        let Some(silo_policy) = self.silo_policy.as_ref() else {
+            return vec![];
+        };
+        silo_policy
+            .mapped_project_roles()
+            .into_iter()
+            .filter_map(|(silo_role, project_roles)| {
+                if project_roles.contains(&project_role) {
+                    Some(silo_role.to_database_string().to_string())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 impl PartialEq for AuthenticatedActor {
@@ -137,6 +162,12 @@ impl oso::PolarClass for AuthenticatedActor {
                     a.confers_fleet_role(&role)
                 },
             )
+            .add_method(
+                "confers_project_role",
+                |a: &AuthenticatedActor, role: String| {
+                    a.confers_project_role(&role)
+                },
+            )
             .add_method(
                 "equals_silo_user",
                 |a: &AuthenticatedActor, u: SiloUser| a.actor_id == u.id(),
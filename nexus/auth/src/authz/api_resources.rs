@@ -125,21 +125,9 @@ where
         actor: AnyActor,
         action: Action,
     ) -> Error {
-        if action == Action::Read {
-            return self.not_found();
-        }
-
-        // If the user failed an authz check, and they can't even read this
-        // resource, then we should produce a 404 rather than a 401/403.
-        match authz.is_allowed(&actor, Action::Read, self) {
-            Err(error) => Error::internal_error(&format!(
-                "failed to compute read authorization to determine visibility: \
-                {:#}",
-                error
-            )),
-            Ok(false) => self.not_found(),
-            Ok(true) => error,
-        }
+        mask_unauthorized(self, authz, error, actor, action, || {
+            self.not_found()
+        })
     }
 
     fn polar_class(&self) -> oso::Class {
@@ -147,6 +135,39 @@ where
     }
 }
 
+/// Implements [`AuthorizedResource::on_unauthorized()`] for resources that
+/// should be hidden from actors that cannot read them
+///
+/// If the actor failed an authz check on `resource` and they can't even read
+/// it, then we produce the error returned by `not_found` (normally a 404)
+/// rather than `error` (normally a 403).  Synthetic resources that are
+/// sensitive can opt into this from their own `on_unauthorized()`.
+pub fn mask_unauthorized<R>(
+    resource: &R,
+    authz: &Authz,
+    error: Error,
+    actor: AnyActor,
+    action: Action,
+    not_found: impl FnOnce() -> Error,
+) -> Error
+where
+    R: oso::ToPolar + Clone,
+{
+    if action == Action::Read {
+        return not_found();
+    }
+
+    match authz.is_allowed(&actor, Action::Read, resource) {
+        Err(error) => Error::internal_error(&format!(
+            "failed to compute read authorization to determine visibility: \
+            {:#}",
+            error
+        )),
+        Ok(false) => not_found(),
+        Ok(true) => error,
+    }
+}
+
 /// Represents the Oxide fleet for authz purposes
 ///
 /// Fleet-level resources are essentially global.  See RFD 24 for more on
@@ -270,12 +291,16 @@ impl AuthorizedResource for BlueprintConfig {
 
     fn on_unauthorized(
         &self,
-        _: &Authz,
+        authz: &Authz,
         error: Error,
-        _: AnyActor,
-        _: Action,
+        actor: AnyActor,
+        action: Action,
     ) -> Error {
-        error
+        // Blueprints describe the internals of the whole system.  Don't reveal
+        // anything about them to actors that can't read them.
+        mask_unauthorized(self, authz, error, actor, action, || {
+            Error::non_resourcetype_not_found("blueprint configuration")
+        })
     }
 
     fn polar_class(&self) -> oso::Class {
@@ -330,6 +355,53 @@ impl AuthorizedResource for ConsoleSessionList {
     }
 }
 
+/// SupportBundleList is a synthetic resource used for modeling who has access
+/// to create and list support bundles.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SupportBundleList;
+
+pub const SUPPORT_BUNDLE_LIST: SupportBundleList = SupportBundleList {};
+
+impl oso::PolarClass for SupportBundleList {
+    fn get_polar_class_builder() -> oso::ClassBuilder<Self> {
+        // Roles are not directly attached to SupportBundleList.
+        oso::Class::builder()
+            .with_equality_check()
+            .add_method(
+                "has_role",
+                |_: &SupportBundleList,
+                 _actor: AuthenticatedActor,
+                 _role: String| false,
+            )
+            .add_attribute_getter("fleet", |_| FLEET)
+    }
+}
+
+impl AuthorizedResource for SupportBundleList {
+    fn load_roles<'fut>(
+        &'fut self,
+        opctx: &'fut OpContext,
+        authn: &'fut authn::Context,
+        roleset: &'fut mut RoleSet,
+    ) -> futures::future::BoxFuture<'fut, Result<(), Error>> {
+        load_roles_for_resource_tree(&FLEET, opctx, authn, roleset).boxed()
+    }
+
+    fn on_unauthorized(
+        &self,
+        _: &Authz,
+        error: Error,
+        _: AnyActor,
+        _: Action,
+    ) -> Error {
+        error
+    }
+
+    fn polar_class(&self) -> oso::Class {
+        Self::get_polar_class()
+    }
+}
+
 /// DnsConfig is a synthetic resource used for modeling access to the internal
 /// and external DNS configuration
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -502,12 +574,16 @@ impl AuthorizedResource for Inventory {
 
     fn on_unauthorized(
         &self,
-        _: &Authz,
+        authz: &Authz,
         error: Error,
-        _: AnyActor,
-        _: Action,
+        actor: AnyActor,
+        action: Action,
     ) -> Error {
-        error
+        // Hardware inventory is sensitive.  Don't reveal anything about it to
+        // actors that can't read it.
+        mask_unauthorized(self, authz, error, actor, action, || {
+            Error::non_resourcetype_not_found("inventory")
+        })
     }
 
     fn polar_class(&self) -> oso::Class {
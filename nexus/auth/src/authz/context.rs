@@ -298,4 +298,197 @@ mod test {
 
         logctx.cleanup_successful();
     }
+
+    /// Storage that reports that the actor has exactly one role on one
+    /// resource (or no roles at all)
+    struct OneRoleStorage {
+        role: Option<(ResourceType, Uuid, &'static str)>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::storage::Storage for OneRoleStorage {
+        async fn role_asgn_list_for(
+            &self,
+            _opctx: &OpContext,
+            identity_type: IdentityType,
+            identity_id: Uuid,
+            resource_type: ResourceType,
+            resource_id: Uuid,
+        ) -> Result<Vec<RoleAssignment>, Error> {
+            Ok(self
+                .role
+                .iter()
+                .filter(|(rtype, rid, _)| {
+                    *rtype == resource_type && *rid == resource_id
+                })
+                .map(|(_, _, role_name)| {
+                    RoleAssignment::new(
+                        identity_type,
+                        identity_id,
+                        resource_type,
+                        resource_id,
+                        role_name,
+                    )
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_silo_confers_project_roles() {
+        use crate::authz::Project;
+        use crate::authz::Silo;
+        use crate::authz::FLEET;
+        use nexus_types::external_api::shared::ProjectRole;
+        use nexus_types::external_api::shared::SiloRole;
+        use omicron_common::api::external::LookupType;
+        use std::collections::BTreeMap;
+        use std::collections::BTreeSet;
+
+        let logctx = dev::test_setup_log("test_silo_confers_project_roles");
+        let silo_id = Uuid::new_v4();
+        let datastore: Arc<dyn crate::storage::Storage> =
+            Arc::new(OneRoleStorage {
+                role: Some((ResourceType::Silo, silo_id, "viewer")),
+            });
+        let opctx = OpContext::for_background(
+            logctx.log.new(o!()),
+            Arc::new(Authz::new(&logctx.log)),
+            authn::Context::internal_db_init(),
+            Arc::clone(&datastore),
+        );
+
+        let silo = Silo::new(FLEET, silo_id, LookupType::ById(silo_id));
+        let project_id = Uuid::new_v4();
+        let project =
+            Project::new(silo, project_id, LookupType::ById(project_id));
+        let other_silo_id = Uuid::new_v4();
+        let other_project_id = Uuid::new_v4();
+        let other_project = Project::new(
+            Silo::new(FLEET, other_silo_id, LookupType::ById(other_silo_id)),
+            other_project_id,
+            LookupType::ById(other_project_id),
+        );
+
+        // With the default policy, a Silo viewer can read the Project (by way
+        // of its parent Silo), but not modify it.
+        let user_id = Uuid::new_v4();
+        let authz = authz_context_for_actor(
+            &logctx.log,
+            authn::Context::for_test_user(
+                user_id,
+                silo_id,
+                authn::SiloAuthnPolicy::default(),
+            ),
+            Arc::clone(&datastore),
+        );
+        authz
+            .authorize(&opctx, Action::Read, project.clone())
+            .await
+            .expect("Silo viewer should be able to read Project");
+        let error = authz
+            .authorize(&opctx, Action::Modify, project.clone())
+            .await
+            .expect_err("Silo viewer should not be able to modify Project");
+        assert!(matches!(error, Error::Forbidden), "{:?}", error);
+
+        // With a policy that says Silo viewers are Project admins, the same
+        // actor can modify the Project.  The policy has no bearing on
+        // Projects in other Silos.
+        let policy = authn::SiloAuthnPolicy::default()
+            .with_mapped_project_roles(BTreeMap::from([(
+                SiloRole::Viewer,
+                BTreeSet::from([ProjectRole::Admin]),
+            )]));
+        let authz = authz_context_for_actor(
+            &logctx.log,
+            authn::Context::for_test_user(user_id, silo_id, policy),
+            Arc::clone(&datastore),
+        );
+        authz
+            .authorize(&opctx, Action::Modify, project)
+            .await
+            .expect("Silo viewer should be able to modify Project");
+        let error = authz
+            .authorize(&opctx, Action::Modify, other_project)
+            .await
+            .expect_err("Silo policy should not confer roles in other Silos");
+        assert!(matches!(error, Error::ObjectNotFound { .. }), "{:?}", error);
+
+        logctx.cleanup_successful();
+    }
+
+    #[tokio::test]
+    async fn test_synthetic_resources_masked_on_unauthorized() {
+        use crate::authz::BLUEPRINT_CONFIG;
+        use crate::authz::INVENTORY;
+        use nexus_db_fixed_data::FLEET_ID;
+
+        let logctx = dev::test_setup_log(
+            "test_synthetic_resources_masked_on_unauthorized",
+        );
+
+        // An actor with no roles at all can't even read the inventory or
+        // blueprints, so they don't get to find out anything about them, not
+        // even that they exist.
+        let datastore: Arc<dyn crate::storage::Storage> =
+            Arc::new(OneRoleStorage { role: None });
+        let opctx = OpContext::for_background(
+            logctx.log.new(o!()),
+            Arc::new(Authz::new(&logctx.log)),
+            authn::Context::internal_db_init(),
+            Arc::clone(&datastore),
+        );
+        let authz = authz_context_for_actor(
+            &logctx.log,
+            authn::Context::unprivileged_test_user(),
+            Arc::clone(&datastore),
+        );
+        for action in [Action::Read, Action::Modify] {
+            let error = authz
+                .authorize(&opctx, action, INVENTORY)
+                .await
+                .expect_err("unprivileged user cannot access inventory");
+            assert!(matches!(error, Error::NotFound { .. }), "{:?}", error);
+            let error = authz
+                .authorize(&opctx, action, BLUEPRINT_CONFIG)
+                .await
+                .expect_err("unprivileged user cannot access blueprints");
+            assert!(matches!(error, Error::NotFound { .. }), "{:?}", error);
+        }
+
+        // A Fleet viewer can see that these exist, so they get an ordinary 403
+        // when they try to change them.
+        let datastore: Arc<dyn crate::storage::Storage> =
+            Arc::new(OneRoleStorage {
+                role: Some((ResourceType::Fleet, *FLEET_ID, "viewer")),
+            });
+        let opctx = OpContext::for_background(
+            logctx.log.new(o!()),
+            Arc::new(Authz::new(&logctx.log)),
+            authn::Context::internal_db_init(),
+            Arc::clone(&datastore),
+        );
+        let authz = authz_context_for_actor(
+            &logctx.log,
+            authn::Context::unprivileged_test_user(),
+            Arc::clone(&datastore),
+        );
+        authz
+            .authorize(&opctx, Action::Read, INVENTORY)
+            .await
+            .expect("Fleet viewer can read inventory");
+        let error = authz
+            .authorize(&opctx, Action::Modify, INVENTORY)
+            .await
+            .expect_err("Fleet viewer cannot modify inventory");
+        assert!(matches!(error, Error::Forbidden), "{:?}", error);
+        let error = authz
+            .authorize(&opctx, Action::Modify, BLUEPRINT_CONFIG)
+            .await
+            .expect_err("Fleet viewer cannot modify blueprints");
+        assert!(matches!(error, Error::Forbidden), "{:?}", error);
+
+        logctx.cleanup_successful();
+    }
 }
@@ -107,7 +107,9 @@ where
         .await?;
 
         // If roles can be conferred by another resource, load that resource's
-        // roles, too.
+        // roles, too.  This is only one hop: we load the roles directly
+        // attached to that resource, not those of its parents (nor any roles
+        // that might in turn be conferred on it).
         if let Some((resource_type, resource_id)) =
             with_roles.conferred_roles_by(authn)?
         {
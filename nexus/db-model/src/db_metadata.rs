@@ -18,6 +18,7 @@ pub struct DbMetadata {
     time_modified: DateTime<Utc>,
     version: SemverVersion,
     target_version: Option<SemverVersion>,
+    version_checksum: Option<String>,
 }
 
 impl DbMetadata {
@@ -32,4 +33,8 @@ impl DbMetadata {
     pub fn version(&self) -> &SemverVersion {
         &self.version
     }
+
+    pub fn version_checksum(&self) -> Option<&str> {
+        self.version_checksum.as_deref()
+    }
 }
@@ -681,6 +681,8 @@ table! {
         mapped_fleet_roles -> Jsonb,
 
         rcgen -> Int8,
+
+        mapped_project_roles -> Jsonb,
     }
 }
 
@@ -1793,6 +1795,7 @@ table! {
         time_modified -> Timestamptz,
         version -> Text,
         target_version -> Nullable<Text>,
+        version_checksum -> Nullable<Text>,
     }
 }
 
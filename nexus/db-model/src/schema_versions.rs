@@ -6,10 +6,12 @@
 //!
 //! For details, see schema/crdb/README.adoc in the root of this repository.
 
-use anyhow::{bail, ensure, Context};
+use anyhow::{anyhow, bail, ensure, Context};
 use camino::Utf8Path;
+use camino::Utf8PathBuf;
 use omicron_common::api::external::SemverVersion;
 use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 
 /// The version of the database schema this particular version of Nexus was
@@ -17,7 +19,7 @@ use std::collections::BTreeMap;
 ///
 /// This must be updated when you change the database schema.  Refer to
 /// schema/crdb/README.adoc in the root of this repository for details.
-pub const SCHEMA_VERSION: SemverVersion = SemverVersion::new(93, 0, 0);
+pub const SCHEMA_VERSION: SemverVersion = SemverVersion::new(95, 0, 0);
 
 /// List of all past database schema versions, in *reverse* order
 ///
@@ -29,6 +31,8 @@ static KNOWN_VERSIONS: Lazy<Vec<KnownVersion>> = Lazy::new(|| {
         // |  leaving the first copy as an example for the next person.
         // v
         // KnownVersion::new(next_int, "unique-dirname-with-the-sql-files"),
+        KnownVersion::new(95, "silo-mapped-project-roles"),
+        KnownVersion::new(94, "db-metadata-version-checksum"),
         KnownVersion::new(93, "dataset-kinds-zone-and-debug"),
         KnownVersion::new(92, "lldp-link-config-nullable"),
         KnownVersion::new(91, "add-management-gateway-producer-kind"),
@@ -201,7 +205,24 @@ impl AllSchemaVersions {
     pub fn load(
         schema_directory: &Utf8Path,
     ) -> Result<AllSchemaVersions, anyhow::Error> {
-        Self::load_known_versions(schema_directory, KNOWN_VERSIONS.iter())
+        Self::load_from_source(&FilesystemSchemaSource, schema_directory)
+    }
+
+    /// Load the set of all known schema versions from the given
+    /// [`SchemaSource`]
+    ///
+    /// This is like [`AllSchemaVersions::load()`], except that the version
+    /// tree rooted at `schema_directory` is read through `source` rather than
+    /// necessarily from the local filesystem.
+    pub fn load_from_source(
+        source: &dyn SchemaSource,
+        schema_directory: &Utf8Path,
+    ) -> Result<AllSchemaVersions, anyhow::Error> {
+        Self::load_known_versions(
+            source,
+            schema_directory,
+            KNOWN_VERSIONS.iter(),
+        )
     }
 
     /// Load a specific set of known schema versions using the legacy
@@ -220,10 +241,15 @@ impl AllSchemaVersions {
             })
             .collect();
 
-        Self::load_known_versions(schema_directory, known_versions.iter())
+        Self::load_known_versions(
+            &FilesystemSchemaSource,
+            schema_directory,
+            known_versions.iter(),
+        )
     }
 
     fn load_known_versions<'a>(
+        source: &dyn SchemaSource,
         schema_directory: &Utf8Path,
         known_versions: impl Iterator<Item = &'a KnownVersion>,
     ) -> Result<AllSchemaVersions, anyhow::Error> {
@@ -231,8 +257,9 @@ impl AllSchemaVersions {
         for known_version in known_versions {
             let version_path =
                 schema_directory.join(&known_version.relative_path);
-            let schema_version = SchemaVersion::load_from_directory(
+            let schema_version = SchemaVersion::load_from_source(
                 known_version.semver.clone(),
+                source,
                 &version_path,
             )
             .with_context(|| {
@@ -298,20 +325,13 @@ impl SchemaVersion {
     ///
     /// Any violation of these two rules will result in an error. Collections of
     /// the second form (`up1.sql`, ...) will be sorted numerically.
-    fn load_from_directory(
+    fn load_from_source(
         semver: SemverVersion,
+        source: &dyn SchemaSource,
         directory: &Utf8Path,
     ) -> Result<SchemaVersion, anyhow::Error> {
         let mut up_sqls = vec![];
-        let entries = directory
-            .read_dir_utf8()
-            .with_context(|| format!("Failed to readdir {directory}"))?;
-        for entry in entries {
-            let entry = entry.with_context(|| {
-                format!("Reading {directory:?}: invalid entry")
-            })?;
-            let pathbuf = entry.into_path();
-
+        for pathbuf in source.list_files(directory)? {
             // Ensure filename ends with ".sql"
             if pathbuf.extension() != Some("sql") {
                 continue;
@@ -385,14 +405,15 @@ impl SchemaVersion {
         // order.
         let mut steps = vec![];
         for (_, path) in up_sqls.into_iter() {
-            let sql = std::fs::read_to_string(&path)
-                .with_context(|| format!("Cannot read {path}"))?;
+            let sql = source.read_file(&path)?;
+            check_step_retry_safe(&path, &sql)?;
             // unwrap: `file_name()` is documented to return `None` only when
-            // the path is `..`.  But we got this path from reading the
-            // directory, and that process explicitly documents that it skips
-            // `..`.
+            // the path is `..`.  But we got this path from listing the
+            // directory, and both sources only produce paths that end in a
+            // file name.
             steps.push(SchemaUpgradeStep {
                 label: path.file_name().unwrap().to_string(),
+                path,
                 sql,
             });
         }
@@ -416,6 +437,19 @@ impl SchemaVersion {
     pub fn upgrade_steps(&self) -> impl Iterator<Item = &SchemaUpgradeStep> {
         self.upgrade_from_previous.iter()
     }
+
+    /// Returns a hex-encoded SHA-256 digest of the SQL for all of this
+    /// version's upgrade steps, in order
+    ///
+    /// This is recorded in the database when the version is applied so that
+    /// later edits to the SQL files can be detected.
+    pub fn checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        for step in self.upgrade_steps() {
+            hasher.update(step.sql().as_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
 }
 
 impl std::fmt::Display for SchemaVersion {
@@ -427,10 +461,200 @@ impl std::fmt::Display for SchemaVersion {
     }
 }
 
+/// Checks that a single `up*.sql` step can be safely retried
+///
+/// Each step is applied in its own transaction, which CockroachDB may retry.
+/// A step that mixes schema changes with DML that's not idempotent can behave
+/// surprisingly when that happens, so such statements must go into separate
+/// steps.  `INSERT ... ON CONFLICT` and `UPSERT` are idempotent and allowed.
+///
+/// This is a lint based on each statement's leading keyword, not a full SQL
+/// parser.
+fn check_step_retry_safe(
+    path: &Utf8Path,
+    sql: &str,
+) -> Result<(), anyhow::Error> {
+    let mut schema_change = None;
+    let mut dml = None;
+    for statement in sql_statements(sql) {
+        let statement = statement.to_uppercase();
+        let Some(keyword) = statement.split_whitespace().next() else {
+            continue;
+        };
+        match keyword {
+            "CREATE" | "ALTER" | "DROP" => {
+                schema_change.get_or_insert(keyword.to_string());
+            }
+            "INSERT" if !statement.contains("ON CONFLICT") => {
+                dml.get_or_insert(keyword.to_string());
+            }
+            "UPDATE" | "DELETE" => {
+                dml.get_or_insert(keyword.to_string());
+            }
+            _ => (),
+        }
+    }
+
+    if let (Some(schema_change), Some(dml)) = (schema_change, dml) {
+        bail!(
+            "{path}: step mixes schema changes ({schema_change}) with DML \
+             that may not be idempotent ({dml}); split these into separate \
+             `up*.sql` files"
+        );
+    }
+
+    Ok(())
+}
+
+/// Splits `sql` into its statements, with comments and the contents of string
+/// literals and quoted identifiers removed
+fn sql_statements(sql: &str) -> Vec<String> {
+    let mut statements = vec![];
+    let mut current = String::new();
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '-' if chars.peek() == Some(&'-') => {
+                // Line comment: skip to the end of the line.
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        current.push(c);
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                // Block comment: skip to the closing "*/".
+                chars.next();
+                let mut prev = None;
+                for c in chars.by_ref() {
+                    if prev == Some('*') && c == '/' {
+                        break;
+                    }
+                    prev = Some(c);
+                }
+                current.push(' ');
+            }
+            '\'' | '"' => {
+                // String literal or quoted identifier.  A doubled quote
+                // character is an escaped quote and does not end it.
+                current.push(c);
+                while let Some(next) = chars.next() {
+                    if next == c {
+                        if chars.peek() == Some(&c) {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                current.push(c);
+            }
+            ';' => statements.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    statements.push(current);
+    statements.retain(|statement| !statement.trim().is_empty());
+    statements
+}
+
+/// Provides access to the files making up a schema version tree
+///
+/// The tree is laid out as described in schema/crdb/README.adoc: one
+/// directory per version, each containing that version's `up*.sql` files.
+/// [`FilesystemSchemaSource`] reads the tree from disk, while
+/// [`InMemorySchemaSource`] serves it from memory so that Nexus can carry its
+/// schema with it (e.g., when it is embedded in the binary).
+pub trait SchemaSource {
+    /// Returns the paths of the files directly inside `directory`
+    fn list_files(
+        &self,
+        directory: &Utf8Path,
+    ) -> Result<Vec<Utf8PathBuf>, anyhow::Error>;
+
+    /// Returns the contents of the file at `path`
+    fn read_file(&self, path: &Utf8Path) -> Result<String, anyhow::Error>;
+}
+
+/// A [`SchemaSource`] that reads schema files from the local filesystem
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FilesystemSchemaSource;
+
+impl SchemaSource for FilesystemSchemaSource {
+    fn list_files(
+        &self,
+        directory: &Utf8Path,
+    ) -> Result<Vec<Utf8PathBuf>, anyhow::Error> {
+        let entries = directory
+            .read_dir_utf8()
+            .with_context(|| format!("Failed to readdir {directory}"))?;
+        entries
+            .map(|entry| {
+                entry.map(|entry| entry.into_path()).with_context(|| {
+                    format!("Reading {directory:?}: invalid entry")
+                })
+            })
+            .collect()
+    }
+
+    fn read_file(&self, path: &Utf8Path) -> Result<String, anyhow::Error> {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read {path}"))
+    }
+}
+
+/// A [`SchemaSource`] that serves schema files from memory
+///
+/// Files are keyed by their full path, so a tree loaded with a
+/// `schema_directory` of "" would contain entries like "1.0.0/up.sql".
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySchemaSource {
+    files: BTreeMap<Utf8PathBuf, String>,
+}
+
+impl InMemorySchemaSource {
+    /// Returns a source with no files in it
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds (or replaces) the file at `path`
+    pub fn insert(
+        &mut self,
+        path: impl Into<Utf8PathBuf>,
+        contents: impl Into<String>,
+    ) {
+        self.files.insert(path.into(), contents.into());
+    }
+}
+
+impl SchemaSource for InMemorySchemaSource {
+    fn list_files(
+        &self,
+        directory: &Utf8Path,
+    ) -> Result<Vec<Utf8PathBuf>, anyhow::Error> {
+        Ok(self
+            .files
+            .keys()
+            .filter(|path| path.parent() == Some(directory))
+            .cloned()
+            .collect())
+    }
+
+    fn read_file(&self, path: &Utf8Path) -> Result<String, anyhow::Error> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| anyhow!("Cannot read {path}: no such file"))
+    }
+}
+
 /// Describes a single file containing a schema change, as SQL.
 #[derive(Debug, Clone)]
 pub struct SchemaUpgradeStep {
     label: String,
+    path: Utf8PathBuf,
     sql: String,
 }
 
@@ -441,6 +665,11 @@ impl SchemaUpgradeStep {
         self.label.as_ref()
     }
 
+    /// Returns the full path of the file this step was loaded from
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
     /// Returns the actual SQL to execute for this step
     pub fn sql(&self) -> &str {
         self.sql.as_ref()
@@ -686,7 +915,7 @@ mod test {
         Ok(())
     }
 
-    // Confirm that `SchemaVersion::load_from_directory()` rejects `up*.sql`
+    // Confirm that `SchemaVersion::load_from_source()` rejects `up*.sql`
     // files where the `*` doesn't contain a positive integer.
     #[tokio::test]
     async fn test_reject_invalid_up_sql_names() {
@@ -711,8 +940,9 @@ mod test {
             let tempdir = Utf8TempDir::new().unwrap();
             let filename = tempdir.path().join(invalid_filename);
             _ = tokio::fs::File::create(&filename).await.unwrap();
-            let maybe_schema = SchemaVersion::load_from_directory(
+            let maybe_schema = SchemaVersion::load_from_source(
                 SemverVersion::new(12, 0, 0),
+                &FilesystemSchemaSource,
                 tempdir.path(),
             );
             match maybe_schema {
@@ -732,7 +962,7 @@ mod test {
         }
     }
 
-    // Confirm that `SchemaVersion::load_from_directory()` rejects a directory
+    // Confirm that `SchemaVersion::load_from_source()` rejects a directory
     // with no appropriately-named files.
     #[tokio::test]
     async fn test_reject_no_up_sql_files() {
@@ -749,8 +979,9 @@ mod test {
                     .unwrap();
             }
 
-            let maybe_schema = SchemaVersion::load_from_directory(
+            let maybe_schema = SchemaVersion::load_from_source(
                 SemverVersion::new(12, 0, 0),
+                &FilesystemSchemaSource,
                 tempdir.path(),
             );
             match maybe_schema {
@@ -770,7 +1001,7 @@ mod test {
         }
     }
 
-    // Confirm that `SchemaVersion::load_from_directory()` rejects collections
+    // Confirm that `SchemaVersion::load_from_source()` rejects collections
     // of `up*.sql` files with individually-valid names but that do not pass the
     // rules of the entire collection.
     #[tokio::test]
@@ -788,8 +1019,9 @@ mod test {
                     .unwrap();
             }
 
-            let maybe_schema = SchemaVersion::load_from_directory(
+            let maybe_schema = SchemaVersion::load_from_source(
                 SemverVersion::new(12, 0, 0),
+                &FilesystemSchemaSource,
                 tempdir.path(),
             );
             match maybe_schema {
@@ -811,7 +1043,7 @@ mod test {
         }
     }
 
-    // Confirm that `SchemaVersion::load_from_directory()` accepts legal
+    // Confirm that `SchemaVersion::load_from_source()` accepts legal
     // collections of `up*.sql` filenames.
     #[tokio::test]
     async fn test_allows_valid_up_sql_collections() {
@@ -832,8 +1064,9 @@ mod test {
                     .unwrap();
             }
 
-            let maybe_schema = SchemaVersion::load_from_directory(
+            let maybe_schema = SchemaVersion::load_from_source(
                 SemverVersion::new(12, 0, 0),
+                &FilesystemSchemaSource,
                 tempdir.path(),
             );
             match maybe_schema {
@@ -844,4 +1077,101 @@ mod test {
             }
         }
     }
+
+    // Confirm that an `InMemorySchemaSource` is subject to the same rules as
+    // the filesystem and produces steps in order.
+    #[test]
+    fn test_in_memory_schema_source() {
+        let mut source = InMemorySchemaSource::new();
+        source.insert("12.0.0/up02.sql", "SELECT 2;");
+        source.insert("12.0.0/up01.sql", "SELECT 1;");
+        source.insert("12.0.0/README.md", "not sql");
+        source.insert("13.0.0/up.sql", "SELECT 3;");
+
+        let schema = SchemaVersion::load_from_source(
+            SemverVersion::new(12, 0, 0),
+            &source,
+            Utf8Path::new("12.0.0"),
+        )
+        .expect("failed to load in-memory schema");
+        let steps: Vec<_> = schema
+            .upgrade_steps()
+            .map(|step| (step.label(), step.sql()))
+            .collect();
+        assert_eq!(
+            steps,
+            [("up01.sql", "SELECT 1;"), ("up02.sql", "SELECT 2;")]
+        );
+
+        source.insert("12.0.0/up04.sql", "SELECT 4;");
+        let error = SchemaVersion::load_from_source(
+            SemverVersion::new(12, 0, 0),
+            &source,
+            Utf8Path::new("12.0.0"),
+        )
+        .expect_err("loaded schema with a gap in `up*.sql` numbering");
+        assert_eq!(
+            format!("{error:#}"),
+            "invalid `up*.sql` sequence: 12.0.0/up02.sql, 12.0.0/up04.sql"
+        );
+
+        let error = SchemaVersion::load_from_source(
+            SemverVersion::new(14, 0, 0),
+            &source,
+            Utf8Path::new("14.0.0"),
+        )
+        .expect_err("loaded schema for missing version");
+        assert_eq!(format!("{error:#}"), "no `up*.sql` files found");
+    }
+
+    // Confirm that `SchemaVersion::load_from_source()` rejects steps that mix
+    // schema changes with DML that may not be idempotent.
+    #[test]
+    fn test_reject_retry_unsafe_steps() {
+        for sql in [
+            "ALTER TABLE t ADD COLUMN c INT; UPDATE t SET c = 1;",
+            "CREATE TABLE t (a INT);\nINSERT INTO t VALUES (1);",
+            "delete from t where a = 1; drop index t@a;",
+        ] {
+            let mut source = InMemorySchemaSource::new();
+            source.insert("12.0.0/up01.sql", sql);
+            let error = SchemaVersion::load_from_source(
+                SemverVersion::new(12, 0, 0),
+                &source,
+                Utf8Path::new("12.0.0"),
+            )
+            .expect_err("loaded retry-unsafe step");
+            let message = format!("{error:#}");
+            assert!(
+                message
+                    .starts_with("12.0.0/up01.sql: step mixes schema changes"),
+                "unexpected message for {sql:?}: {message:?}"
+            );
+        }
+
+        for sql in [
+            // schema changes and DML in separate steps are fine
+            "ALTER TABLE t ADD COLUMN c INT;",
+            "UPDATE t SET c = 1;",
+            // idempotent DML is fine
+            "CREATE TABLE t (a INT PRIMARY KEY);\n\
+             INSERT INTO t VALUES (1) ON CONFLICT DO NOTHING;",
+            "CREATE TABLE t (a INT PRIMARY KEY); UPSERT INTO t VALUES (1);",
+            // keywords in comments, strings, and identifiers don't count
+            "-- UPDATE t SET c = 1;\nALTER TABLE t ADD COLUMN c INT;",
+            "/* then; DELETE FROM t */ CREATE TABLE t (a INT);",
+            "CREATE TYPE e AS ENUM ('a; DELETE FROM t', 'it''s');",
+            "CREATE TABLE \"update\" (a INT);",
+        ] {
+            let mut source = InMemorySchemaSource::new();
+            source.insert("12.0.0/up.sql", sql);
+            if let Err(error) = SchemaVersion::load_from_source(
+                SemverVersion::new(12, 0, 0),
+                &source,
+                Utf8Path::new("12.0.0"),
+            ) {
+                panic!("unexpected failure on {sql:?}: {error:#}");
+            }
+        }
+    }
 }
@@ -8,7 +8,7 @@ use crate::schema::{image, project, silo};
 use crate::{impl_enum_type, DatabaseString, Image};
 use db_macros::Resource;
 use nexus_types::external_api::shared::{
-    FleetRole, SiloIdentityMode, SiloRole,
+    FleetRole, ProjectRole, SiloIdentityMode, SiloRole,
 };
 use nexus_types::external_api::views;
 use nexus_types::external_api::{params, shared};
@@ -106,6 +106,13 @@ pub struct Silo {
 
     /// child resource generation number, per RFD 192
     pub rcgen: Generation,
+
+    // The mapping of Silo roles to the Project roles they confer on every
+    // Project in the Silo.  This is stored the same way as
+    // `mapped_fleet_roles` (see above), but it's not part of the external API,
+    // so it always starts out empty.  Consumers use mapped_project_roles() to
+    // read it.
+    mapped_project_roles: serde_json::Value,
 }
 
 /// Form of mapped fleet roles used when serializing to the database
@@ -154,6 +161,33 @@ impl<'a> From<&'a BTreeMap<SiloRole, BTreeSet<FleetRole>>>
     }
 }
 
+/// Form of mapped project roles used when serializing to the database
+//
+// See `SerializedMappedFleetRoles` for why this exists.  The same warning
+// applies: be sure you've considered how to handle existing database records
+// before changing anything about it.
+struct SerializedMappedProjectRoles(BTreeMap<String, BTreeSet<String>>);
+impl<'a> From<&'a BTreeMap<SiloRole, BTreeSet<ProjectRole>>>
+    for SerializedMappedProjectRoles
+{
+    fn from(value: &'a BTreeMap<SiloRole, BTreeSet<ProjectRole>>) -> Self {
+        SerializedMappedProjectRoles(
+            value
+                .iter()
+                .map(|(silo_role, project_roles)| {
+                    let silo_role_str =
+                        silo_role.to_database_string().to_string();
+                    let project_roles_str = project_roles
+                        .iter()
+                        .map(|p| p.to_database_string().to_string())
+                        .collect();
+                    (silo_role_str, project_roles_str)
+                })
+                .collect(),
+        )
+    }
+}
+
 impl Silo {
     /// Creates a new database Silo object.
     pub fn new(params: params::SiloCreate) -> Result<Self, Error> {
@@ -173,6 +207,8 @@ impl Silo {
                 e
             ))
         })?;
+        let mapped_project_roles =
+            Self::serialize_mapped_project_roles(&BTreeMap::new())?;
         Ok(Self {
             identity: SiloIdentity::new(id, params.identity),
             discoverable: params.discoverable,
@@ -186,6 +222,7 @@ impl Silo {
                 .into(),
             rcgen: Generation::new(),
             mapped_fleet_roles,
+            mapped_project_roles,
         })
     }
 
@@ -199,6 +236,34 @@ impl Silo {
             ))
         })
     }
+
+    pub fn mapped_project_roles(
+        &self,
+    ) -> Result<BTreeMap<SiloRole, BTreeSet<ProjectRole>>, Error> {
+        serde_json::from_value(self.mapped_project_roles.clone()).map_err(|e| {
+            Error::internal_error(&format!(
+                "failed to deserialize mapped project roles from \
+                    database: {:#}",
+                e
+            ))
+        })
+    }
+
+    /// Returns the stored form of a mapping of Silo roles to the Project roles
+    /// that they confer
+    pub fn serialize_mapped_project_roles(
+        mapped_project_roles: &BTreeMap<SiloRole, BTreeSet<ProjectRole>>,
+    ) -> Result<serde_json::Value, Error> {
+        serde_json::to_value(
+            &SerializedMappedProjectRoles::from(mapped_project_roles).0,
+        )
+        .map_err(|e| {
+            Error::internal_error(&format!(
+                "failed to serialize mapped_project_roles: {:#}",
+                e
+            ))
+        })
+    }
 }
 
 impl TryFrom<Silo> for views::Silo {
@@ -12,16 +12,24 @@ use anyhow::{bail, ensure, Context};
 use async_bb8_diesel::{AsyncRunQueryDsl, AsyncSimpleConnection};
 use chrono::Utc;
 use diesel::prelude::*;
+use nexus_config::SchemaConfig;
 use nexus_db_model::AllSchemaVersions;
 use nexus_db_model::SchemaUpgradeStep;
 use nexus_db_model::SchemaVersion;
 use nexus_db_model::EARLIEST_SUPPORTED_VERSION;
 use omicron_common::api::external::Error;
+use omicron_common::api::external::InternalContext;
 use omicron_common::api::external::SemverVersion;
-use slog::{error, info, o, Logger};
+use slog::{debug, error, info, o, Logger};
 use std::ops::Bound;
 use std::str::FromStr;
 
+// The first schema version in which "db_metadata.version_checksum" exists.
+//
+// Before this version, there is nowhere to record the checksum of applied
+// migrations, so we neither write nor verify it.
+const VERSION_CHECKSUM_ADDED: SemverVersion = SemverVersion::new(94, 0, 0);
+
 // A SchemaVersion which uses a pre-release value to indicate
 // "incremental progress".
 //
@@ -102,6 +110,18 @@ fn skippable_version(
 }
 
 impl DataStore {
+    /// Returns every schema version that this build knows how to upgrade
+    /// through, in ascending order
+    ///
+    /// These are loaded from the schema directory described by `config`.
+    pub fn known_schema_versions(
+        config: &SchemaConfig,
+    ) -> Result<Vec<SemverVersion>, String> {
+        let all_versions = AllSchemaVersions::load(&config.schema_dir)
+            .map_err(|e| format!("{e:#}"))?;
+        Ok(all_versions.iter_versions().map(|v| v.semver().clone()).collect())
+    }
+
     // Ensures that the database schema matches "desired_version".
     //
     // - Updating the schema makes the database incompatible with older
@@ -143,6 +163,10 @@ impl DataStore {
         // modification.
         if found_version == desired_version {
             info!(log, "Database schema version is up to date");
+            if let Some(all_versions) = all_versions {
+                self.verify_schema_checksum(&log, &found_version, all_versions)
+                    .await;
+            }
             return Ok(());
         }
 
@@ -218,11 +242,16 @@ impl DataStore {
             // update), but the "target_version" will keep shifting on each
             // incremental step.
             let mut last_step_version = None;
+            let nsteps = target_version.upgrade_steps().count();
 
             for (i, step) in target_version.upgrade_steps().enumerate() {
                 let target_step =
                     StepSemverVersion::new(&target_version.semver(), i)?;
-                let log = log.new(o!("target_step.version" => target_step.version.to_string()));
+                let log = log.new(o!(
+                    "target_step.version" => target_step.version.to_string(),
+                    "target_step.index" => format!("{}/{}", i + 1, nsteps),
+                    "target_step.path" => step.path().to_string(),
+                ));
 
                 self.apply_step_version_update(
                     &log,
@@ -265,9 +294,13 @@ impl DataStore {
             // - db_metadata.target_version = NULL
             let last_step_version = last_step_version
                 .ok_or_else(|| anyhow::anyhow!("Missing final step version"))?;
-            self.finalize_schema_update(&current_version, &last_step_version)
-                .await
-                .context("Failed to finalize schema update")?;
+            self.finalize_schema_update(
+                &current_version,
+                &last_step_version,
+                &target_version.checksum(),
+            )
+            .await
+            .context("Failed to finalize schema update")?;
 
             info!(
                 log,
@@ -320,20 +353,28 @@ impl DataStore {
             "Marked schema upgrade as prepared";
         );
 
+        info!(
+            log,
+            "Applying subcomponent of schema upgrade";
+        );
+
         // Perform the schema change.
-        self.apply_schema_update(
-            &current_version,
-            &target_step.version,
-            step.sql(),
-        )
-        .await
-        .with_context(|| {
-            format!(
-                "update to {}, applying step {:?}",
-                target_step.version,
-                step.label()
-            )
-        })?;
+        self.apply_schema_update(&current_version, &target_step.version, step)
+            .await
+            .inspect_err(|e| {
+                error!(
+                    log,
+                    "Failed to apply subcomponent of schema upgrade";
+                    "error" => %e,
+                );
+            })
+            .with_context(|| {
+                format!(
+                    "update to {}, applying step {:?}",
+                    target_step.version,
+                    step.label()
+                )
+            })?;
 
         info!(
             log,
@@ -342,6 +383,76 @@ impl DataStore {
         Ok(())
     }
 
+    // Compares the checksum recorded for "version" against the SQL we have
+    // for that version, warning if they differ.
+    //
+    // A mismatch means that the migration files for an already-shipped
+    // version were modified after the database applied them. That's not
+    // fatal by itself, so this only logs.
+    async fn verify_schema_checksum(
+        &self,
+        log: &Logger,
+        version: &SemverVersion,
+        all_versions: &AllSchemaVersions,
+    ) {
+        if *version < VERSION_CHECKSUM_ADDED {
+            return;
+        }
+        let Some(schema_version) = all_versions
+            .versions_range((
+                Bound::Included(version),
+                Bound::Included(version),
+            ))
+            .next()
+        else {
+            return;
+        };
+
+        let recorded = match self.database_schema_checksum().await {
+            Ok(Some(recorded)) => recorded,
+            Ok(None) => {
+                debug!(
+                    log,
+                    "No checksum recorded for schema version \
+                    (database was not upgraded to it)";
+                );
+                return;
+            }
+            Err(e) => {
+                warn!(
+                    log,
+                    "Failed to read schema version checksum";
+                    "error" => %e,
+                );
+                return;
+            }
+        };
+
+        let expected = schema_version.checksum();
+        if recorded != expected {
+            warn!(
+                log,
+                "Schema version checksum does not match the SQL files for \
+                this version; were they modified after being applied?";
+                "recorded_checksum" => recorded,
+                "expected_checksum" => expected,
+            );
+        }
+    }
+
+    // Returns the checksum of the SQL applied to reach the current version,
+    // if one was recorded.
+    async fn database_schema_checksum(&self) -> Result<Option<String>, Error> {
+        use db::schema::db_metadata::dsl;
+
+        dsl::db_metadata
+            .filter(dsl::singleton.eq(true))
+            .select(dsl::version_checksum)
+            .get_result_async(&*self.pool_connection_unauthorized().await?)
+            .await
+            .map_err(|e| public_error_from_diesel(e, ErrorHandler::Server))
+    }
+
     pub async fn database_schema_version(
         &self,
     ) -> Result<(SemverVersion, Option<SemverVersion>), Error> {
@@ -414,6 +525,24 @@ impl DataStore {
         .map_err(|e| public_error_from_diesel(e, ErrorHandler::Server))?;
 
         if rows_updated != 1 {
+            // Try to explain why we couldn't claim this update. The most
+            // interesting case is another Nexus concurrently upgrading the
+            // same starting version towards a different target.
+            let (found_version, found_target_version) =
+                self.database_schema_version().await?;
+            if let Some(found_target_version) = found_target_version {
+                let mut found_target = found_target_version.clone();
+                found_target.0.pre = semver::Prerelease::EMPTY;
+                let target = target_step.without_prerelease();
+                if found_version == *from_version && found_target != target {
+                    return Err(Error::internal_error(&format!(
+                        "Failed to prepare schema for update to {target}: \
+                        another upgrade to {found_target} is in progress \
+                        (target_version is {found_target_version})"
+                    )));
+                }
+            }
+
             return Err(Error::internal_error(
                 "Failed to prepare schema for update",
             ));
@@ -423,12 +552,16 @@ impl DataStore {
 
     // Applies a schema update, using raw SQL read from a caller-supplied
     // configuration file.
+    //
+    // On failure, the returned error identifies the file that `step` was
+    // loaded from.
     async fn apply_schema_update(
         &self,
         current: &SemverVersion,
         target: &SemverVersion,
-        sql: &str,
+        step: &SchemaUpgradeStep,
     ) -> Result<(), Error> {
+        let sql = step.sql();
         let conn = self.pool_connection_unauthorized().await?;
 
         let result = self.transaction_retry_wrapper("apply_schema_update")
@@ -450,38 +583,55 @@ impl DataStore {
                 Ok(())
             }).await;
 
-        match result {
-            Ok(()) => Ok(()),
-            Err(e) => Err(public_error_from_diesel(e, ErrorHandler::Server)),
-        }
+        result
+            .map_err(|e| public_error_from_diesel(e, ErrorHandler::Server))
+            .with_internal_context(|| format!("applying {}", step.path()))
     }
 
     // Completes a schema migration, upgrading to the new version.
     //
     // - from_version: What we expect "version" must be to proceed
     // - last_step: What we expect "target_version" must be to proceed.
+    // - checksum: The checksum of the SQL applied for the new version, which
+    // is recorded if the new version has somewhere to store it.
     async fn finalize_schema_update(
         &self,
         from_version: &SemverVersion,
         last_step: &StepSemverVersion,
+        checksum: &str,
     ) -> Result<(), Error> {
         use db::schema::db_metadata::dsl;
 
         let to_version = last_step.without_prerelease();
-        let rows_updated = diesel::update(
+        let query = diesel::update(
             dsl::db_metadata
                 .filter(dsl::singleton.eq(true))
                 .filter(dsl::version.eq(from_version.to_string()))
                 .filter(dsl::target_version.eq(last_step.version.to_string())),
-        )
-        .set((
-            dsl::time_modified.eq(Utc::now()),
-            dsl::version.eq(to_version.to_string()),
-            dsl::target_version.eq(None as Option<String>),
-        ))
-        .execute_async(&*self.pool_connection_unauthorized().await?)
-        .await
-        .map_err(|e| public_error_from_diesel(e, ErrorHandler::Server))?;
+        );
+        let conn = self.pool_connection_unauthorized().await?;
+        let result = if to_version < VERSION_CHECKSUM_ADDED {
+            query
+                .set((
+                    dsl::time_modified.eq(Utc::now()),
+                    dsl::version.eq(to_version.to_string()),
+                    dsl::target_version.eq(None as Option<String>),
+                ))
+                .execute_async(&*conn)
+                .await
+        } else {
+            query
+                .set((
+                    dsl::time_modified.eq(Utc::now()),
+                    dsl::version.eq(to_version.to_string()),
+                    dsl::target_version.eq(None as Option<String>),
+                    dsl::version_checksum.eq(Some(checksum.to_string())),
+                ))
+                .execute_async(&*conn)
+                .await
+        };
+        let rows_updated = result
+            .map_err(|e| public_error_from_diesel(e, ErrorHandler::Server))?;
 
         if rows_updated != 1 {
             return Err(Error::internal_error(&format!(
@@ -524,6 +674,84 @@ mod test {
         logctx.cleanup_successful();
     }
 
+    #[test]
+    fn known_schema_versions_are_sorted() {
+        let config = SchemaConfig {
+            schema_dir: concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../../schema/crdb"
+            )
+            .into(),
+        };
+        let versions = DataStore::known_schema_versions(&config)
+            .expect("failed to load known schema versions");
+        assert_eq!(versions.first(), Some(&EARLIEST_SUPPORTED_VERSION));
+        assert_eq!(versions.last(), Some(&SCHEMA_VERSION));
+        assert!(versions.windows(2).all(|w| w[0] < w[1]));
+
+        // An empty schema directory is missing every known version.
+        let config_dir = Utf8TempDir::new().unwrap();
+        let config = SchemaConfig { schema_dir: config_dir.path().to_owned() };
+        let error = DataStore::known_schema_versions(&config)
+            .expect_err("loaded versions from an empty directory");
+        assert!(error.contains("loading schema version"), "{error}");
+    }
+
+    // Confirms that if another Nexus has claimed an upgrade to a different
+    // version, preparing our own upgrade reports that specifically.
+    #[tokio::test]
+    async fn prepare_schema_update_reports_conflicting_target() {
+        let logctx = dev::test_setup_log(
+            "prepare_schema_update_reports_conflicting_target",
+        );
+        let log = &logctx.log;
+        let mut crdb = test_db::test_setup_database(&logctx.log).await;
+
+        let cfg = db::Config { url: crdb.pg_config().clone() };
+        let pool = Arc::new(db::Pool::new_single_host(&logctx.log, &cfg));
+        let datastore =
+            DataStore::new_unchecked(log.clone(), pool.clone()).unwrap();
+        let conn = datastore.pool_connection_for_tests().await.unwrap();
+
+        // Pretend that some other Nexus is partway through upgrading "v0" to
+        // "v2".
+        let v0 = SemverVersion::new(0, 0, 0);
+        let v1 = SemverVersion::new(0, 0, 1);
+        let v2 = SemverVersion::new(0, 0, 2);
+        let v2_step = StepSemverVersion::new(&v2, 0).unwrap();
+        use db::schema::db_metadata::dsl;
+        diesel::update(dsl::db_metadata.filter(dsl::singleton.eq(true)))
+            .set((
+                dsl::version.eq(v0.to_string()),
+                dsl::target_version.eq(Some(v2_step.version.to_string())),
+            ))
+            .execute_async(&*conn)
+            .await
+            .expect("Failed to set db_metadata");
+
+        // Preparing an upgrade from "v0" to "v1" should fail, and tell us
+        // about the upgrade to "v2".
+        let v1_step = StepSemverVersion::new(&v1, 0).unwrap();
+        let error = datastore
+            .prepare_schema_update(&v0, &v1_step)
+            .await
+            .expect_err("prepared update despite conflicting target");
+        let message = error.to_string();
+        assert!(
+            message.contains("another upgrade to 0.0.2 is in progress"),
+            "unexpected error: {message}"
+        );
+
+        // Preparing the same upgrade the other Nexus claimed is fine.
+        datastore
+            .prepare_schema_update(&v0, &v2_step)
+            .await
+            .expect("Failed to prepare update to the in-progress target");
+
+        crdb.cleanup().await.unwrap();
+        logctx.cleanup_successful();
+    }
+
     // Helper to create the version directory and "up.sql".
     async fn add_upgrade<S: AsRef<str>>(
         config_dir_path: &Utf8Path,
@@ -773,7 +1001,18 @@ mod test {
             .await
             .expect("Failed to get DB version");
         assert_eq!(version, SCHEMA_VERSION.to_string());
-        // 2. We only applied each incremental step once
+        // 2. The checksum of the SQL we applied was recorded
+        let checksum = datastore
+            .database_schema_checksum()
+            .await
+            .expect("Failed to get DB version checksum");
+        let expected = all_versions
+            .iter_versions()
+            .last()
+            .expect("at least one version")
+            .checksum();
+        assert_eq!(checksum, Some(expected));
+        // 3. We only applied each incremental step once
         let data = sql::<Text>("SELECT data FROM t WHERE id = 1")
             .get_result_async::<String>(&*conn)
             .await
@@ -33,6 +33,8 @@ use omicron_common::api::external::Error;
 use omicron_common::api::external::ListResultVec;
 use omicron_common::api::external::LookupResult;
 use omicron_common::api::external::UpdateResult;
+use omicron_uuid_kinds::CollectionUuid;
+use omicron_uuid_kinds::GenericUuid;
 use slog::Logger;
 use std::net::SocketAddrV6;
 use uuid::Uuid;
@@ -448,6 +450,62 @@ impl DataStore {
             .map_err(|e| public_error_from_diesel(e, ErrorHandler::Server))
     }
 
+    /// Find regions on zpools that are missing from an inventory collection.
+    ///
+    /// Only zpools on sleds that reported to the given collection are
+    /// considered: if a sled agent responded to inventory but no longer
+    /// reports one of its in-service zpools, that zpool has likely failed even
+    /// though its physical disk has not (yet) been expunged.  Regions on
+    /// expunged physical disks are found by
+    /// `find_regions_on_expunged_physical_disks` instead.
+    pub async fn find_regions_on_zpools_missing_from_inventory(
+        &self,
+        opctx: &OpContext,
+        collection_id: CollectionUuid,
+    ) -> LookupResult<Vec<Region>> {
+        let conn = self.pool_connection_authorized(opctx).await?;
+
+        use db::schema::dataset::dsl as dataset_dsl;
+        use db::schema::inv_sled_agent::dsl as inv_sled_agent_dsl;
+        use db::schema::inv_zpool::dsl as inv_zpool_dsl;
+        use db::schema::physical_disk::dsl as physical_disk_dsl;
+        use db::schema::region::dsl as region_dsl;
+        use db::schema::zpool::dsl as zpool_dsl;
+
+        let collection_id = collection_id.into_untyped_uuid();
+
+        region_dsl::region
+            .filter(region_dsl::dataset_id.eq_any(
+                dataset_dsl::dataset
+                    .filter(dataset_dsl::time_deleted.is_null())
+                    .filter(dataset_dsl::pool_id.eq_any(
+                        zpool_dsl::zpool
+                            .filter(zpool_dsl::time_deleted.is_null())
+                            .filter(zpool_dsl::physical_disk_id.eq_any(
+                                physical_disk_dsl::physical_disk
+                                    .filter(physical_disk_dsl::disk_policy.eq(PhysicalDiskPolicy::InService))
+                                    .select(physical_disk_dsl::id)
+                            ))
+                            .filter(zpool_dsl::sled_id.eq_any(
+                                inv_sled_agent_dsl::inv_sled_agent
+                                    .filter(inv_sled_agent_dsl::inv_collection_id.eq(collection_id))
+                                    .select(inv_sled_agent_dsl::sled_id)
+                            ))
+                            .filter(diesel::dsl::not(zpool_dsl::id.eq_any(
+                                inv_zpool_dsl::inv_zpool
+                                    .filter(inv_zpool_dsl::inv_collection_id.eq(collection_id))
+                                    .select(inv_zpool_dsl::id)
+                            )))
+                            .select(zpool_dsl::id)
+                    ))
+                    .select(dataset_dsl::id)
+            ))
+            .select(Region::as_select())
+            .load_async(&*conn)
+            .await
+            .map_err(|e| public_error_from_diesel(e, ErrorHandler::Server))
+    }
+
     pub async fn region_set_port(
         &self,
         region_id: Uuid,
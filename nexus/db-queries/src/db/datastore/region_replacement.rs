@@ -120,6 +120,29 @@ impl DataStore {
         Ok(replacements)
     }
 
+    /// Return the number of region replacement requests that have not yet
+    /// reached state `Complete`.
+    pub async fn count_in_flight_region_replacements(
+        &self,
+        opctx: &OpContext,
+    ) -> Result<usize, Error> {
+        use db::schema::region_replacement::dsl;
+
+        let conn = self.pool_connection_authorized(opctx).await?;
+        let count = dsl::region_replacement
+            .filter(dsl::replacement_state.ne(RegionReplacementState::Complete))
+            .select(diesel::dsl::count_star())
+            .first_async::<i64>(&*conn)
+            .await
+            .map_err(|e| public_error_from_diesel(e, ErrorHandler::Server))?;
+
+        usize::try_from(count).map_err(|e| {
+            Error::internal_error(&format!(
+                "failed to convert region replacement count to usize: {e}"
+            ))
+        })
+    }
+
     /// Return region replacement requests that are in state `Running` with no
     /// currently operating saga. These need to be checked on or driven forward.
     pub async fn get_running_region_replacements(
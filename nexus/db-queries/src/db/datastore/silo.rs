@@ -34,6 +34,7 @@ use nexus_db_model::ServiceKind;
 use nexus_db_model::SiloQuotas;
 use nexus_types::external_api::params;
 use nexus_types::external_api::shared;
+use nexus_types::external_api::shared::ProjectRole;
 use nexus_types::external_api::shared::SiloRole;
 use omicron_common::api::external::http_pagination::PaginatedBy;
 use omicron_common::api::external::CreateResult;
@@ -43,7 +44,10 @@ use omicron_common::api::external::Error;
 use omicron_common::api::external::ListResultVec;
 use omicron_common::api::external::LookupType;
 use omicron_common::api::external::ResourceType;
+use omicron_common::api::external::UpdateResult;
 use ref_cast::RefCast;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use uuid::Uuid;
 
 /// Filter a "silo_list" query based on silos' discoverability
@@ -382,6 +386,37 @@ impl DataStore {
         Ok(all_silos)
     }
 
+    /// Updates which Project roles are conferred (on every Project in the
+    /// Silo) by which Silo roles
+    pub async fn silo_update_mapped_project_roles(
+        &self,
+        opctx: &OpContext,
+        authz_silo: &authz::Silo,
+        mapped_project_roles: &BTreeMap<SiloRole, BTreeSet<ProjectRole>>,
+    ) -> UpdateResult<Silo> {
+        opctx.authorize(authz::Action::ModifyPolicy, authz_silo).await?;
+
+        use db::schema::silo::dsl;
+        let mapped_project_roles =
+            Silo::serialize_mapped_project_roles(mapped_project_roles)?;
+        diesel::update(dsl::silo)
+            .filter(dsl::time_deleted.is_null())
+            .filter(dsl::id.eq(authz_silo.id()))
+            .set((
+                dsl::mapped_project_roles.eq(mapped_project_roles),
+                dsl::time_modified.eq(Utc::now()),
+            ))
+            .returning(Silo::as_returning())
+            .get_result_async(&*self.pool_connection_authorized(opctx).await?)
+            .await
+            .map_err(|e| {
+                public_error_from_diesel(
+                    e,
+                    ErrorHandler::NotFoundByResource(authz_silo),
+                )
+            })
+    }
+
     pub async fn silo_delete(
         &self,
         opctx: &OpContext,
@@ -600,3 +635,56 @@ impl DataStore {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::authn;
+    use crate::db::datastore::test_utils::datastore_test;
+    use crate::db::lookup::LookupPath;
+    use nexus_test_utils::db::test_setup_database;
+    use nexus_types::external_api::shared::ProjectRole;
+    use nexus_types::external_api::shared::SiloRole;
+    use omicron_test_utils::dev;
+    use std::collections::BTreeMap;
+    use std::collections::BTreeSet;
+
+    #[tokio::test]
+    async fn test_silo_mapped_project_roles() {
+        let logctx = dev::test_setup_log("test_silo_mapped_project_roles");
+        let mut db = test_setup_database(&logctx.log).await;
+        let (opctx, datastore) = datastore_test(&logctx, &db).await;
+
+        let authz_silo = opctx.authn.silo_required().unwrap();
+        let (.., silo) = LookupPath::new(&opctx, &datastore)
+            .silo_id(authz_silo.id())
+            .fetch()
+            .await
+            .unwrap();
+        assert!(silo.mapped_project_roles().unwrap().is_empty());
+
+        // Once the mapping is stored, it should show up in the authn policy
+        // that's built from the Silo.
+        let mapped_project_roles = BTreeMap::from([(
+            SiloRole::Viewer,
+            BTreeSet::from([ProjectRole::Collaborator]),
+        )]);
+        datastore
+            .silo_update_mapped_project_roles(
+                &opctx,
+                &authz_silo,
+                &mapped_project_roles,
+            )
+            .await
+            .unwrap();
+        let (.., silo) = LookupPath::new(&opctx, &datastore)
+            .silo_id(authz_silo.id())
+            .fetch()
+            .await
+            .unwrap();
+        let policy = authn::SiloAuthnPolicy::try_from(&silo).unwrap();
+        assert_eq!(*policy.mapped_project_roles(), mapped_project_roles);
+
+        db.cleanup().await.unwrap();
+        logctx.cleanup_successful();
+    }
+}
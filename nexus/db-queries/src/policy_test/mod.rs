@@ -258,7 +258,8 @@ async fn authorize_one_resource(
                 let summary = match result {
                     Ok(_) => '\u{2714}',
                     Err(Error::Forbidden)
-                    | Err(Error::ObjectNotFound { .. }) => '\u{2718}',
+                    | Err(Error::ObjectNotFound { .. })
+                    | Err(Error::NotFound { .. }) => '\u{2718}',
                     Err(Error::Unauthenticated { .. }) => '!',
                     Err(_) => '\u{26a0}',
                 };
@@ -574,8 +574,12 @@ mod test {
     use super::now_db_precision;
     use super::CollectionBuilder;
     use crate::examples::representative;
+    use crate::examples::representative_with;
+    use crate::examples::representative_with_errors;
     use crate::examples::sp_state;
+    use crate::examples::with_omicron_zones;
     use crate::examples::Representative;
+    use crate::examples::RepresentativeParams;
     use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
     use base64::Engine;
     use gateway_client::types::PowerState;
@@ -584,6 +588,7 @@ mod test {
     use gateway_client::types::SpComponentCaboose;
     use gateway_client::types::SpState;
     use gateway_client::types::SpType;
+    use nexus_sled_agent_shared::inventory::OmicronZonesConfig;
     use nexus_sled_agent_shared::inventory::SledRole;
     use nexus_types::inventory::BaseboardId;
     use nexus_types::inventory::Caboose;
@@ -591,6 +596,11 @@ mod test {
     use nexus_types::inventory::RotPage;
     use nexus_types::inventory::RotPageWhich;
     use omicron_common::api::external::ByteCount;
+    use omicron_common::api::external::Generation;
+    use omicron_uuid_kinds::SledUuid;
+    use std::collections::BTreeMap;
+    use std::collections::BTreeSet;
+    use strum::IntoEnumIterator;
 
     // Verify the contents of an empty collection.
     #[test]
@@ -634,12 +644,16 @@ mod test {
         let time_before = now_db_precision();
         let Representative {
             builder,
-            sleds: [sled1_bb, sled2_bb, sled3_bb, sled4_bb],
-            switch,
-            psc,
+            sleds,
+            switches,
+            pscs,
             sled_agents:
                 [sled_agent_id_basic, sled_agent_id_extra, sled_agent_id_pc, sled_agent_id_unknown],
         } = representative();
+        let [sled1_bb, sled2_bb, sled3_bb, sled4_bb] =
+            <[_; 4]>::try_from(sleds).unwrap();
+        let [switch] = <[_; 1]>::try_from(switches).unwrap();
+        let [psc] = <[_; 1]>::try_from(pscs).unwrap();
         let collection = builder.build();
         let time_after = now_db_precision();
         println!("{:#?}", collection);
@@ -977,6 +991,109 @@ mod test {
             .is_none());
     }
 
+    // Exercises `representative_with()` with counts other than the defaults.
+    #[test]
+    fn test_representative_with() {
+        let Representative { builder, sleds, switches, pscs, .. } =
+            representative_with(RepresentativeParams {
+                num_sleds: 32,
+                num_switches: 0,
+                num_pscs: 2,
+            });
+        let collection = builder.build();
+
+        // One sled beyond those requested is reported only by its sled agent.
+        assert_eq!(sleds.len(), 33);
+        assert!(switches.is_empty());
+        assert_eq!(pscs.len(), 2);
+        assert_eq!(
+            collection.sps.len(),
+            sleds.len() - 1 + switches.len() + pscs.len()
+        );
+        assert_eq!(collection.baseboards.len(), sleds.len() + pscs.len());
+        for bb in sleds.iter().chain(pscs.iter()) {
+            assert!(collection.baseboards.contains(bb));
+        }
+        assert!(collection.sps.values().all(|sp| sp.sp_type != SpType::Switch));
+
+        // Each generated baseboard has its own cabooses.
+        let generated = sleds[3..32].iter().chain(pscs[1..].iter());
+        let mut generated_cabooses = BTreeSet::new();
+        for bb in generated {
+            for which in CabooseWhich::iter() {
+                let c = collection.caboose_for(which, bb).unwrap();
+                assert_eq!(c.source, "test suite");
+                generated_cabooses.insert(c.caboose.clone());
+            }
+        }
+        assert_eq!(generated_cabooses.len(), 29 + 1);
+    }
+
+    // Verifies that `representative_with_errors()` reports the errors it says
+    // it does.
+    #[test]
+    fn test_representative_with_errors() {
+        let (Representative { builder, .. }, nerrors) =
+            representative_with_errors();
+        let collection = builder.build();
+        assert_eq!(collection.errors.len(), nerrors);
+
+        let messages: Vec<_> =
+            collection.errors.iter().map(|e| e.to_string()).collect();
+        assert!(messages[0].contains("reading RoT state"));
+        assert!(messages[1].contains("reported unknown baseboard"));
+        assert!(messages[2].contains("fetching state of SP"));
+        assert!(messages[3].contains("caboose SpSlot0"));
+        assert!(messages[4].starts_with("Sled Agent"));
+        for message in &messages[2..] {
+            assert!(message.contains("test suite injected error"), "{message}");
+        }
+    }
+
+    // Exercises `with_omicron_zones()`.
+    #[test]
+    fn test_with_omicron_zones() {
+        let mut builder = CollectionBuilder::new("test_with_omicron_zones");
+        let sled1 = SledUuid::new_v4();
+        let sled2 = SledUuid::new_v4();
+        let zones = BTreeMap::from([
+            (
+                sled1,
+                OmicronZonesConfig {
+                    generation: Generation::new(),
+                    zones: vec![],
+                },
+            ),
+            (
+                sled2,
+                OmicronZonesConfig {
+                    generation: Generation::new().next(),
+                    zones: vec![],
+                },
+            ),
+        ]);
+        with_omicron_zones(&mut builder, zones.clone()).unwrap();
+
+        // Reporting the same sleds again is an error.
+        let error = with_omicron_zones(&mut builder, zones).unwrap_err();
+        assert!(error.to_string().contains("reported previously"));
+
+        let collection = builder.build();
+        assert!(collection.errors.is_empty());
+        assert_eq!(collection.omicron_zones.len(), 2);
+        assert_eq!(
+            collection.omicron_zones[&sled1].zones.generation,
+            Generation::new()
+        );
+        assert_eq!(
+            collection.omicron_zones[&sled2].zones.generation,
+            Generation::new().next()
+        );
+        for found in collection.omicron_zones.values() {
+            assert_eq!(found.source, "fake sled agent");
+        }
+    }
+
     // Exercises all the failure cases that shouldn't happen in real systems.
     // Despite all of these failures, we should get a valid collection at the
     // end.
@@ -5,6 +5,8 @@
 //! Example collections used for testing
 
 use crate::CollectionBuilder;
+use crate::InventoryError;
+use anyhow::anyhow;
 use gateway_client::types::PowerState;
 use gateway_client::types::RotSlot;
 use gateway_client::types::RotState;
@@ -25,6 +27,7 @@ use omicron_common::api::external::ByteCount;
 use omicron_common::disk::DiskVariant;
 use omicron_uuid_kinds::GenericUuid;
 use omicron_uuid_kinds::SledUuid;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use strum::IntoEnumIterator;
 
@@ -40,67 +43,132 @@ use strum::IntoEnumIterator;
 /// - some missing cabooses
 /// - some cabooses common to multiple baseboards; others not
 /// - serial number reused across different model numbers
+///
+/// This is [`representative_with()`] using the default
+/// [`RepresentativeParams`].
 pub fn representative() -> Representative {
+    representative_with(RepresentativeParams::default())
+}
+
+/// Parameters for [`representative_with()`]
+#[derive(Clone, Copy, Debug)]
+pub struct RepresentativeParams {
+    /// number of sleds found via MGS
+    ///
+    /// The collection always also includes one more sled that's reported only
+    /// by its sled agent.
+    pub num_sleds: usize,
+    /// number of switches found via MGS
+    pub num_switches: usize,
+    /// number of power shelf controllers found via MGS
+    pub num_pscs: usize,
+}
+
+impl Default for RepresentativeParams {
+    /// Returns the parameters used by [`representative()`]
+    fn default() -> Self {
+        RepresentativeParams { num_sleds: 3, num_switches: 1, num_pscs: 1 }
+    }
+}
+
+/// Returns an example Collection used for testing, with the requested number
+/// of each kind of baseboard
+///
+/// The first few baseboards of each kind are the hand-picked ones described
+/// in [`representative()`].  Any baseboards beyond those are generated, each
+/// with its own serial number, model number, and cabooses.
+pub fn representative_with(params: RepresentativeParams) -> Representative {
     let mut builder = CollectionBuilder::new("example");
 
-    // an ordinary, working sled
-    let sled1_bb = builder
-        .found_sp_state(
-            "fake MGS 1",
-            SpType::Sled,
-            3,
-            SpState {
-                base_mac_address: [0; 6],
-                hubris_archive_id: String::from("hubris1"),
-                model: String::from("model1"),
-                power_state: PowerState::A0,
-                revision: 0,
-                rot: RotState::V2 {
-                    active: RotSlot::A,
-                    pending_persistent_boot_preference: None,
-                    persistent_boot_preference: RotSlot::A,
-                    slot_a_sha3_256_digest: Some(String::from("slotAdigest1")),
-                    slot_b_sha3_256_digest: Some(String::from("slotBdigest1")),
-                    transient_boot_preference: None,
+    let mut sleds = Vec::with_capacity(params.num_sleds + 1);
+    for i in 0..params.num_sleds {
+        let (source, slot, state) = match i {
+            // an ordinary, working sled
+            0 => (
+                "fake MGS 1",
+                3,
+                SpState {
+                    base_mac_address: [0; 6],
+                    hubris_archive_id: String::from("hubris1"),
+                    model: String::from("model1"),
+                    power_state: PowerState::A0,
+                    revision: 0,
+                    rot: RotState::V2 {
+                        active: RotSlot::A,
+                        pending_persistent_boot_preference: None,
+                        persistent_boot_preference: RotSlot::A,
+                        slot_a_sha3_256_digest: Some(String::from(
+                            "slotAdigest1",
+                        )),
+                        slot_b_sha3_256_digest: Some(String::from(
+                            "slotBdigest1",
+                        )),
+                        transient_boot_preference: None,
+                    },
+                    serial_number: String::from("s1"),
                 },
-                serial_number: String::from("s1"),
-            },
-        )
-        .unwrap();
+            ),
 
-    // another ordinary sled with different values for ordinary fields
-    let sled2_bb = builder
-        .found_sp_state(
-            "fake MGS 2",
-            SpType::Sled,
-            4,
-            SpState {
-                base_mac_address: [1; 6],
-                hubris_archive_id: String::from("hubris2"),
-                model: String::from("model2"),
-                power_state: PowerState::A2,
-                revision: 1,
-                rot: RotState::V2 {
-                    active: RotSlot::B,
-                    pending_persistent_boot_preference: Some(RotSlot::A),
-                    persistent_boot_preference: RotSlot::A,
-                    slot_a_sha3_256_digest: Some(String::from("slotAdigest2")),
-                    slot_b_sha3_256_digest: Some(String::from("slotBdigest2")),
-                    transient_boot_preference: Some(RotSlot::B),
+            // another ordinary sled with different values for ordinary fields
+            1 => (
+                "fake MGS 2",
+                4,
+                SpState {
+                    base_mac_address: [1; 6],
+                    hubris_archive_id: String::from("hubris2"),
+                    model: String::from("model2"),
+                    power_state: PowerState::A2,
+                    revision: 1,
+                    rot: RotState::V2 {
+                        active: RotSlot::B,
+                        pending_persistent_boot_preference: Some(RotSlot::A),
+                        persistent_boot_preference: RotSlot::A,
+                        slot_a_sha3_256_digest: Some(String::from(
+                            "slotAdigest2",
+                        )),
+                        slot_b_sha3_256_digest: Some(String::from(
+                            "slotBdigest2",
+                        )),
+                        transient_boot_preference: Some(RotSlot::B),
+                    },
+                    // same serial number, which is okay because it's a
+                    // different model number
+                    serial_number: String::from("s1"),
                 },
-                // same serial number, which is okay because it's a
-                // different model number
-                serial_number: String::from("s1"),
-            },
-        )
-        .unwrap();
+            ),
+
+            // a sled with no RoT state or other optional fields
+            2 => (
+                "fake MGS 1",
+                5,
+                SpState {
+                    base_mac_address: [4; 6],
+                    hubris_archive_id: String::from("hubris5"),
+                    model: String::from("model1"),
+                    power_state: PowerState::A2,
+                    revision: 1,
+                    rot: RotState::CommunicationFailed {
+                        message: String::from("test suite injected error"),
+                    },
+                    serial_number: String::from("s2"),
+                },
+            ),
+
+            // any number of additional, generated sleds
+            _ => (
+                "fake MGS 1",
+                u32::try_from(3 + i).unwrap(),
+                sp_state(&format!("sled{}", i)),
+            ),
+        };
+        let bb =
+            builder.found_sp_state(source, SpType::Sled, slot, state).unwrap();
+        sleds.push(bb);
+    }
 
-    // a switch
-    let switch1_bb = builder
-        .found_sp_state(
-            "fake MGS 2",
-            SpType::Switch,
-            0,
+    let mut switches = Vec::with_capacity(params.num_switches);
+    for i in 0..params.num_switches {
+        let state = if i == 0 {
             SpState {
                 base_mac_address: [2; 6],
                 hubris_archive_id: String::from("hubris3"),
@@ -118,16 +186,24 @@ pub fn representative() -> Representative {
                 // same serial number, which is okay because it's a
                 // different model number
                 serial_number: String::from("s1"),
-            },
-        )
-        .unwrap();
+            }
+        } else {
+            sp_state(&format!("switch{}", i))
+        };
+        let bb = builder
+            .found_sp_state(
+                "fake MGS 2",
+                SpType::Switch,
+                u32::try_from(i).unwrap(),
+                state,
+            )
+            .unwrap();
+        switches.push(bb);
+    }
 
-    // a PSC
-    let psc_bb = builder
-        .found_sp_state(
-            "fake MGS 1",
-            SpType::Power,
-            1,
+    let mut pscs = Vec::with_capacity(params.num_pscs);
+    for i in 0..params.num_pscs {
+        let state = if i == 0 {
             SpState {
                 base_mac_address: [3; 6],
                 hubris_archive_id: String::from("hubris4"),
@@ -143,37 +219,30 @@ pub fn representative() -> Representative {
                     transient_boot_preference: None,
                 },
                 serial_number: String::from("s2"),
-            },
-        )
-        .unwrap();
-
-    // a sled with no RoT state or other optional fields
-    let sled3_bb = builder
-        .found_sp_state(
-            "fake MGS 1",
-            SpType::Sled,
-            5,
-            SpState {
-                base_mac_address: [4; 6],
-                hubris_archive_id: String::from("hubris5"),
-                model: String::from("model1"),
-                power_state: PowerState::A2,
-                revision: 1,
-                rot: RotState::CommunicationFailed {
-                    message: String::from("test suite injected error"),
-                },
-                serial_number: String::from("s2"),
-            },
-        )
-        .unwrap();
+            }
+        } else {
+            sp_state(&format!("psc{}", i))
+        };
+        let bb = builder
+            .found_sp_state(
+                "fake MGS 1",
+                SpType::Power,
+                u32::try_from(1 + i).unwrap(),
+                state,
+            )
+            .unwrap();
+        pscs.push(bb);
+    }
 
     // Report some cabooses.
 
-    // We'll use the same cabooses for most of these components, although
-    // that's not possible in a real system.  We deliberately construct a
-    // new value each time to make sure the builder correctly normalizes it.
-    let common_caboose_baseboards = [&sled1_bb, &sled2_bb, &switch1_bb];
-    for bb in &common_caboose_baseboards {
+    // We'll use the same cabooses for most of the hand-picked components,
+    // although that's not possible in a real system.  We deliberately
+    // construct a new value each time to make sure the builder correctly
+    // normalizes it.
+    let common_caboose_baseboards =
+        sleds.iter().take(2).chain(switches.iter().take(1));
+    for bb in common_caboose_baseboards {
         for which in CabooseWhich::iter() {
             assert!(!builder.found_caboose_already(bb, which));
             builder
@@ -183,50 +252,72 @@ pub fn representative() -> Representative {
         }
     }
 
-    // For the PSC, use different cabooses for both slots of both the SP and
-    // RoT, just to exercise that we correctly keep track of different
+    // For the first PSC, use different cabooses for both slots of both the SP
+    // and RoT, just to exercise that we correctly keep track of different
     // cabooses.
-    builder
-        .found_caboose(
-            &psc_bb,
-            CabooseWhich::SpSlot0,
-            "test suite",
-            caboose("psc_sp_0"),
-        )
-        .unwrap();
-    builder
-        .found_caboose(
-            &psc_bb,
-            CabooseWhich::SpSlot1,
-            "test suite",
-            caboose("psc_sp_1"),
-        )
-        .unwrap();
-    builder
-        .found_caboose(
-            &psc_bb,
-            CabooseWhich::RotSlotA,
-            "test suite",
-            caboose("psc_rot_a"),
-        )
-        .unwrap();
-    builder
-        .found_caboose(
-            &psc_bb,
-            CabooseWhich::RotSlotB,
-            "test suite",
-            caboose("psc_rot_b"),
-        )
-        .unwrap();
+    if let Some(psc_bb) = pscs.first() {
+        builder
+            .found_caboose(
+                psc_bb,
+                CabooseWhich::SpSlot0,
+                "test suite",
+                caboose("psc_sp_0"),
+            )
+            .unwrap();
+        builder
+            .found_caboose(
+                psc_bb,
+                CabooseWhich::SpSlot1,
+                "test suite",
+                caboose("psc_sp_1"),
+            )
+            .unwrap();
+        builder
+            .found_caboose(
+                psc_bb,
+                CabooseWhich::RotSlotA,
+                "test suite",
+                caboose("psc_rot_a"),
+            )
+            .unwrap();
+        builder
+            .found_caboose(
+                psc_bb,
+                CabooseWhich::RotSlotB,
+                "test suite",
+                caboose("psc_rot_b"),
+            )
+            .unwrap();
+    }
 
     // We deliberately provide no cabooses for sled3.
 
+    // Each generated baseboard gets its own cabooses.
+    let generated_baseboards = sleds
+        .iter()
+        .skip(3)
+        .chain(switches.iter().skip(1))
+        .chain(pscs.iter().skip(1));
+    for bb in generated_baseboards {
+        let unique = format!("{}_{}", bb.part_number, bb.serial_number);
+        for which in CabooseWhich::iter() {
+            builder
+                .found_caboose(bb, which, "test suite", caboose(&unique))
+                .unwrap();
+        }
+    }
+
     // Report some RoT pages.
 
     // We'll use the same RoT pages for most of these components, although
     // that's not possible in a real system. We deliberately construct a new
     // value each time to make sure the builder correctly normalizes it.
-    let common_rot_page_baseboards = [&sled1_bb, &sled3_bb, &switch1_bb];
+    let common_rot_page_baseboards = sleds
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != 1)
+        .map(|(_, bb)| bb)
+        .chain(switches.iter());
     for bb in common_rot_page_baseboards {
         for which in RotPageWhich::iter() {
             assert!(!builder.found_rot_page_already(bb, which));
@@ -237,46 +328,33 @@ pub fn representative() -> Representative {
         }
     }
 
-    // For the PSC, use different RoT page data for each kind of page, just to
+    // For the PSCs, use different RoT page data for each kind of page, just to
     // exercise that we correctly keep track of different data values.
-    builder
-        .found_rot_page(
-            &psc_bb,
-            RotPageWhich::Cmpa,
-            "test suite",
-            rot_page("psc cmpa"),
-        )
-        .unwrap();
-    builder
-        .found_rot_page(
-            &psc_bb,
-            RotPageWhich::CfpaActive,
-            "test suite",
-            rot_page("psc cfpa active"),
-        )
-        .unwrap();
-    builder
-        .found_rot_page(
-            &psc_bb,
-            RotPageWhich::CfpaInactive,
-            "test suite",
-            rot_page("psc cfpa inactive"),
-        )
-        .unwrap();
-    builder
-        .found_rot_page(
-            &psc_bb,
-            RotPageWhich::CfpaScratch,
-            "test suite",
-            rot_page("psc cfpa scratch"),
-        )
-        .unwrap();
+    for (i, psc_bb) in pscs.iter().enumerate() {
+        let prefix =
+            if i == 0 { String::from("psc") } else { format!("psc{}", i) };
+        for (which, label) in [
+            (RotPageWhich::Cmpa, "cmpa"),
+            (RotPageWhich::CfpaActive, "cfpa active"),
+            (RotPageWhich::CfpaInactive, "cfpa inactive"),
+            (RotPageWhich::CfpaScratch, "cfpa scratch"),
+        ] {
+            builder
+                .found_rot_page(
+                    psc_bb,
+                    which,
+                    "test suite",
+                    rot_page(&format!("{} {}", prefix, label)),
+                )
+                .unwrap();
+        }
+    }
 
     // We deliberately provide no RoT pages for sled2.
 
     // Report some sled agents.
     //
-    // This first one will match "sled1_bb"'s baseboard information.
+    // This first one will match the first sled's baseboard information.
     let sled_agent_id_basic =
         "c5aec1df-b897-49e4-8085-ccd975f9b529".parse().unwrap();
     // Add some disks to this first sled.
@@ -435,11 +513,12 @@ pub fn representative() -> Representative {
         .found_sled_omicron_zones("fake sled 15 agent", sled17_id, sled17)
         .unwrap();
 
+    sleds.push(sled4_bb);
     Representative {
         builder,
-        sleds: [sled1_bb, sled2_bb, sled3_bb, sled4_bb],
-        switch: switch1_bb,
-        psc: psc_bb,
+        sleds,
+        switches,
+        pscs,
         sled_agents: [
             sled_agent_id_basic,
             sled_agent_id_extra,
@@ -449,24 +528,80 @@ pub fn representative() -> Representative {
     }
 }
 
+/// Returns an example Collection like [`representative()`] that also includes
+/// several operational errors from the collection process, along with the
+/// total number of errors that the collection should report
+///
+/// [`representative()`] itself produces two errors (one for a sled whose RoT
+/// could not be reached and one for a sled agent that reported an unknown
+/// baseboard).  This adds errors like those reported when:
+///
+/// - an SP that ignition reported as powered on failed to respond to MGS
+/// - reading a caboose from an SP failed
+/// - a sled agent failed to respond
+pub fn representative_with_errors() -> (Representative, usize) {
+    let mut representative = representative();
+    let builder = &mut representative.builder;
+
+    let injected = [
+        anyhow!("test suite injected error: timed out").context(
+            "MGS \"fake MGS 2\": fetching state of SP \
+             SpIdentifier { slot: 7, type_: Sled }",
+        ),
+        anyhow!("test suite injected error: communication failed").context(
+            "MGS \"fake MGS 1\": SP SpIdentifier { slot: 5, type_: Sled }: \
+             caboose SpSlot0",
+        ),
+        anyhow!("test suite injected error: connection refused")
+            .context("Sled Agent \"http://[::1]:12345\": inventory"),
+    ];
+    let nerrors = 2 + injected.len();
+    for error in injected {
+        builder.found_error(InventoryError::from(error));
+    }
+
+    (representative, nerrors)
+}
+
 pub struct Representative {
     pub builder: CollectionBuilder,
-    pub sleds: [Arc<BaseboardId>; 4],
-    pub switch: Arc<BaseboardId>,
-    pub psc: Arc<BaseboardId>,
+    /// sleds found via MGS, followed by the one reported only by its sled
+    /// agent
+    pub sleds: Vec<Arc<BaseboardId>>,
+    pub switches: Vec<Arc<BaseboardId>>,
+    pub pscs: Vec<Arc<BaseboardId>>,
     pub sled_agents: [SledUuid; 4],
 }
 
 impl Representative {
     pub fn new(
         builder: CollectionBuilder,
-        sleds: [Arc<BaseboardId>; 4],
-        switch: Arc<BaseboardId>,
-        psc: Arc<BaseboardId>,
+        sleds: Vec<Arc<BaseboardId>>,
+        switches: Vec<Arc<BaseboardId>>,
+        pscs: Vec<Arc<BaseboardId>>,
         sled_agents: [SledUuid; 4],
     ) -> Self {
-        Self { builder, sleds, switch, psc, sled_agents }
+        Self { builder, sleds, switches, pscs, sled_agents }
+    }
+}
+
+/// Reports each sled's Omicron zones to `builder`, as though each sled's agent
+/// had reported the given configuration
+///
+/// This is useful for building collections that reflect some particular
+/// layout of zones (e.g., one produced by a blueprint).
+pub fn with_omicron_zones(
+    builder: &mut CollectionBuilder,
+    zones: BTreeMap<SledUuid, OmicronZonesConfig>,
+) -> Result<(), anyhow::Error> {
+    for (sled_id, sled_zones) in zones {
+        builder.found_sled_omicron_zones(
+            "fake sled agent",
+            sled_id,
+            sled_zones,
+        )?;
     }
+    Ok(())
 }
 
 /// Returns an SP state that can be used to populate a collection for testing
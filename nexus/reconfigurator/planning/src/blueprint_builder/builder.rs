@@ -162,17 +162,7 @@ impl fmt::Display for Operation {
                 write!(f, "sled {sled_id}: added {added} disks, removed {removed} disks")
             }
             Self::ZoneExpunged { sled_id, reason, count } => {
-                let reason = match reason {
-                    ZoneExpungeReason::DiskExpunged => {
-                        "zone using expunged disk"
-                    }
-                    ZoneExpungeReason::SledDecommissioned => {
-                        "sled state is decomissioned"
-                    }
-                    ZoneExpungeReason::SledExpunged => {
-                        "sled policy is expunged"
-                    }
-                };
+                let reason = reason.explanation();
                 write!(
                     f,
                     "sled {sled_id}: expunged {count} zones because: {reason}"
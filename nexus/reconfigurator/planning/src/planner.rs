@@ -55,6 +55,11 @@ pub struct Planner<'a> {
     // information about all sleds that we expect), we should verify that up
     // front and update callers to ensure that it's true.
     inventory: &'a Collection,
+    // human-readable explanations of the changes made during planning
+    //
+    // This is only intended for debugging the planner; see
+    // `Planner::plan_with_rationale()`.
+    rationale: Vec<String>,
 }
 
 impl<'a> Planner<'a> {
@@ -73,7 +78,7 @@ impl<'a> Planner<'a> {
             input,
             creator,
         )?;
-        Ok(Planner { log, input, blueprint, inventory })
+        Ok(Planner { log, input, blueprint, inventory, rationale: Vec::new() })
     }
 
     /// Within tests, set a seeded RNG for deterministic results.
@@ -92,6 +97,23 @@ impl<'a> Planner<'a> {
         Ok(self.blueprint.build())
     }
 
+    /// Like [`Planner::plan()`], but also returns a human-readable list of
+    /// reasons for each change the planner made.
+    ///
+    /// This is intended only for debugging the planner.  The exact contents
+    /// of these messages are not stable.
+    pub fn plan_with_rationale(
+        mut self,
+    ) -> Result<(Blueprint, Vec<String>), Error> {
+        self.do_plan()?;
+        Ok((self.blueprint.build(), self.rationale))
+    }
+
+    /// Records the reason for a change made during planning.
+    fn explain(&mut self, reason: String) {
+        self.rationale.push(reason);
+    }
+
     fn do_plan(&mut self) -> Result<(), Error> {
         // We perform planning in two loops: the first one turns expunged sleds
         // into expunged zones, and the second one adds services.
@@ -160,6 +182,10 @@ impl<'a> Planner<'a> {
             if all_zones_expunged && num_instances_assigned == 0 {
                 self.blueprint
                     .set_sled_state(sled_id, SledState::Decommissioned);
+                self.explain(format!(
+                    "sled {sled_id}: decommissioned because its policy is \
+                     expunged and all of its zones are expunged"
+                ));
             }
         }
 
@@ -178,7 +204,14 @@ impl<'a> Planner<'a> {
             commissioned_sled_ids.insert(sled_id);
 
             // Perform the expungement, for any zones that might need it.
-            self.blueprint.expunge_zones_for_sled(sled_id, sled_details)?;
+            let expunged =
+                self.blueprint.expunge_zones_for_sled(sled_id, sled_details)?;
+            for (zone_id, reason) in expunged {
+                self.explain(format!(
+                    "sled {sled_id}: expunged zone {zone_id} because {}",
+                    reason.explanation(),
+                ));
+            }
         }
 
         // Check for any decommissioned sleds (i.e., sleds for which our
@@ -243,6 +276,10 @@ impl<'a> Planner<'a> {
                     added,
                     removed,
                 });
+                self.explain(format!(
+                    "sled {sled_id}: added {added} disks and removed \
+                     {removed} disks to match the sled's physical disks"
+                ));
 
                 // Note that this doesn't actually need to short-circuit the
                 // rest of the blueprint planning, as long as during execution
@@ -263,6 +300,11 @@ impl<'a> Planner<'a> {
                     sled_id,
                     kind: ZoneKind::BoundaryNtp,
                 });
+                self.explain(format!(
+                    "sled {sled_id}: added NTP zone because every sled needs \
+                     one (no other zones will be added to this sled until it \
+                     appears in inventory)"
+                ));
                 // Don't make any other changes to this sled.  However, this
                 // change is compatible with any other changes to other sleds,
                 // so we can "continue" here rather than "break".
@@ -302,6 +344,10 @@ impl<'a> Planner<'a> {
                     inventory yet";
                     "sled_id" => %sled_id,
                 );
+                self.explain(format!(
+                    "sled {sled_id}: made no other changes because its NTP \
+                     zone is not yet present in inventory"
+                ));
                 continue;
             }
 
@@ -320,6 +366,10 @@ impl<'a> Planner<'a> {
                         "sled_id" => ?sled_id,
                         "zpool_id" => ?zpool_id,
                     );
+                    self.explain(format!(
+                        "sled {sled_id}: added Crucible zone because \
+                         in-service zpool {zpool_id} did not have one"
+                    ));
                     ncrucibles_added += 1;
                 }
             }
@@ -425,20 +475,7 @@ impl<'a> Planner<'a> {
             num_existing_kind_zones += num_zones_of_kind;
         }
 
-        let target_count = match zone_kind {
-            DiscretionaryOmicronZone::BoundaryNtp => {
-                self.input.target_boundary_ntp_zone_count()
-            }
-            DiscretionaryOmicronZone::CockroachDb => {
-                self.input.target_cockroachdb_zone_count()
-            }
-            DiscretionaryOmicronZone::InternalDns => {
-                self.input.target_internal_dns_zone_count()
-            }
-            DiscretionaryOmicronZone::Nexus => {
-                self.input.target_nexus_zone_count()
-            }
-        };
+        let target_count = self.target_zone_count(zone_kind);
 
         // TODO-correctness What should we do if we have _too many_
         // `zone_kind` zones? For now, just log it the number of zones any
@@ -455,6 +492,25 @@ impl<'a> Planner<'a> {
         num_zones_to_add
     }
 
+    // Returns the number of zones of the given `zone_kind` that the policy
+    // says should be running.
+    fn target_zone_count(&self, zone_kind: DiscretionaryOmicronZone) -> usize {
+        match zone_kind {
+            DiscretionaryOmicronZone::BoundaryNtp => {
+                self.input.target_boundary_ntp_zone_count()
+            }
+            DiscretionaryOmicronZone::CockroachDb => {
+                self.input.target_cockroachdb_zone_count()
+            }
+            DiscretionaryOmicronZone::InternalDns => {
+                self.input.target_internal_dns_zone_count()
+            }
+            DiscretionaryOmicronZone::Nexus => {
+                self.input.target_nexus_zone_count()
+            }
+        }
+    }
+
     // Attempts to place `num_zones_to_add` new zones of `kind`.
     //
     // It is not an error if there are too few eligible sleds to start a
@@ -485,6 +541,11 @@ impl<'a> Planner<'a> {
                         "placed" => i,
                         "wanted_to_place" => num_zones_to_add,
                     );
+                    self.explain(format!(
+                        "placed only {i} of {num_zones_to_add} desired new \
+                         {} zones because no other sleds were eligible",
+                        ZoneKind::from(kind).report_str(),
+                    ));
 
                     // Adjust `num_zones_to_add` downward so it's consistent
                     // with the number of zones we're actually adding.
@@ -496,6 +557,7 @@ impl<'a> Planner<'a> {
         }
 
         // For each sled we need to change, actually do so.
+        let target_count = self.target_zone_count(kind);
         let mut new_zones_added = 0;
         for (sled_id, additional_zone_count) in sleds_to_change {
             // TODO-cleanup This is awkward: the builder wants to know how many
@@ -539,6 +601,11 @@ impl<'a> Planner<'a> {
                         "added" => added,
                         "removed" => removed,
                     );
+                    self.explain(format!(
+                        "sled {sled_id}: added {added} {} zone(s) to reach \
+                         target count {target_count}",
+                        ZoneKind::from(kind).report_str(),
+                    ));
                     new_zones_added += added;
                 }
                 // This is only possible if we asked the sled to ensure the same
@@ -720,6 +787,19 @@ pub(crate) enum ZoneExpungeReason {
     SledExpunged,
 }
 
+impl ZoneExpungeReason {
+    /// Returns a human-readable explanation of this reason.
+    pub(crate) fn explanation(self) -> &'static str {
+        match self {
+            ZoneExpungeReason::DiskExpunged => "zone using expunged disk",
+            ZoneExpungeReason::SledDecommissioned => {
+                "sled state is decomissioned"
+            }
+            ZoneExpungeReason::SledExpunged => "sled policy is expunged",
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::Planner;
@@ -762,6 +842,54 @@ mod test {
     use std::mem;
     use typed_rng::TypedUuidRng;
 
+    /// Checks that the planner explains the changes it makes
+    #[test]
+    fn test_plan_with_rationale() {
+        static TEST_NAME: &str = "planner_plan_with_rationale";
+        let logctx = test_setup_log(TEST_NAME);
+
+        let mut example =
+            ExampleSystem::new(&logctx.log, TEST_NAME, DEFAULT_N_SLEDS);
+        let blueprint1 = &example.blueprint;
+
+        // With no changes to make, there should be nothing to explain.
+        let (_, rationale) = Planner::new_based_on(
+            logctx.log.clone(),
+            blueprint1,
+            &example.input,
+            "no-op?",
+            &example.collection,
+        )
+        .expect("failed to create planner")
+        .with_rng_seed((TEST_NAME, "bp2"))
+        .plan_with_rationale()
+        .expect("failed to plan");
+        assert!(rationale.is_empty(), "unexpected rationale: {rationale:?}");
+
+        // Adding a sled should produce an explanation for its new NTP zone.
+        let new_sled_id = example.sled_rng.next();
+        let _ =
+            example.system.sled(SledBuilder::new().id(new_sled_id)).unwrap();
+        let input = example.system.to_planning_input_builder().unwrap().build();
+        let (_, rationale) = Planner::new_based_on(
+            logctx.log.clone(),
+            blueprint1,
+            &input,
+            "test: add NTP?",
+            &example.collection,
+        )
+        .expect("failed to create planner")
+        .with_rng_seed((TEST_NAME, "bp3"))
+        .plan_with_rationale()
+        .expect("failed to plan");
+        println!("rationale: {rationale:#?}");
+        assert!(rationale.iter().any(|r| {
+            r.starts_with(&format!("sled {new_sled_id}: added NTP zone"))
+        }));
+
+        logctx.cleanup_successful();
+    }
+
     /// Runs through a basic sequence of blueprints for adding a sled
     #[test]
     fn test_basic_add_sled() {
@@ -57,6 +57,9 @@ impl<T> SubnetIterator for T where
 {
 }
 
+/// Hardware slots (cubby numbers) that may only be used by Scrimlets
+const SCRIMLET_SLOTS: [u16; 2] = [14, 16];
+
 /// Describes an actual or synthetic Oxide rack for planning and testing
 ///
 /// From this description, you can extract a `PlanningInput` or inventory
@@ -107,7 +110,8 @@ impl SystemDescription {
         //
         // We use `BTreeSet` because it efficiently expresses what we want,
         // though the set sizes are small enough that it doesn't much matter.
-        let available_scrimlet_slots: BTreeSet<u16> = BTreeSet::from([14, 16]);
+        let available_scrimlet_slots: BTreeSet<u16> =
+            BTreeSet::from(SCRIMLET_SLOTS);
         let available_non_scrimlet_slots: BTreeSet<u16> = (0..=31)
             .collect::<BTreeSet<_>>()
             .difference(&available_scrimlet_slots)
@@ -202,6 +206,12 @@ impl SystemDescription {
         self
     }
 
+    /// Resets the target number of Nexus zones to the policy default
+    pub fn reset_target_nexus_zone_count(&mut self) -> &mut Self {
+        self.target_nexus_zone_count = NEXUS_REDUNDANCY;
+        self
+    }
+
     pub fn service_ip_pool_ranges(
         &mut self,
         ranges: Vec<IpRange>,
@@ -259,6 +269,28 @@ impl SystemDescription {
         Ok(self)
     }
 
+    /// Remove a sled (along with its zpools) from the system
+    ///
+    /// The sled's hardware slot becomes available to sleds added later.  Its
+    /// subnet is not reused.
+    pub fn sled_remove(
+        &mut self,
+        sled_id: SledUuid,
+    ) -> anyhow::Result<&mut Self> {
+        let sled = self
+            .sleds
+            .shift_remove(&sled_id)
+            .ok_or_else(|| anyhow!("no sled with id {}", sled_id))?;
+        if let Some((slot, _)) = sled.inventory_sp {
+            if SCRIMLET_SLOTS.contains(&slot) {
+                self.available_scrimlet_slots.insert(slot);
+            } else {
+                self.available_non_scrimlet_slots.insert(slot);
+            }
+        }
+        Ok(self)
+    }
+
     /// Add a sled to the system based on information that came from the
     /// database of an existing system
     pub fn sled_full(
@@ -533,7 +533,7 @@ impl BackgroundTasksInitializer {
                 ),
             ),
             opctx: opctx.child(BTreeMap::new()),
-            watchers: vec![Box::new(inventory_watcher)],
+            watchers: vec![Box::new(inventory_watcher.clone())],
             activator: task_physical_disk_adoption,
         });
 
@@ -598,7 +598,12 @@ impl BackgroundTasksInitializer {
             let detector = region_replacement::RegionReplacementDetector::new(
                 datastore.clone(),
                 sagas.clone(),
+                &config.region_replacement,
+                inventory_watcher.clone(),
             );
+            producer_registry
+                .register_producer(detector.metrics_producer(nexus_id, rack_id))
+                .unwrap();
 
             driver.register(TaskDefinition {
                 name: "region_replacement",
@@ -608,7 +613,7 @@ impl BackgroundTasksInitializer {
                 period: config.region_replacement.period_secs,
                 task_impl: Box::new(detector),
                 opctx: opctx.child(BTreeMap::new()),
-                watchers: vec![],
+                watchers: vec![Box::new(inventory_watcher.clone())],
                 activator: task_region_replacement,
             });
         };
@@ -6,9 +6,10 @@
 //! process
 //!
 //! This task's responsibility is to create region replacement requests when
-//! physical disks are expunged, and trigger the region replacement start saga
-//! for any requests that are in state "Requested". See the documentation there
-//! for more information.
+//! physical disks are expunged (or, depending on the configured
+//! [`RegionReplacementDetectionMode`], when zpools go missing from inventory),
+//! and trigger the region replacement start saga for any requests that are in
+//! state "Requested". See the documentation there for more information.
 
 use crate::app::authn;
 use crate::app::background::BackgroundTask;
@@ -19,39 +20,224 @@ use crate::app::sagas::NexusSaga;
 use crate::app::RegionAllocationStrategy;
 use futures::future::BoxFuture;
 use futures::FutureExt;
+use nexus_config::RegionReplacementConfig;
+use nexus_config::RegionReplacementDetectionMode;
+use nexus_db_model::Region;
 use nexus_db_model::RegionReplacement;
 use nexus_db_queries::context::OpContext;
 use nexus_db_queries::db::DataStore;
+use omicron_uuid_kinds::CollectionUuid;
 use omicron_uuid_kinds::GenericUuid;
 use omicron_uuid_kinds::TypedUuid;
+use rand::Rng;
 use serde_json::json;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use steno::SagaDag;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+// The most distinct error messages reported by a single activation
+const MAX_DISTINCT_ERRORS: usize = 10;
+
+oximeter::use_timeseries!("region-replacement-task.toml");
+use region_replacement_task::RegionReplacementTask;
 
 pub struct RegionReplacementDetector {
     datastore: Arc<DataStore>,
     sagas: Arc<dyn StartSaga>,
+    detection_mode: RegionReplacementDetectionMode,
+    max_start_jitter: Duration,
+    max_in_flight_requests: Option<usize>,
+    start_cooldown: bool,
+    max_starts_per_activation: Option<usize>,
+    rx_inventory_collection: watch::Receiver<Option<CollectionUuid>>,
+    last_activation: Arc<Mutex<Option<metrics::ActivationCounts>>>,
+
+    /// How the start saga allocates replacement regions
+    allocation_strategy: RegionAllocationStrategy,
+
+    /// Builds the start saga's DAG (overridden in tests to inject failures)
+    prepare_start_saga:
+        fn(
+            &sagas::region_replacement_start::Params,
+        ) -> Result<SagaDag, omicron_common::api::external::Error>,
+
+    /// Requests for which the start saga's DAG could not be built
+    ///
+    /// This is deterministic for a given request, so these are not retried
+    /// for as long as this Nexus is running.
+    prepare_failed: BTreeSet<Uuid>,
 }
 
 impl RegionReplacementDetector {
-    pub fn new(datastore: Arc<DataStore>, sagas: Arc<dyn StartSaga>) -> Self {
-        RegionReplacementDetector { datastore, sagas }
+    pub fn new(
+        datastore: Arc<DataStore>,
+        sagas: Arc<dyn StartSaga>,
+        config: &RegionReplacementConfig,
+        rx_inventory_collection: watch::Receiver<Option<CollectionUuid>>,
+    ) -> Self {
+        RegionReplacementDetector {
+            datastore,
+            sagas,
+            detection_mode: config.detection_mode,
+            max_start_jitter: config.max_start_jitter_secs,
+            max_in_flight_requests: config.max_in_flight_requests,
+            start_cooldown: config.start_cooldown,
+            max_starts_per_activation: config.max_starts_per_activation,
+            rx_inventory_collection,
+            last_activation: Arc::new(Mutex::new(None)),
+            allocation_strategy: config.allocation_strategy.clone(),
+            prepare_start_saga: SagaRegionReplacementStart::prepare,
+            prepare_failed: BTreeSet::new(),
+        }
     }
 
-    async fn send_start_request(
+    /// Returns an Oximeter producer reporting this task's counts from its
+    /// most recent activation.
+    pub fn metrics_producer(
+        &self,
+        nexus_id: Uuid,
+        rack_id: Uuid,
+    ) -> metrics::Producer {
+        metrics::Producer {
+            target: RegionReplacementTask { nexus_id, rack_id },
+            last_activation: self.last_activation.clone(),
+        }
+    }
+
+    /// Returns the regions that need replacing according to this task's
+    /// detection mode, with duplicates removed.
+    async fn find_regions_to_replace(
+        &self,
+        opctx: &OpContext,
+    ) -> Result<Vec<Region>, String> {
+        let log = &opctx.log;
+
+        let (check_expunged_disks, check_inventory) = match self.detection_mode
+        {
+            RegionReplacementDetectionMode::ExpungedDisks => (true, false),
+            RegionReplacementDetectionMode::MissingFromInventory => {
+                (false, true)
+            }
+            RegionReplacementDetectionMode::All => (true, true),
+        };
+
+        // A region may be found by more than one source (e.g., if its zpool
+        // is missing from inventory _and_ its disk was expunged), so key the
+        // results by region id.
+        let mut regions: BTreeMap<_, Region> = BTreeMap::new();
+
+        if check_expunged_disks {
+            let expunged = self
+                .datastore
+                .find_regions_on_expunged_physical_disks(opctx)
+                .await
+                .map_err(|e| {
+                    format!(
+                        "find_regions_on_expunged_physical_disks failed: {e}"
+                    )
+                })?;
+            regions.extend(expunged.into_iter().map(|r| (r.id(), r)));
+        }
+
+        if check_inventory {
+            let maybe_collection_id = *self.rx_inventory_collection.borrow();
+            match maybe_collection_id {
+                Some(collection_id) => {
+                    let missing = self
+                        .datastore
+                        .find_regions_on_zpools_missing_from_inventory(
+                            opctx,
+                            collection_id,
+                        )
+                        .await
+                        .map_err(|e| {
+                            format!(
+                                "find_regions_on_zpools_missing_from_inventory \
+                                 failed: {e}"
+                            )
+                        })?;
+                    regions.extend(missing.into_iter().map(|r| (r.id(), r)));
+                }
+
+                None => {
+                    warn!(
+                        &log,
+                        "no inventory collection available yet; skipping \
+                         inventory-based region replacement detection"
+                    );
+                }
+            }
+        }
+
+        Ok(regions.into_values().collect())
+    }
+
+    fn prepare_start_request(
         &self,
         serialized_authn: authn::saga::Serialized,
         request: RegionReplacement,
-    ) -> Result<(), omicron_common::api::external::Error> {
+    ) -> Result<SagaDag, omicron_common::api::external::Error> {
         let params = sagas::region_replacement_start::Params {
             serialized_authn,
             request,
-            allocation_strategy:
-                RegionAllocationStrategy::RandomWithDistinctSleds { seed: None },
+            allocation_strategy: self.allocation_strategy.clone(),
         };
 
-        let saga_dag = SagaRegionReplacementStart::prepare(&params)?;
-        self.sagas.saga_start(saga_dag).await
+        (self.prepare_start_saga)(&params)
+    }
+}
+
+/// Error messages recorded during a single activation
+///
+/// A persistent failure can hit every region or request that an activation
+/// looks at, so identical messages are collapsed into one entry with a count,
+/// and only the first [`MAX_DISTINCT_ERRORS`] distinct messages are kept.
+#[derive(Default)]
+struct ActivationErrors {
+    messages: Vec<(String, usize)>,
+    dropped: usize,
+}
+
+impl ActivationErrors {
+    fn record(&mut self, message: String) {
+        if let Some((_, count)) =
+            self.messages.iter_mut().find(|(m, _)| *m == message)
+        {
+            *count += 1;
+        } else if self.messages.len() < MAX_DISTINCT_ERRORS {
+            self.messages.push((message, 1));
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.messages
+            .iter()
+            .map(|(message, count)| {
+                json!({ "message": message, "count": count })
+            })
+            .collect()
+    }
+}
+
+/// Returns `duration` in whole milliseconds, for reporting in task status
+fn as_millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Returns a random delay in `[0, max]`
+fn start_jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
     }
+    rand::thread_rng().gen_range(Duration::ZERO..=max)
 }
 
 impl BackgroundTask for RegionReplacementDetector {
@@ -64,29 +250,89 @@ impl BackgroundTask for RegionReplacementDetector {
 
             let mut ok = 0;
             let mut err = 0;
+            let mut deferred = 0;
+            let mut cooldown_deferred = 0;
+            let mut start_deferred = 0;
+            let mut prepare_skipped = 0;
+            let mut created = 0;
+            let mut errors = ActivationErrors::default();
 
-            // Find regions on expunged physical disks
-            let regions_to_be_replaced = match self
-                .datastore
-                .find_regions_on_expunged_physical_disks(opctx)
-                .await
-            {
-                Ok(regions) => regions,
+            // Find regions that need replacing
+            let find_regions_start = Instant::now();
+            let regions_to_be_replaced =
+                match self.find_regions_to_replace(opctx).await {
+                    Ok(regions) => regions,
 
-                Err(e) => {
-                    error!(
-                        &log,
-                        "find_regions_on_expunged_physical_disks failed: {e}"
-                    );
-                    err += 1;
+                    Err(e) => {
+                        let find_regions_elapsed = find_regions_start.elapsed();
+                        error!(&log, "{e}");
+                        errors.record(e);
+                        err += 1;
 
-                    return json!({
-                        "region_replacement_started_ok": ok,
-                        "region_replacement_started_err": err,
-                    });
-                }
+                        *self.last_activation.lock().unwrap() =
+                            Some(metrics::ActivationCounts {
+                                errors: err,
+                                ..Default::default()
+                            });
+                        return json!({
+                            "region_replacement_started_ok": ok,
+                            "region_replacement_started_err": err,
+                            "region_replacement_deferred": deferred,
+                            "region_replacement_cooldown_deferred":
+                                cooldown_deferred,
+                            "region_replacement_start_deferred":
+                                start_deferred,
+                            "region_replacement_prepare_skipped":
+                                prepare_skipped,
+                            "region_replacement_errors": errors.to_json(),
+                            "region_replacement_errors_dropped":
+                                errors.dropped,
+                            "region_replacement_scan": {
+                                "regions_scanned": 0,
+                                "find_regions_ms":
+                                    as_millis(find_regions_elapsed),
+                                "get_requested_ms": null,
+                            },
+                        });
+                    }
+                };
+            let find_regions_elapsed = find_regions_start.elapsed();
+
+            // If configured, find out how many more requests can be created
+            // before reaching the ceiling on in-flight requests. If that can't
+            // be determined, don't create any: existing requests are still
+            // advanced below.
+            let mut remaining = match self.max_in_flight_requests {
+                None => None,
+                Some(max) => match self
+                    .datastore
+                    .count_in_flight_region_replacements(opctx)
+                    .await
+                {
+                    Ok(count) => Some(max.saturating_sub(count)),
+
+                    Err(e) => {
+                        error!(
+                            &log,
+                            "error counting in-flight region replacement \
+                             requests: {e}",
+                        );
+                        errors.record(format!(
+                            "error counting in-flight region replacement \
+                             requests: {e}"
+                        ));
+                        err += 1;
+                        Some(0)
+                    }
+                },
             };
 
+            let scanned = regions_to_be_replaced.len();
+
+            // Requests created during this activation, which will not be
+            // started until a later one if the start cooldown is enabled.
+            let mut created_requests = BTreeSet::new();
+
             // Then create replacement requests for those if one doesn't exist
             // yet.
             for region in regions_to_be_replaced {
@@ -107,11 +353,20 @@ impl BackgroundTask for RegionReplacementDetector {
                              requests for {}: {e}",
                             region.id(),
                         );
+                        errors.record(format!(
+                            "error looking for existing region replacement \
+                             requests: {e}"
+                        ));
                         continue;
                     }
                 };
 
                 if maybe_request.is_none() {
+                    if remaining == Some(0) {
+                        deferred += 1;
+                        continue;
+                    }
+
                     match self
                         .datastore
                         .create_region_replacement_request_for_region(
@@ -127,6 +382,11 @@ impl BackgroundTask for RegionReplacementDetector {
                                 region.id(),
                                 region.volume_id(),
                             );
+                            created += 1;
+                            created_requests.insert(request_id);
+                            if let Some(remaining) = remaining.as_mut() {
+                                *remaining -= 1;
+                            }
                         }
 
                         Err(e) => {
@@ -137,24 +397,105 @@ impl BackgroundTask for RegionReplacementDetector {
                                 region.id(),
                                 region.volume_id(),
                             );
+                            errors.record(format!(
+                                "error adding region replacement request: {e}"
+                            ));
                             continue;
                         }
                     }
                 }
             }
 
+            if deferred > 0 {
+                warn!(
+                    &log,
+                    "deferred creating region replacement requests: too many \
+                     requests in flight";
+                    "deferred" => deferred,
+                    "max_in_flight_requests" => ?self.max_in_flight_requests,
+                );
+            }
+
+            // Other Nexus instances are likely activating this task on the
+            // same schedule. Wait a random amount of time before starting
+            // sagas so that they don't all try to start the same ones at once.
+            let jitter = start_jitter(self.max_start_jitter);
+            if !jitter.is_zero() {
+                debug!(
+                    &log,
+                    "delaying region replacement starts";
+                    "jitter" => ?jitter,
+                );
+                tokio::time::sleep(jitter).await;
+            }
+
             // Next, for each region replacement request in state "Requested",
             // run the start saga.
-            match self.datastore.get_requested_region_replacements(opctx).await
-            {
+            let get_requested_start = Instant::now();
+            let requested =
+                self.datastore.get_requested_region_replacements(opctx).await;
+            let get_requested_elapsed = get_requested_start.elapsed();
+            match requested {
                 Ok(requests) => {
+                    let mut sagas_attempted = 0;
+
+                    // Forget about failed requests that have since moved on.
+                    self.prepare_failed.retain(|id| {
+                        requests.iter().any(|request| request.id == *id)
+                    });
+
                     for request in requests {
-                        let result = self
-                            .send_start_request(
-                                authn::saga::Serialized::for_opctx(opctx),
-                                request,
-                            )
-                            .await;
+                        let request_id = request.id;
+
+                        if self.start_cooldown
+                            && created_requests.contains(&request_id)
+                        {
+                            cooldown_deferred += 1;
+                            continue;
+                        }
+
+                        if self.prepare_failed.contains(&request_id) {
+                            prepare_skipped += 1;
+                            continue;
+                        }
+
+                        // Leave the request in state "Requested" for a later
+                        // activation once this one has started enough sagas.
+                        if self
+                            .max_starts_per_activation
+                            .is_some_and(|max| sagas_attempted >= max)
+                        {
+                            start_deferred += 1;
+                            continue;
+                        }
+
+                        // A failure here is specific to this request: record
+                        // it so it isn't retried, and move on to the others.
+                        let saga_dag = match self.prepare_start_request(
+                            authn::saga::Serialized::for_opctx(opctx),
+                            request,
+                        ) {
+                            Ok(saga_dag) => saga_dag,
+
+                            Err(e) => {
+                                error!(
+                                    &log,
+                                    "preparing region replacement start saga \
+                                     failed: {e}";
+                                    "request_id" => %request_id,
+                                );
+                                errors.record(format!(
+                                    "preparing region replacement start saga \
+                                     failed: {e}"
+                                ));
+                                self.prepare_failed.insert(request_id);
+                                err += 1;
+                                continue;
+                            }
+                        };
+
+                        sagas_attempted += 1;
+                        let result = self.sagas.saga_start(saga_dag).await;
 
                         match result {
                             Ok(()) => {
@@ -165,8 +506,13 @@ impl BackgroundTask for RegionReplacementDetector {
                                 error!(
                                     &log,
                                     "sending region replacement start request \
-                                     failed: {e}",
+                                     failed: {e}";
+                                    "request_id" => %request_id,
                                 );
+                                errors.record(format!(
+                                    "sending region replacement start request \
+                                     failed: {e}"
+                                ));
                                 err += 1;
                             }
                         };
@@ -178,29 +524,203 @@ impl BackgroundTask for RegionReplacementDetector {
                         &log,
                         "query for region replacement requests failed: {e}",
                     );
+                    errors.record(format!(
+                        "query for region replacement requests failed: {e}"
+                    ));
                 }
             }
 
+            if start_deferred > 0 {
+                warn!(
+                    &log,
+                    "deferred starting region replacement requests: too many \
+                     start sagas in this activation";
+                    "start_deferred" => start_deferred,
+                    "max_starts_per_activation" =>
+                        ?self.max_starts_per_activation,
+                );
+            }
+
+            if cooldown_deferred > 0 {
+                debug!(
+                    &log,
+                    "deferred starting new region replacement requests until \
+                     the next activation";
+                    "cooldown_deferred" => cooldown_deferred,
+                );
+            }
+
+            *self.last_activation.lock().unwrap() =
+                Some(metrics::ActivationCounts {
+                    regions_scanned: scanned as u64,
+                    requests_created: created,
+                    sagas_started: ok,
+                    errors: err,
+                });
+
             json!({
                 "region_replacement_started_ok": ok,
                 "region_replacement_started_err": err,
+                "region_replacement_deferred": deferred,
+                "region_replacement_cooldown_deferred": cooldown_deferred,
+                "region_replacement_start_deferred": start_deferred,
+                "region_replacement_prepare_skipped": prepare_skipped,
+                "region_replacement_errors": errors.to_json(),
+                "region_replacement_errors_dropped": errors.dropped,
+                "region_replacement_scan": {
+                    "regions_scanned": scanned,
+                    "find_regions_ms": as_millis(find_regions_elapsed),
+                    "get_requested_ms": as_millis(get_requested_elapsed),
+                },
             })
         }
         .boxed()
     }
 }
 
+pub mod metrics {
+    use super::region_replacement_task::{
+        Errors, RegionsScanned, RequestsCreated, SagasStarted,
+    };
+    use super::RegionReplacementTask;
+    use oximeter::MetricsError;
+    use oximeter::Sample;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    /// Counts recorded by a single activation of the task
+    #[derive(Clone, Copy, Debug, Default)]
+    pub(super) struct ActivationCounts {
+        pub regions_scanned: u64,
+        pub requests_created: u64,
+        pub sagas_started: u64,
+        pub errors: u64,
+    }
+
+    /// Produces gauges describing the most recent activation of the region
+    /// replacement task
+    #[derive(Debug)]
+    pub struct Producer {
+        pub(super) target: RegionReplacementTask,
+        pub(super) last_activation: Arc<Mutex<Option<ActivationCounts>>>,
+    }
+
+    impl oximeter::Producer for Producer {
+        fn produce(
+            &mut self,
+        ) -> Result<Box<dyn Iterator<Item = Sample>>, MetricsError> {
+            // Nothing to report until the task has been activated
+            let Some(counts) = *self.last_activation.lock().unwrap() else {
+                return Ok(Box::new(std::iter::empty()));
+            };
+            let target = &self.target;
+            let samples = vec![
+                Sample::new(
+                    target,
+                    &RegionsScanned { datum: counts.regions_scanned },
+                )?,
+                Sample::new(
+                    target,
+                    &RequestsCreated { datum: counts.requests_created },
+                )?,
+                Sample::new(
+                    target,
+                    &SagasStarted { datum: counts.sagas_started },
+                )?,
+                Sample::new(target, &Errors { datum: counts.errors })?,
+            ];
+            Ok(Box::new(samples.into_iter()))
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::app::background::init::test::NoopStartSaga;
+    use nexus_config::RegionAllocationStrategy;
+    use nexus_db_model::PhysicalDiskPolicy;
     use nexus_db_model::RegionReplacement;
+    use nexus_db_queries::db::lookup::LookupPath;
+    use nexus_inventory::CollectionBuilder;
+    use nexus_sled_agent_shared::inventory::Baseboard;
+    use nexus_sled_agent_shared::inventory::InventoryZpool;
+    use nexus_sled_agent_shared::inventory::SledRole;
+    use nexus_test_utils::resource_helpers::DiskTest;
+    use nexus_test_utils::SLED_AGENT_UUID;
     use nexus_test_utils_macros::nexus_test;
+    use nexus_types::external_api::params;
+    use omicron_common::api::external::ByteCount;
+    use omicron_uuid_kinds::SledUuid;
+    use oximeter::types::Datum;
+    use oximeter::Producer as _;
     use uuid::Uuid;
 
     type ControlPlaneTestContext =
         nexus_test_utils::ControlPlaneTestContext<crate::Server>;
 
+    /// Returns the task's default configuration
+    fn test_config() -> RegionReplacementConfig {
+        RegionReplacementConfig {
+            period_secs: Duration::from_secs(30),
+            detection_mode: RegionReplacementDetectionMode::ExpungedDisks,
+            max_start_jitter_secs: Duration::ZERO,
+            max_in_flight_requests: None,
+            start_cooldown: false,
+            max_starts_per_activation: None,
+            allocation_strategy:
+                RegionAllocationStrategy::RandomWithDistinctSleds { seed: None },
+        }
+    }
+
+    /// Activates `task`, checking and then removing the scan timings (which
+    /// vary from run to run) from the result
+    async fn activate(
+        task: &mut RegionReplacementDetector,
+        opctx: &OpContext,
+    ) -> serde_json::Value {
+        let mut result = task.activate(opctx).await;
+        let scan = result
+            .as_object_mut()
+            .unwrap()
+            .remove("region_replacement_scan")
+            .expect("activation reports scan timings");
+        assert!(scan["regions_scanned"].is_u64());
+        assert!(scan["find_regions_ms"].is_u64());
+        assert!(scan["get_requested_ms"].is_u64());
+        result
+    }
+
+    #[test]
+    fn test_start_jitter_bound() {
+        assert_eq!(start_jitter(Duration::ZERO), Duration::ZERO);
+
+        let max = Duration::from_millis(250);
+        for _ in 0..1000 {
+            assert!(start_jitter(max) <= max);
+        }
+    }
+
+    #[test]
+    fn test_activation_errors_collapse_and_cap() {
+        let mut errors = ActivationErrors::default();
+        for _ in 0..3 {
+            errors.record(String::from("unreachable dataset"));
+        }
+        for i in 0..MAX_DISTINCT_ERRORS + 2 {
+            errors.record(format!("error {i}"));
+        }
+
+        let json = errors.to_json();
+        let messages = json.as_array().unwrap();
+        assert_eq!(messages.len(), MAX_DISTINCT_ERRORS);
+        assert_eq!(
+            messages[0],
+            json!({ "message": "unreachable dataset", "count": 3 })
+        );
+        assert_eq!(errors.dropped, 3);
+    }
+
     #[nexus_test(server = crate::Server)]
     async fn test_add_region_replacement_causes_start(
         cptestctx: &ControlPlaneTestContext,
@@ -213,16 +733,27 @@ mod test {
         );
 
         let starter = Arc::new(NoopStartSaga::new());
-        let mut task =
-            RegionReplacementDetector::new(datastore.clone(), starter.clone());
+        let (_tx, rx) = watch::channel(None);
+        let mut task = RegionReplacementDetector::new(
+            datastore.clone(),
+            starter.clone(),
+            &test_config(),
+            rx,
+        );
 
         // Noop test
-        let result = task.activate(&opctx).await;
+        let result = activate(&mut task, &opctx).await;
         assert_eq!(
             result,
             json!({
                 "region_replacement_started_ok": 0,
                 "region_replacement_started_err": 0,
+                "region_replacement_deferred": 0,
+                "region_replacement_cooldown_deferred": 0,
+                "region_replacement_start_deferred": 0,
+                "region_replacement_prepare_skipped": 0,
+                "region_replacement_errors": [],
+                "region_replacement_errors_dropped": 0,
             })
         );
 
@@ -236,15 +767,592 @@ mod test {
 
         // Activate the task - it should pick that up and try to run the region
         // replacement start saga
+        let result = activate(&mut task, &opctx).await;
+        assert_eq!(
+            result,
+            json!({
+                "region_replacement_started_ok": 1,
+                "region_replacement_started_err": 0,
+                "region_replacement_deferred": 0,
+                "region_replacement_cooldown_deferred": 0,
+                "region_replacement_start_deferred": 0,
+                "region_replacement_prepare_skipped": 0,
+                "region_replacement_errors": [],
+                "region_replacement_errors_dropped": 0,
+            })
+        );
+
+        assert_eq!(starter.count_reset(), 1);
+    }
+
+    #[nexus_test(server = crate::Server)]
+    async fn test_prepare_failure_does_not_block_others(
+        cptestctx: &ControlPlaneTestContext,
+    ) {
+        let nexus = &cptestctx.server.server_context().nexus;
+        let datastore = nexus.datastore();
+        let opctx = OpContext::for_tests(
+            cptestctx.logctx.log.clone(),
+            datastore.clone(),
+        );
+
+        const BAD_REQUEST_ID: Uuid =
+            Uuid::from_u128(0x6e0d4e5a_3f0c_4b3e_9b51_4a5c2f1e0d01);
+
+        let starter = Arc::new(NoopStartSaga::new());
+        let (_tx, rx) = watch::channel(None);
+        let mut task = RegionReplacementDetector::new(
+            datastore.clone(),
+            starter.clone(),
+            &test_config(),
+            rx,
+        );
+        task.prepare_start_saga = |params| {
+            if params.request.id == BAD_REQUEST_ID {
+                Err(omicron_common::api::external::Error::internal_error(
+                    "injected prepare failure",
+                ))
+            } else {
+                SagaRegionReplacementStart::prepare(params)
+            }
+        };
+
+        for i in 0..3 {
+            let mut request =
+                RegionReplacement::new(Uuid::new_v4(), Uuid::new_v4());
+            if i == 1 {
+                request.id = BAD_REQUEST_ID;
+            }
+            datastore
+                .insert_region_replacement_request(&opctx, request)
+                .await
+                .unwrap();
+        }
+
+        // The other requests are started despite the failure.
+        let result = activate(&mut task, &opctx).await;
+        assert_eq!(
+            result,
+            json!({
+                "region_replacement_started_ok": 2,
+                "region_replacement_started_err": 1,
+                "region_replacement_deferred": 0,
+                "region_replacement_cooldown_deferred": 0,
+                "region_replacement_start_deferred": 0,
+                "region_replacement_prepare_skipped": 0,
+                "region_replacement_errors": [{
+                    "message": "preparing region replacement start saga \
+                                failed: Internal Error: injected prepare \
+                                failure",
+                    "count": 1,
+                }],
+                "region_replacement_errors_dropped": 0,
+            })
+        );
+        assert_eq!(starter.count_reset(), 2);
+
+        // The failed request is not retried on the next activation.
+        let result = activate(&mut task, &opctx).await;
+        assert_eq!(
+            result,
+            json!({
+                "region_replacement_started_ok": 2,
+                "region_replacement_started_err": 0,
+                "region_replacement_deferred": 0,
+                "region_replacement_cooldown_deferred": 0,
+                "region_replacement_start_deferred": 0,
+                "region_replacement_prepare_skipped": 1,
+                "region_replacement_errors": [],
+                "region_replacement_errors_dropped": 0,
+            })
+        );
+        assert_eq!(starter.count_reset(), 2);
+    }
+
+    #[nexus_test(server = crate::Server)]
+    async fn test_metrics_match_activation(
+        cptestctx: &ControlPlaneTestContext,
+    ) {
+        let nexus = &cptestctx.server.server_context().nexus;
+        let datastore = nexus.datastore();
+        let opctx = OpContext::for_tests(
+            cptestctx.logctx.log.clone(),
+            datastore.clone(),
+        );
+
+        let starter = Arc::new(NoopStartSaga::new());
+        let (_tx, rx) = watch::channel(None);
+        let mut task = RegionReplacementDetector::new(
+            datastore.clone(),
+            starter.clone(),
+            &test_config(),
+            rx,
+        );
+        let mut producer =
+            task.metrics_producer(Uuid::new_v4(), Uuid::new_v4());
+
+        // Nothing is reported before the first activation
+        assert_eq!(producer.produce().unwrap().count(), 0);
+
+        for _ in 0..2 {
+            let request =
+                RegionReplacement::new(Uuid::new_v4(), Uuid::new_v4());
+            datastore
+                .insert_region_replacement_request(&opctx, request)
+                .await
+                .unwrap();
+        }
+
+        let result = activate(&mut task, &opctx).await;
+        let samples: BTreeMap<String, Datum> = producer
+            .produce()
+            .unwrap()
+            .map(|sample| {
+                (
+                    sample.metric_name().to_string(),
+                    sample.measurement.datum().clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            samples,
+            BTreeMap::from([
+                (String::from("regions_scanned"), Datum::U64(0)),
+                (String::from("requests_created"), Datum::U64(0)),
+                (
+                    String::from("sagas_started"),
+                    Datum::U64(
+                        result["region_replacement_started_ok"]
+                            .as_u64()
+                            .unwrap()
+                    ),
+                ),
+                (
+                    String::from("errors"),
+                    Datum::U64(
+                        result["region_replacement_started_err"]
+                            .as_u64()
+                            .unwrap()
+                    ),
+                ),
+            ]),
+        );
+        assert_eq!(samples["sagas_started"], Datum::U64(2));
+    }
+
+    #[nexus_test(server = crate::Server)]
+    async fn test_detection_modes(cptestctx: &ControlPlaneTestContext) {
+        let nexus = &cptestctx.server.server_context().nexus;
+        let datastore = nexus.datastore();
+        let opctx = OpContext::for_tests(
+            cptestctx.logctx.log.clone(),
+            datastore.clone(),
+        );
+
+        // Create three zpools, and allocate one region on each of them.
+        let disk_test = DiskTest::new(cptestctx).await;
+        let datasets_and_regions = datastore
+            .disk_region_allocate(
+                &opctx,
+                Uuid::new_v4(),
+                &params::DiskSource::Blank {
+                    block_size: params::BlockSize::try_from(512).unwrap(),
+                },
+                ByteCount::from_gibibytes_u32(1),
+                &RegionAllocationStrategy::Random { seed: None },
+            )
+            .await
+            .unwrap();
+        assert_eq!(datasets_and_regions.len(), 3);
+        let region_on_zpool = |zpool_id: Uuid| {
+            datasets_and_regions
+                .iter()
+                .find(|(dataset, _)| dataset.pool_id == zpool_id)
+                .map(|(_, region)| region.id())
+                .expect("expected a region on each zpool")
+        };
+
+        let mut zpools = disk_test.zpools();
+        let expunged_zpool = zpools.next().unwrap();
+        let missing_zpool = zpools.next().unwrap();
+        let healthy_zpool = zpools.next().unwrap();
+        let expunged_region =
+            region_on_zpool(expunged_zpool.id.into_untyped_uuid());
+        let missing_region =
+            region_on_zpool(missing_zpool.id.into_untyped_uuid());
+
+        // Expunge the physical disk backing the first zpool.
+        let (_, db_zpool) = LookupPath::new(&opctx, datastore)
+            .zpool_id(expunged_zpool.id.into_untyped_uuid())
+            .fetch()
+            .await
+            .unwrap();
+        datastore
+            .physical_disk_update_policy(
+                &opctx,
+                db_zpool.physical_disk_id,
+                PhysicalDiskPolicy::Expunged,
+            )
+            .await
+            .unwrap();
+
+        // Record an inventory collection in which the sled reports every
+        // zpool except the second one.
+        let mut builder = CollectionBuilder::new("test");
+        builder
+            .found_sled_inventory(
+                "test",
+                nexus_inventory::examples::sled_agent(
+                    SLED_AGENT_UUID.parse::<SledUuid>().unwrap(),
+                    Baseboard::new_pc("test".to_string(), "test".to_string()),
+                    SledRole::Gimlet,
+                    vec![],
+                    [expunged_zpool, healthy_zpool]
+                        .into_iter()
+                        .map(|zpool| InventoryZpool {
+                            id: zpool.id,
+                            total_size: zpool.size,
+                        })
+                        .collect(),
+                ),
+            )
+            .unwrap();
+        let collection = builder.build();
+        datastore
+            .inventory_insert_collection(&opctx, &collection)
+            .await
+            .unwrap();
+
+        let starter = Arc::new(NoopStartSaga::new());
+        let (_tx, rx) = watch::channel(Some(collection.id));
+        let find = |detection_mode| {
+            let task = RegionReplacementDetector::new(
+                datastore.clone(),
+                starter.clone(),
+                &RegionReplacementConfig { detection_mode, ..test_config() },
+                rx.clone(),
+            );
+            let opctx = &opctx;
+            async move {
+                task.find_regions_to_replace(opctx)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|region| region.id())
+                    .collect::<BTreeSet<_>>()
+            }
+        };
+
+        assert_eq!(
+            find(RegionReplacementDetectionMode::ExpungedDisks).await,
+            BTreeSet::from([expunged_region]),
+        );
+        assert_eq!(
+            find(RegionReplacementDetectionMode::MissingFromInventory).await,
+            BTreeSet::from([missing_region]),
+        );
+        assert_eq!(
+            find(RegionReplacementDetectionMode::All).await,
+            BTreeSet::from([expunged_region, missing_region]),
+        );
+    }
+
+    #[nexus_test(server = crate::Server)]
+    async fn test_max_in_flight_requests(cptestctx: &ControlPlaneTestContext) {
+        let nexus = &cptestctx.server.server_context().nexus;
+        let datastore = nexus.datastore();
+        let opctx = OpContext::for_tests(
+            cptestctx.logctx.log.clone(),
+            datastore.clone(),
+        );
+
+        // Allocate a region on each zpool, then expunge the physical disk
+        // backing one of them so that its region needs replacing.
+        let disk_test = DiskTest::new(cptestctx).await;
+        let datasets_and_regions = datastore
+            .disk_region_allocate(
+                &opctx,
+                Uuid::new_v4(),
+                &params::DiskSource::Blank {
+                    block_size: params::BlockSize::try_from(512).unwrap(),
+                },
+                ByteCount::from_gibibytes_u32(1),
+                &RegionAllocationStrategy::Random { seed: None },
+            )
+            .await
+            .unwrap();
+        let expunged_zpool = disk_test.zpools().next().unwrap();
+        let expunged_region = datasets_and_regions
+            .iter()
+            .find(|(dataset, _)| {
+                dataset.pool_id == expunged_zpool.id.into_untyped_uuid()
+            })
+            .map(|(_, region)| region.id())
+            .unwrap();
+
+        let (_, db_zpool) = LookupPath::new(&opctx, datastore)
+            .zpool_id(expunged_zpool.id.into_untyped_uuid())
+            .fetch()
+            .await
+            .unwrap();
+        datastore
+            .physical_disk_update_policy(
+                &opctx,
+                db_zpool.physical_disk_id,
+                PhysicalDiskPolicy::Expunged,
+            )
+            .await
+            .unwrap();
+
+        // Fill up the pipeline with requests for fake regions.
+        const MAX_IN_FLIGHT: usize = 4;
+        for _ in 0..MAX_IN_FLIGHT {
+            let request =
+                RegionReplacement::new(Uuid::new_v4(), Uuid::new_v4());
+            datastore
+                .insert_region_replacement_request(&opctx, request)
+                .await
+                .unwrap();
+        }
+
+        let starter = Arc::new(NoopStartSaga::new());
+        let (_tx, rx) = watch::channel(None);
+        let mut task = RegionReplacementDetector::new(
+            datastore.clone(),
+            starter.clone(),
+            &RegionReplacementConfig {
+                max_in_flight_requests: Some(MAX_IN_FLIGHT),
+                ..test_config()
+            },
+            rx,
+        );
+
+        // At the cap, no request is created for the expunged region, but the
+        // existing requests are still started.
+        let result = activate(&mut task, &opctx).await;
+        assert_eq!(
+            result,
+            json!({
+                "region_replacement_started_ok": MAX_IN_FLIGHT,
+                "region_replacement_started_err": 0,
+                "region_replacement_deferred": 1,
+                "region_replacement_cooldown_deferred": 0,
+                "region_replacement_start_deferred": 0,
+                "region_replacement_prepare_skipped": 0,
+                "region_replacement_errors": [],
+                "region_replacement_errors_dropped": 0,
+            })
+        );
+        assert_eq!(starter.count_reset(), MAX_IN_FLIGHT as u64);
+        assert!(datastore
+            .lookup_region_replacement_request_by_old_region_id(
+                &opctx,
+                TypedUuid::from_untyped_uuid(expunged_region),
+            )
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            datastore
+                .count_in_flight_region_replacements(&opctx)
+                .await
+                .unwrap(),
+            MAX_IN_FLIGHT,
+        );
+
+        // The expunged region is still examined on every activation.
         let result = task.activate(&opctx).await;
+        assert_eq!(result["region_replacement_scan"]["regions_scanned"], 1);
+    }
+
+    #[nexus_test(server = crate::Server)]
+    async fn test_start_cooldown(cptestctx: &ControlPlaneTestContext) {
+        let nexus = &cptestctx.server.server_context().nexus;
+        let datastore = nexus.datastore();
+        let opctx = OpContext::for_tests(
+            cptestctx.logctx.log.clone(),
+            datastore.clone(),
+        );
+
+        // Allocate a region on each zpool, then expunge the physical disk
+        // backing one of them so that its region needs replacing.
+        let disk_test = DiskTest::new(cptestctx).await;
+        datastore
+            .disk_region_allocate(
+                &opctx,
+                Uuid::new_v4(),
+                &params::DiskSource::Blank {
+                    block_size: params::BlockSize::try_from(512).unwrap(),
+                },
+                ByteCount::from_gibibytes_u32(1),
+                &RegionAllocationStrategy::Random { seed: None },
+            )
+            .await
+            .unwrap();
+        let expunged_zpool = disk_test.zpools().next().unwrap();
+        let (_, db_zpool) = LookupPath::new(&opctx, datastore)
+            .zpool_id(expunged_zpool.id.into_untyped_uuid())
+            .fetch()
+            .await
+            .unwrap();
+        datastore
+            .physical_disk_update_policy(
+                &opctx,
+                db_zpool.physical_disk_id,
+                PhysicalDiskPolicy::Expunged,
+            )
+            .await
+            .unwrap();
+
+        let starter = Arc::new(NoopStartSaga::new());
+        let (_tx, rx) = watch::channel(None);
+        let mut task = RegionReplacementDetector::new(
+            datastore.clone(),
+            starter.clone(),
+            &RegionReplacementConfig { start_cooldown: true, ..test_config() },
+            rx,
+        );
+
+        // The first activation creates a request for the expunged region but
+        // does not start it.
+        let result = activate(&mut task, &opctx).await;
+        assert_eq!(
+            result,
+            json!({
+                "region_replacement_started_ok": 0,
+                "region_replacement_started_err": 0,
+                "region_replacement_deferred": 0,
+                "region_replacement_cooldown_deferred": 1,
+                "region_replacement_start_deferred": 0,
+                "region_replacement_prepare_skipped": 0,
+                "region_replacement_errors": [],
+                "region_replacement_errors_dropped": 0,
+            })
+        );
+        assert_eq!(starter.count_reset(), 0);
+        assert_eq!(
+            datastore
+                .count_in_flight_region_replacements(&opctx)
+                .await
+                .unwrap(),
+            1,
+        );
+
+        // The next activation starts it.
+        let result = activate(&mut task, &opctx).await;
         assert_eq!(
             result,
             json!({
                 "region_replacement_started_ok": 1,
                 "region_replacement_started_err": 0,
+                "region_replacement_deferred": 0,
+                "region_replacement_cooldown_deferred": 0,
+                "region_replacement_start_deferred": 0,
+                "region_replacement_prepare_skipped": 0,
+                "region_replacement_errors": [],
+                "region_replacement_errors_dropped": 0,
             })
         );
+        assert_eq!(starter.count_reset(), 1);
+    }
+
+    #[nexus_test(server = crate::Server)]
+    async fn test_max_starts_per_activation(
+        cptestctx: &ControlPlaneTestContext,
+    ) {
+        let nexus = &cptestctx.server.server_context().nexus;
+        let datastore = nexus.datastore();
+        let opctx = OpContext::for_tests(
+            cptestctx.logctx.log.clone(),
+            datastore.clone(),
+        );
+
+        const MAX_STARTS: usize = 2;
+        for _ in 0..MAX_STARTS + 1 {
+            let request =
+                RegionReplacement::new(Uuid::new_v4(), Uuid::new_v4());
+            datastore
+                .insert_region_replacement_request(&opctx, request)
+                .await
+                .unwrap();
+        }
+
+        let starter = Arc::new(NoopStartSaga::new());
+        let (_tx, rx) = watch::channel(None);
+        let mut task = RegionReplacementDetector::new(
+            datastore.clone(),
+            starter.clone(),
+            &RegionReplacementConfig {
+                max_starts_per_activation: Some(MAX_STARTS),
+                ..test_config()
+            },
+            rx,
+        );
+
+        // Only the first `MAX_STARTS` requests are started; the rest are left
+        // for a later activation.
+        let result = activate(&mut task, &opctx).await;
+        assert_eq!(
+            result,
+            json!({
+                "region_replacement_started_ok": MAX_STARTS,
+                "region_replacement_started_err": 0,
+                "region_replacement_deferred": 0,
+                "region_replacement_cooldown_deferred": 0,
+                "region_replacement_start_deferred": 1,
+                "region_replacement_prepare_skipped": 0,
+                "region_replacement_errors": [],
+                "region_replacement_errors_dropped": 0,
+            })
+        );
+        assert_eq!(starter.count_reset(), MAX_STARTS as u64);
+    }
+
+    #[nexus_test(server = crate::Server)]
+    async fn test_allocation_strategy_passed_to_saga(
+        cptestctx: &ControlPlaneTestContext,
+    ) {
+        let nexus = &cptestctx.server.server_context().nexus;
+        let datastore = nexus.datastore();
+        let opctx = OpContext::for_tests(
+            cptestctx.logctx.log.clone(),
+            datastore.clone(),
+        );
+
+        const STRATEGY: RegionAllocationStrategy =
+            RegionAllocationStrategy::RandomWithDistinctSleds { seed: Some(1) };
+
+        let starter = Arc::new(NoopStartSaga::new());
+        let (_tx, rx) = watch::channel(None);
+        let mut task = RegionReplacementDetector::new(
+            datastore.clone(),
+            starter.clone(),
+            &RegionReplacementConfig {
+                allocation_strategy: STRATEGY,
+                ..test_config()
+            },
+            rx,
+        );
+        task.prepare_start_saga = |params| {
+            if params.allocation_strategy != STRATEGY {
+                return Err(
+                    omicron_common::api::external::Error::internal_error(
+                        "unexpected allocation strategy",
+                    ),
+                );
+            }
+            SagaRegionReplacementStart::prepare(params)
+        };
+
+        let request = RegionReplacement::new(Uuid::new_v4(), Uuid::new_v4());
+        datastore
+            .insert_region_replacement_request(&opctx, request)
+            .await
+            .unwrap();
 
+        let result = activate(&mut task, &opctx).await;
+        assert_eq!(result["region_replacement_started_ok"], 1);
+        assert_eq!(result["region_replacement_started_err"], 0);
         assert_eq!(starter.count_reset(), 1);
     }
 }
@@ -784,6 +784,10 @@ impl super::Nexus {
         &self,
         opctx: &OpContext,
     ) -> ListResultVec<UninitializedSled> {
+        // Check access to the Fleet first.  The inventory is hidden from
+        // actors that can't read it, but this endpoint itself is not.
+        opctx.authorize(authz::Action::Read, &authz::FLEET).await?;
+
         debug!(self.log, "Getting latest collection");
         // Grab the SPs from the last collection
         let collection =
@@ -842,6 +846,10 @@ impl super::Nexus {
         opctx: &OpContext,
         sled: UninitializedSledId,
     ) -> Result<SledUuid, Error> {
+        // As in `sled_list_uninitialized()`, check access to the Fleet before
+        // we go looking in the (hidden) inventory.
+        opctx.authorize(authz::Action::Read, &authz::FLEET).await?;
+
         let baseboard_id = sled.clone().into();
         let hw_baseboard_id = self
             .db_datastore
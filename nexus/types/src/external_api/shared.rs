@@ -133,6 +133,8 @@ pub enum SiloRole {
     EnumIter,
     Eq,
     FromStr,
+    Ord,
+    PartialOrd,
     PartialEq,
     Serialize,
     JsonSchema,
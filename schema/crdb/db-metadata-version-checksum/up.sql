@@ -0,0 +1,2 @@
+ALTER TABLE omicron.public.db_metadata
+    ADD COLUMN IF NOT EXISTS version_checksum STRING(64);
@@ -724,7 +724,10 @@ CREATE TABLE IF NOT EXISTS omicron.public.silo (
     mapped_fleet_roles JSONB NOT NULL,
 
     /* child resource generation number, per RFD 192 */
-    rcgen INT NOT NULL
+    rcgen INT NOT NULL,
+
+    /* Silo roles that confer Project roles on every Project in the Silo */
+    mapped_project_roles JSONB NOT NULL
 );
 
 CREATE UNIQUE INDEX IF NOT EXISTS lookup_silo_by_name ON omicron.public.silo (
@@ -4108,6 +4111,11 @@ CREATE TABLE IF NOT EXISTS omicron.public.db_metadata (
     -- (Optional) Semver representation of the DB version to which we're upgrading
     target_version STRING(64),
 
+    -- (Optional) Hex-encoded SHA-256 digest of the SQL that was applied to
+    -- reach "version". This is NULL for databases that were initialized
+    -- directly at "version" rather than upgraded to it.
+    version_checksum STRING(64),
+
     CHECK (singleton = true)
 );
 
@@ -4225,7 +4233,7 @@ INSERT INTO omicron.public.db_metadata (
     version,
     target_version
 ) VALUES
-    (TRUE, NOW(), NOW(), '93.0.0', NULL)
+    (TRUE, NOW(), NOW(), '95.0.0', NULL)
 ON CONFLICT DO NOTHING;
 
 COMMIT;
@@ -0,0 +1,3 @@
+ALTER TABLE omicron.public.silo
+  ADD COLUMN IF NOT EXISTS mapped_project_roles JSONB NOT NULL
+  DEFAULT '{}';
@@ -0,0 +1,2 @@
+ALTER TABLE omicron.public.silo
+  ALTER COLUMN mapped_project_roles DROP DEFAULT;
@@ -12,6 +12,7 @@ use illumos_utils::dladm::FindPhysicalLinkError;
 use illumos_utils::dladm::GetLinkpropError;
 use illumos_utils::dladm::PhysicalLink;
 use illumos_utils::dladm::SetLinkpropError;
+use illumos_utils::dladm::SetMtuError;
 use illumos_utils::dladm::CHELSIO_LINK_PREFIX;
 use illumos_utils::zone::Zones;
 
@@ -36,6 +37,9 @@ pub enum Error {
 
     #[error("Could not get linkprop: {0}")]
     GetLinkprop(#[from] GetLinkpropError),
+
+    #[error("Could not set MTU: {0}")]
+    SetMtu(#[from] SetMtuError),
 }
 
 /// Convenience function that calls
@@ -48,12 +52,12 @@ pub fn find_nics(
 
     // Before these links have any consumers (eg. IP interfaces), set the MTU.
     // If we have previously set the MTU, do not attempt to re-set.
-    const MTU: &str = "9000";
+    const MTU: usize = 9000;
     for link in &underlay_nics {
-        let existing_mtu = Dladm::get_linkprop(&link.to_string(), "mtu")?;
+        let existing_mtu = Dladm::get_mtu(&link.to_string())?;
 
         if existing_mtu != MTU {
-            Dladm::set_linkprop(&link.to_string(), "mtu", MTU)?;
+            Dladm::set_mtu(&link.to_string(), MTU)?;
         }
     }
 
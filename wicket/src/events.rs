@@ -1,7 +1,8 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
-use crate::{keymap::Cmd, state::ComponentId, State};
+use crate::state::{ComponentId, WicketdPoller};
+use crate::{keymap::Cmd, State};
 use camino::Utf8PathBuf;
 use humantime::format_rfc3339;
 use serde::{Deserialize, Serialize};
@@ -29,6 +30,10 @@ pub enum Event {
     /// An Inventory Update Event
     Inventory { inventory: RackV1Inventory, mgs_last_seen: Duration },
 
+    /// wicketd reported that the inventory hasn't changed since the last
+    /// `Inventory` event, which is still `mgs_last_seen` old
+    InventoryUnchanged { mgs_last_seen: Duration },
+
     /// TUF repo artifacts unpacked by wicketd, and event reports
     ArtifactsAndEventReports {
         system_version: Option<SemverVersion>,
@@ -45,6 +50,16 @@ pub enum Event {
     /// The location within the rack where wicketd is running.
     WicketdLocation(GetLocationResponse),
 
+    /// A request to wicketd has failed `consecutive` times in a row.
+    WicketdPollFailed {
+        poller: WicketdPoller,
+        consecutive: usize,
+        last_error: String,
+    },
+
+    /// A request to wicketd that was reported as failing has succeeded.
+    WicketdPollRecovered(WicketdPoller),
+
     /// The tick of a Timer
     /// This can be used to draw a frame to the terminal
     Tick,
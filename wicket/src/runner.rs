@@ -29,7 +29,7 @@ use crate::helpers::get_update_test_error;
 use crate::state::CreateClearUpdateStateOptions;
 use crate::state::CreateStartUpdateOptions;
 use crate::ui::Screen;
-use crate::wicketd::{self, WicketdHandle, WicketdManager};
+use crate::wicketd::{self, PollConfig, WicketdHandle, WicketdManager};
 use crate::{Action, Cmd, Event, KeyHandler, Recorder, State, TICK_INTERVAL};
 
 // We can avoid a bunch of unnecessary type parameters by picking them ahead of time.
@@ -128,6 +128,11 @@ impl RunnerCore {
                 self.state.inventory.update_inventory(inventory)?;
                 self.screen.draw(&self.state, &mut self.terminal)?;
             }
+            Event::InventoryUnchanged { mgs_last_seen } => {
+                // Only the status timers move; the next tick redraws them.
+                self.state.service_status.reset_mgs(mgs_last_seen);
+                self.state.service_status.reset_wicketd(Duration::ZERO);
+            }
             Event::ArtifactsAndEventReports {
                 system_version,
                 artifacts,
@@ -155,6 +160,18 @@ impl RunnerCore {
                 self.state.wicketd_location = location;
                 self.screen.draw(&self.state, &mut self.terminal)?;
             }
+            Event::WicketdPollFailed { poller, consecutive, last_error } => {
+                self.state.service_status.poll_failed(
+                    poller,
+                    consecutive,
+                    last_error,
+                );
+                self.screen.draw(&self.state, &mut self.terminal)?;
+            }
+            Event::WicketdPollRecovered(poller) => {
+                self.state.service_status.poll_recovered(poller);
+                self.screen.draw(&self.state, &mut self.terminal)?;
+            }
             Event::Shutdown => return Ok(true),
         }
         Ok(false)
@@ -192,7 +209,10 @@ impl RunnerCore {
                     .to_start_update_options()?;
 
                     wicketd.tx.blocking_send(
-                        wicketd::Request::StartUpdate { component_id, options },
+                        wicketd::Request::StartUpdate {
+                            component_ids: vec![component_id],
+                            options,
+                        },
                     )?;
                 }
             }
@@ -288,8 +308,12 @@ impl Runner {
             .enable_all()
             .build()
             .unwrap();
-        let (wicketd, wicketd_manager) =
-            WicketdManager::new(&log, events_tx.clone(), wicketd_addr);
+        let (wicketd, wicketd_manager) = WicketdManager::new(
+            &log,
+            events_tx.clone(),
+            wicketd_addr,
+            PollConfig::default(),
+        );
         let core = RunnerCore::new(log);
         Runner {
             core,
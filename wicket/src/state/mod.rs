@@ -15,7 +15,7 @@ pub use inventory::{
     Component, ComponentId, Inventory, ParsableComponentId, ALL_COMPONENT_IDS,
 };
 pub use rack::{KnightRiderMode, RackState};
-pub use status::ServiceStatus;
+pub use status::{ServiceStatus, WicketdPoller};
 pub use update::{
     parse_event_report_map, update_component_title,
     CreateClearUpdateStateOptions, CreateStartUpdateOptions, RackUpdateState,
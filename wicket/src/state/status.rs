@@ -11,6 +11,8 @@ use crate::ui::defaults::style;
 use ratatui::style::Style;
 use ratatui::text::Span;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::time::Duration;
 
 // This should be greater than the highest poll value for each service
@@ -21,6 +23,39 @@ const LIVENESS_THRESHOLD: Duration = Duration::from_secs(30);
 pub struct ServiceStatus {
     wicketd_last_seen: Option<Duration>,
     mgs_last_seen: Option<Duration>,
+    failing_polls: BTreeMap<WicketdPoller, PollFailure>,
+}
+
+/// A periodic request that wicket makes to wicketd
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum WicketdPoller {
+    Inventory,
+    ArtifactsAndEventReports,
+    RackSetupConfig,
+    RackSetupStatus,
+    Location,
+}
+
+impl fmt::Display for WicketdPoller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            WicketdPoller::Inventory => "inventory",
+            WicketdPoller::ArtifactsAndEventReports => "artifacts",
+            WicketdPoller::RackSetupConfig => "rack setup config",
+            WicketdPoller::RackSetupStatus => "rack setup status",
+            WicketdPoller::Location => "location",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The most recent run of failures for a [`WicketdPoller`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollFailure {
+    pub consecutive: usize,
+    pub last_error: String,
 }
 
 impl ServiceStatus {
@@ -56,6 +91,27 @@ impl ServiceStatus {
         self.mgs_last_seen = Some(elapsed);
     }
 
+    pub fn poll_failed(
+        &mut self,
+        poller: WicketdPoller,
+        consecutive: usize,
+        last_error: String,
+    ) {
+        self.failing_polls
+            .insert(poller, PollFailure { consecutive, last_error });
+    }
+
+    pub fn poll_recovered(&mut self, poller: WicketdPoller) {
+        self.failing_polls.remove(&poller);
+    }
+
+    /// Return every poller that is currently failing repeatedly
+    pub fn failing_polls(
+        &self,
+    ) -> impl Iterator<Item = (&WicketdPoller, &PollFailure)> {
+        self.failing_polls.iter()
+    }
+
     pub fn mgs_liveness(&self) -> Liveness {
         Self::liveness(self.mgs_last_seen)
     }
@@ -202,6 +202,22 @@ impl MainScreen {
         spans.push(Span::styled(" | ", style::divider()));
         spans.push(Span::styled("WICKETD: ", style::service()));
         spans.extend_from_slice(&wicketd_spans);
+        let failing_polls: Vec<_> = state
+            .service_status
+            .failing_polls()
+            .map(|(poller, failure)| {
+                format!(
+                    "{poller} ({}x: {})",
+                    failure.consecutive, failure.last_error
+                )
+            })
+            .collect();
+        if !failing_polls.is_empty() {
+            spans.push(Span::styled(
+                format!(" LOST CONTACT: {}", failing_polls.join(", ")),
+                style::delayed(),
+            ));
+        }
         spans.push(Span::styled(" | ", style::divider()));
         spans.push(Span::styled("MGS: ", style::service()));
         spans.extend_from_slice(&mgs_spans);
@@ -4,6 +4,8 @@
 
 //! Code for talking to wicketd
 
+use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
 use slog::{o, warn, Logger};
 use std::convert::From;
 use std::net::SocketAddrV6;
@@ -21,7 +23,7 @@ use wicketd_client::types::{
 
 use crate::events::EventReportMap;
 use crate::keymap::ShowPopupCmd;
-use crate::state::ComponentId;
+use crate::state::{ComponentId, WicketdPoller};
 use crate::{Cmd, Event};
 
 impl From<ComponentId> for SpIdentifier {
@@ -42,17 +44,161 @@ impl From<ComponentId> for SpIdentifier {
 
 const WICKETD_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
+// The number of consecutive failures of a poll before the UI is told that we
+// may have lost contact with wicketd
+const POLL_FAILURE_THRESHOLD: usize = 3;
+
+/// How often [`WicketdManager`] polls wicketd, and how long it waits for each
+/// request
+#[derive(Debug, Clone, Copy)]
+pub struct PollConfig {
+    /// Interval between inventory polls
+    pub inventory_interval: Duration,
+    /// Interval between polls of the artifacts and update event reports
+    pub artifact_interval: Duration,
+    /// Interval between polls of the rack setup config, rack setup status
+    /// and wicketd's location
+    pub rack_setup_interval: Duration,
+    /// Timeout for each request to wicketd
+    pub timeout: Duration,
+}
+
+impl Default for PollConfig {
+    fn default() -> Self {
+        PollConfig {
+            inventory_interval: WICKETD_POLL_INTERVAL,
+            artifact_interval: WICKETD_POLL_INTERVAL * 2,
+            rack_setup_interval: WICKETD_POLL_INTERVAL * 2,
+            timeout: WICKETD_TIMEOUT,
+        }
+    }
+}
+
 // Assume that these requests are periodic on the order of seconds or the
 // result of human interaction. In either case, this buffer should be plenty
 // large.
 const CHANNEL_CAPACITY: usize = 1000;
 
+/// Counts consecutive failures of a single poller, and tells the UI when the
+/// poller starts failing repeatedly and when it recovers
+struct PollFailureTracker {
+    poller: WicketdPoller,
+    consecutive: usize,
+    tx: UnboundedSender<Event>,
+}
+
+impl PollFailureTracker {
+    fn new(poller: WicketdPoller, tx: UnboundedSender<Event>) -> Self {
+        PollFailureTracker { poller, consecutive: 0, tx }
+    }
+
+    fn on_success(&mut self) {
+        if self.consecutive >= POLL_FAILURE_THRESHOLD {
+            let _ = self.tx.send(Event::WicketdPollRecovered(self.poller));
+        }
+        self.consecutive = 0;
+    }
+
+    fn on_failure(&mut self, last_error: String) {
+        self.consecutive += 1;
+        if self.consecutive >= POLL_FAILURE_THRESHOLD {
+            let _ = self.tx.send(Event::WicketdPollFailed {
+                poller: self.poller,
+                consecutive: self.consecutive,
+                last_error,
+            });
+        }
+    }
+}
+
+/// A wicketd client for one poller that only gets a response body back when
+/// it has changed since the last one
+///
+/// wicketd tags polled responses with an `ETag`, and answers `304 Not
+/// Modified` when a request's `If-None-Match` names the current tag.  The
+/// generated client can't add a header to a single request, so instead this
+/// rebuilds it with a default `If-None-Match` header whenever the tag changes.
+struct ConditionalClient {
+    log: Logger,
+    wicketd_addr: SocketAddrV6,
+    timeout: Duration,
+    client: wicketd_client::Client,
+    etag: Option<HeaderValue>,
+}
+
+impl ConditionalClient {
+    fn new(
+        log: &Logger,
+        wicketd_addr: SocketAddrV6,
+        timeout: Duration,
+    ) -> Self {
+        ConditionalClient {
+            log: log.clone(),
+            wicketd_addr,
+            timeout,
+            client: create_wicketd_client(log, wicketd_addr, timeout),
+            etag: None,
+        }
+    }
+
+    fn client(&self) -> &wicketd_client::Client {
+        &self.client
+    }
+
+    /// Returns the body of a response made with [`Self::client`], or `None`
+    /// if wicketd reported that it hasn't changed
+    fn check<T>(
+        &mut self,
+        result: Result<
+            wicketd_client::ResponseValue<T>,
+            wicketd_client::Error<wicketd_client::types::Error>,
+        >,
+    ) -> Result<Option<T>, wicketd_client::Error<wicketd_client::types::Error>>
+    {
+        match result {
+            Ok(response) => {
+                let etag = response.headers().get(ETAG).cloned();
+                self.set_etag(etag);
+                Ok(Some(response.into_inner()))
+            }
+            Err(wicketd_client::Error::UnexpectedResponse(response))
+                if response.status() == StatusCode::NOT_MODIFIED =>
+            {
+                Ok(None)
+            }
+            Err(err) => {
+                // The caller reports errors too, so what it shows may no
+                // longer match the last body.  Make sure the next successful
+                // poll comes back with one.
+                self.set_etag(None);
+                Err(err)
+            }
+        }
+    }
+
+    fn set_etag(&mut self, etag: Option<HeaderValue>) {
+        if etag != self.etag {
+            self.client = create_conditional_wicketd_client(
+                &self.log,
+                self.wicketd_addr,
+                self.timeout,
+                etag.as_ref(),
+            );
+            self.etag = etag;
+        }
+    }
+}
+
 /// Requests driven by the UI and sent from [`crate::Runner`] to [`WicketdManager`]
 #[allow(unused)]
 #[derive(Debug)]
 pub enum Request {
+    /// Start updating each of `component_ids`
+    ///
+    /// A separate start-update request is made for each component, so a
+    /// failure to start one does not prevent the rest from starting.
     StartUpdate {
-        component_id: ComponentId,
+        component_ids: Vec<ComponentId>,
         options: StartUpdateOptions,
     },
     AbortUpdate {
@@ -79,6 +225,7 @@ pub struct WicketdManager {
     rx: mpsc::Receiver<Request>,
     events_tx: UnboundedSender<Event>,
     wicketd_addr: SocketAddrV6,
+    config: PollConfig,
 }
 
 impl WicketdManager {
@@ -86,11 +233,13 @@ impl WicketdManager {
         log: &Logger,
         events_tx: UnboundedSender<Event>,
         wicketd_addr: SocketAddrV6,
+        config: PollConfig,
     ) -> (WicketdHandle, WicketdManager) {
         let log = log.new(o!("component" => "WicketdManager"));
         let (tx, rx) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
         let handle = WicketdHandle { tx };
-        let manager = WicketdManager { log, rx, events_tx, wicketd_addr };
+        let manager =
+            WicketdManager { log, rx, events_tx, wicketd_addr, config };
 
         (handle, manager)
     }
@@ -121,8 +270,8 @@ impl WicketdManager {
                 Some(request) = self.rx.recv() => {
                     slog::info!(self.log, "Got wicketd req: {:?}", request);
                     match request {
-                        Request::StartUpdate { component_id, options } => {
-                            self.start_update(component_id, options);
+                        Request::StartUpdate { component_ids, options } => {
+                            self.start_update(component_ids, options);
                         }
                         Request::AbortUpdate { component_id, options } => {
                             self.abort_update(component_id, options);
@@ -155,34 +304,40 @@ impl WicketdManager {
 
     fn start_update(
         &self,
-        component_id: ComponentId,
+        component_ids: Vec<ComponentId>,
         options: StartUpdateOptions,
     ) {
         let log = self.log.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         let events_tx = self.events_tx.clone();
         tokio::spawn(async move {
             let update_client =
-                create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
-            let params = StartUpdateParams {
-                targets: vec![component_id.into()],
-                options,
-            };
-            let response = match update_client.post_start_update(&params).await
-            {
-                Ok(_) => Ok(()),
-                Err(error) => Err(error.to_string()),
-            };
-
-            slog::info!(
-                log,
-                "Update response for {}: {:?}",
-                component_id,
-                response
-            );
-            _ = events_tx.send(Event::Term(Cmd::ShowPopup(
-                ShowPopupCmd::StartUpdateResponse { component_id, response },
-            )));
+                create_wicketd_client(&log, addr, config.timeout);
+            for component_id in component_ids {
+                let params = StartUpdateParams {
+                    targets: vec![component_id.into()],
+                    options: options.clone(),
+                };
+                let response =
+                    match update_client.post_start_update(&params).await {
+                        Ok(_) => Ok(()),
+                        Err(error) => Err(error.to_string()),
+                    };
+
+                slog::info!(
+                    log,
+                    "Update response for {}: {:?}",
+                    component_id,
+                    response
+                );
+                _ = events_tx.send(Event::Term(Cmd::ShowPopup(
+                    ShowPopupCmd::StartUpdateResponse {
+                        component_id,
+                        response,
+                    },
+                )));
+            }
         });
     }
 
@@ -193,10 +348,11 @@ impl WicketdManager {
     ) {
         let log = self.log.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         let events_tx = self.events_tx.clone();
         tokio::spawn(async move {
             let update_client =
-                create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
+                create_wicketd_client(&log, addr, config.timeout);
             let sp: SpIdentifier = component_id.into();
             let response = match update_client
                 .post_abort_update(&sp.type_, sp.slot, &options)
@@ -225,10 +381,11 @@ impl WicketdManager {
     ) {
         let log = self.log.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         let events_tx = self.events_tx.clone();
         tokio::spawn(async move {
             let update_client =
-                create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
+                create_wicketd_client(&log, addr, config.timeout);
             let params = ClearUpdateStateParams {
                 targets: vec![component_id.into()],
                 options,
@@ -262,8 +419,9 @@ impl WicketdManager {
     ) {
         let log = self.log.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         tokio::spawn(async move {
-            let client = create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
+            let client = create_wicketd_client(&log, addr, config.timeout);
             let sp: SpIdentifier = component_id.into();
             let res =
                 client.post_ignition_command(&sp.type_, sp.slot, command).await;
@@ -286,9 +444,10 @@ impl WicketdManager {
     fn start_rack_initialization(&self) {
         let log = self.log.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         let events_tx = self.events_tx.clone();
         tokio::spawn(async move {
-            let client = create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
+            let client = create_wicketd_client(&log, addr, config.timeout);
             let response = match client.post_run_rack_setup().await {
                 Ok(_) => Ok(()),
                 Err(error) => Err(error.to_string()),
@@ -304,9 +463,10 @@ impl WicketdManager {
     fn start_rack_reset(&self) {
         let log = self.log.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         let events_tx = self.events_tx.clone();
         tokio::spawn(async move {
-            let client = create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
+            let client = create_wicketd_client(&log, addr, config.timeout);
             let response = match client.post_run_rack_reset().await {
                 Ok(_) => Ok(()),
                 Err(error) => Err(error.to_string()),
@@ -323,17 +483,33 @@ impl WicketdManager {
         let log = self.log.clone();
         let tx = self.events_tx.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         tokio::spawn(async move {
-            let client = create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
-            let mut ticker = interval(WICKETD_POLL_INTERVAL * 2);
+            let mut client = ConditionalClient::new(&log, addr, config.timeout);
+            let mut failures = PollFailureTracker::new(
+                WicketdPoller::RackSetupStatus,
+                tx.clone(),
+            );
+            let mut ticker = interval(config.rack_setup_interval);
             let mut prev = None;
             ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
             loop {
                 ticker.tick().await;
-                // TODO: We should really be using ETAGs here
-                let result = match client.get_rack_setup_state().await {
-                    Ok(val) => Ok(val.into_inner()),
-                    Err(err) => Err(format!("{err:#}")),
+                let result = client.client().get_rack_setup_state().await;
+                let result = match client.check(result) {
+                    Ok(Some(val)) => {
+                        failures.on_success();
+                        Ok(val)
+                    }
+                    Ok(None) => {
+                        failures.on_success();
+                        continue;
+                    }
+                    Err(err) => {
+                        let err = format!("{err:#}");
+                        failures.on_failure(err.clone());
+                        Err(err)
+                    }
                 };
                 // Only send a new event if the config has changed
                 if Some(&result) == prev.as_ref() {
@@ -349,22 +525,33 @@ impl WicketdManager {
         let log = self.log.clone();
         let tx = self.events_tx.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         tokio::spawn(async move {
-            let client = create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
-            let mut ticker = interval(WICKETD_POLL_INTERVAL * 2);
+            let mut client = ConditionalClient::new(&log, addr, config.timeout);
+            let mut failures =
+                PollFailureTracker::new(WicketdPoller::Location, tx.clone());
+            let mut ticker = interval(config.rack_setup_interval);
             let mut prev = None;
             ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
             loop {
                 ticker.tick().await;
-                // TODO: We should really be using ETAGs here
-                let location = match client.get_location().await {
-                    Ok(val) => val.into_inner(),
+                let result = client.client().get_location().await;
+                let location = match client.check(result) {
+                    Ok(Some(val)) => {
+                        failures.on_success();
+                        val
+                    }
+                    Ok(None) => {
+                        failures.on_success();
+                        continue;
+                    }
                     Err(err) => {
                         warn!(
                             log,
                             "Failed to fetch location of wicketd";
                             "err" => #%err,
                         );
+                        failures.on_failure(format!("{err:#}"));
                         continue;
                     }
                 };
@@ -404,17 +591,22 @@ impl WicketdManager {
         let log = self.log.clone();
         let tx = self.events_tx.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         tokio::spawn(async move {
-            let client = create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
-            let mut ticker = interval(WICKETD_POLL_INTERVAL * 2);
+            let mut client = ConditionalClient::new(&log, addr, config.timeout);
+            let mut failures = PollFailureTracker::new(
+                WicketdPoller::RackSetupConfig,
+                tx.clone(),
+            );
+            let mut ticker = interval(config.rack_setup_interval);
             let mut prev = None;
             ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
             loop {
                 ticker.tick().await;
-                // TODO: We should really be using ETAGs here
-                match client.get_rss_config().await {
-                    Ok(val) => {
-                        let rsp = val.into_inner();
+                let result = client.client().get_rss_config().await;
+                match client.check(result) {
+                    Ok(Some(rsp)) => {
+                        failures.on_success();
                         // Only send a new event if the config has changed
                         if Some(&rsp) == prev.as_ref() {
                             continue;
@@ -422,11 +614,13 @@ impl WicketdManager {
                         prev = Some(rsp.clone());
                         let _ = tx.send(Event::RssConfig(rsp));
                     }
+                    Ok(None) => failures.on_success(),
                     Err(err) => {
                         warn!(
                             log, "getting current RSS config failed";
                             "err" => #%err,
                         );
+                        failures.on_failure(format!("{err:#}"));
                     }
                 }
             }
@@ -437,17 +631,22 @@ impl WicketdManager {
         let log = self.log.clone();
         let tx = self.events_tx.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
         tokio::spawn(async move {
-            let client = create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
-            let mut ticker = interval(WICKETD_POLL_INTERVAL * 2);
+            let mut client = ConditionalClient::new(&log, addr, config.timeout);
+            let mut failures = PollFailureTracker::new(
+                WicketdPoller::ArtifactsAndEventReports,
+                tx.clone(),
+            );
+            let mut ticker = interval(config.artifact_interval);
             ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
             loop {
                 ticker.tick().await;
-                // TODO: We should really be using ETAGs here
-                match client.get_artifacts_and_event_reports().await {
-                    Ok(val) => {
-                        // TODO: Only send on changes
-                        let rsp = val.into_inner();
+                let result =
+                    client.client().get_artifacts_and_event_reports().await;
+                match client.check(result) {
+                    Ok(Some(rsp)) => {
+                        failures.on_success();
                         let artifacts = rsp
                             .artifacts
                             .into_iter()
@@ -461,8 +660,10 @@ impl WicketdManager {
                             event_reports,
                         });
                     }
+                    Ok(None) => failures.on_success(),
                     Err(e) => {
                         warn!(log, "{e}");
+                        failures.on_failure(e.to_string());
                     }
                 }
             }
@@ -473,10 +674,16 @@ impl WicketdManager {
         let log = self.log.clone();
         let tx = self.events_tx.clone();
         let addr = self.wicketd_addr;
+        let config = self.config;
 
         tokio::spawn(async move {
-            let client = create_wicketd_client(&log, addr, WICKETD_TIMEOUT);
-            let mut ticker = interval(WICKETD_POLL_INTERVAL);
+            let mut client = ConditionalClient::new(&log, addr, config.timeout);
+            let mut failures =
+                PollFailureTracker::new(WicketdPoller::Inventory, tx.clone());
+            // How stale MGS was at the last inventory we sent, which is still
+            // accurate while wicketd says nothing has changed
+            let mut last_mgs_last_seen = None;
+            let mut ticker = interval(config.inventory_interval);
             ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
             loop {
                 let force_refresh = tokio::select! {
@@ -490,29 +697,43 @@ impl WicketdManager {
                 };
 
                 let params = GetInventoryParams { force_refresh };
-                // TODO: We should really be using ETAGs here
-                match client.get_inventory(&params).await {
-                    Ok(val) => match val.into_inner() {
-                        GetInventoryResponse::Response {
-                            inventory,
-                            mgs_last_seen,
-                        } => {
-                            let _ = tx.send(Event::Inventory {
+                let result = client.client().get_inventory(&params).await;
+                match client.check(result) {
+                    Ok(Some(rsp)) => {
+                        failures.on_success();
+                        match rsp {
+                            GetInventoryResponse::Response {
                                 inventory,
                                 mgs_last_seen,
-                            });
+                            } => {
+                                last_mgs_last_seen = Some(mgs_last_seen);
+                                let _ = tx.send(Event::Inventory {
+                                    inventory,
+                                    mgs_last_seen,
+                                });
+                            }
+                            GetInventoryResponse::Unavailable => {
+                                // Nothing to do here. We keep a running total
+                                // from the last successful response by
+                                // processing ticks in the runner;
+                                last_mgs_last_seen = None;
+                            }
                         }
-                        GetInventoryResponse::Unavailable => {
-                            // Nothing to do here. We keep a running total from
-                            // the last successful response by processing
-                            // ticks in the runner;
+                    }
+                    Ok(None) => {
+                        failures.on_success();
+                        if let Some(mgs_last_seen) = last_mgs_last_seen {
+                            let _ = tx.send(Event::InventoryUnchanged {
+                                mgs_last_seen,
+                            });
                         }
-                    },
+                    }
                     Err(err) => {
                         warn!(
                             log, "Getting inventory from wicketd failed";
                             "err" => %err,
                         );
+                        failures.on_failure(err.to_string());
                     }
                 }
             }
@@ -524,14 +745,101 @@ pub(crate) fn create_wicketd_client(
     log: &Logger,
     wicketd_addr: SocketAddrV6,
     timeout: Duration,
+) -> wicketd_client::Client {
+    create_conditional_wicketd_client(log, wicketd_addr, timeout, None)
+}
+
+/// Like [`create_wicketd_client`], but every request made with the client
+/// sends `If-None-Match: etag`
+fn create_conditional_wicketd_client(
+    log: &Logger,
+    wicketd_addr: SocketAddrV6,
+    timeout: Duration,
+    etag: Option<&HeaderValue>,
 ) -> wicketd_client::Client {
     let endpoint =
         format!("http://[{}]:{}", wicketd_addr.ip(), wicketd_addr.port());
+    let mut headers = HeaderMap::new();
+    if let Some(etag) = etag {
+        headers.insert(IF_NONE_MATCH, etag.clone());
+    }
     let client = reqwest::ClientBuilder::new()
         .connect_timeout(timeout)
         .timeout(timeout)
+        .default_headers(headers)
         .build()
         .unwrap();
 
     wicketd_client::Client::new_with_client(&endpoint, client, log.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::{error::TryRecvError, UnboundedReceiver};
+
+    fn tracker() -> (PollFailureTracker, UnboundedReceiver<Event>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (PollFailureTracker::new(WicketdPoller::Inventory, tx), rx)
+    }
+
+    #[test]
+    fn poll_failures_reported_once_threshold_reached() {
+        let (mut failures, mut rx) = tracker();
+
+        // Failures below the threshold aren't reported
+        for i in 1..POLL_FAILURE_THRESHOLD {
+            failures.on_failure(format!("error {i}"));
+            assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+        }
+
+        // Every failure from the threshold on is reported, with a running
+        // count and the latest error
+        for consecutive in POLL_FAILURE_THRESHOLD..POLL_FAILURE_THRESHOLD + 2 {
+            failures.on_failure(format!("error {consecutive}"));
+            match rx.try_recv() {
+                Ok(Event::WicketdPollFailed {
+                    poller: WicketdPoller::Inventory,
+                    consecutive: reported,
+                    last_error,
+                }) => {
+                    assert_eq!(reported, consecutive);
+                    assert_eq!(last_error, format!("error {consecutive}"));
+                }
+                other => panic!("unexpected event: {other:?}"),
+            }
+        }
+
+        // A success is reported as a recovery, exactly once
+        failures.on_success();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(Event::WicketdPollRecovered(WicketdPoller::Inventory))
+        ));
+        failures.on_success();
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[test]
+    fn poll_success_resets_failure_count() {
+        let (mut failures, mut rx) = tracker();
+
+        // A success below the threshold resets the count without reporting a
+        // recovery, so the next failures start counting from scratch
+        for _ in 1..POLL_FAILURE_THRESHOLD {
+            failures.on_failure(String::from("error"));
+        }
+        failures.on_success();
+        for _ in 1..POLL_FAILURE_THRESHOLD {
+            failures.on_failure(String::from("error"));
+        }
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+
+        failures.on_failure(String::from("error"));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(Event::WicketdPollFailed { consecutive, .. })
+                if consecutive == POLL_FAILURE_THRESHOLD
+        ));
+    }
+}
@@ -4,6 +4,7 @@
 
 use bootstrap_agent_client::types::RackOperationStatus;
 use dropshot::HttpError;
+use dropshot::HttpResponseHeaders;
 use dropshot::HttpResponseOk;
 use dropshot::HttpResponseUpdatedNoContent;
 use dropshot::Path;
@@ -60,7 +61,10 @@ pub trait WicketdApi {
     }]
     async fn get_rss_config(
         rqctx: RequestContext<Self::Context>,
-    ) -> Result<HttpResponseOk<CurrentRssUserConfig>, HttpError>;
+    ) -> Result<
+        HttpResponseHeaders<HttpResponseOk<CurrentRssUserConfig>>,
+        HttpError,
+    >;
 
     /// Update (a subset of) the current RSS configuration.
     ///
@@ -156,7 +160,10 @@ pub trait WicketdApi {
     }]
     async fn get_rack_setup_state(
         rqctx: RequestContext<Self::Context>,
-    ) -> Result<HttpResponseOk<RackOperationStatus>, HttpError>;
+    ) -> Result<
+        HttpResponseHeaders<HttpResponseOk<RackOperationStatus>>,
+        HttpError,
+    >;
 
     /// Run rack setup.
     ///
@@ -194,7 +201,10 @@ pub trait WicketdApi {
     async fn get_inventory(
         rqctx: RequestContext<Self::Context>,
         body_params: TypedBody<GetInventoryParams>,
-    ) -> Result<HttpResponseOk<GetInventoryResponse>, HttpError>;
+    ) -> Result<
+        HttpResponseHeaders<HttpResponseOk<GetInventoryResponse>>,
+        HttpError,
+    >;
 
     /// Upload a TUF repository to the server.
     ///
@@ -219,7 +229,12 @@ pub trait WicketdApi {
     }]
     async fn get_artifacts_and_event_reports(
         rqctx: RequestContext<Self::Context>,
-    ) -> Result<HttpResponseOk<GetArtifactsAndEventReportsResponse>, HttpError>;
+    ) -> Result<
+        HttpResponseHeaders<
+            HttpResponseOk<GetArtifactsAndEventReportsResponse>,
+        >,
+        HttpError,
+    >;
 
     /// Report the configured baseboard details.
     #[endpoint {
@@ -238,7 +253,10 @@ pub trait WicketdApi {
     }]
     async fn get_location(
         rqctx: RequestContext<Self::Context>,
-    ) -> Result<HttpResponseOk<GetLocationResponse>, HttpError>;
+    ) -> Result<
+        HttpResponseHeaders<HttpResponseOk<GetLocationResponse>>,
+        HttpError,
+    >;
 
     /// An endpoint to start updating one or more sleds, switches and PSCs.
     #[endpoint {
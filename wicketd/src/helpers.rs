@@ -6,7 +6,13 @@
 
 use std::fmt;
 
+use dropshot::{HttpError, HttpResponseHeaders, HttpResponseOk, RequestInfo};
+use http::header::{ETAG, IF_NONE_MATCH};
+use http::{HeaderValue, StatusCode};
 use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use wicket_common::inventory::{SpIdentifier, SpType};
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
@@ -39,3 +45,69 @@ pub(crate) fn sps_to_string<S: Into<SpIdentifierDisplay>>(
 ) -> String {
     sps.into_iter().map_into().join(", ")
 }
+
+/// Returns a weak `ETag` for the response to a request that wicket polls
+///
+/// `etag_source` is usually the response body itself.  It differs when part
+/// of the body changes on every request in a way that isn't worth sending a
+/// new response for.
+pub(crate) fn etag_for(
+    etag_source: &impl Serialize,
+) -> Result<String, HttpError> {
+    let serialized = serde_json::to_vec(etag_source).map_err(|error| {
+        HttpError::for_internal_error(format!(
+            "failed to serialize response for ETag: {error}"
+        ))
+    })?;
+    Ok(format!("W/\"{}\"", hex::encode(Sha256::digest(serialized))))
+}
+
+/// Builds the response to a request that wicket polls, tagged with `etag`
+/// (from [`etag_for`])
+///
+/// If the request's `If-None-Match` already names that tag, the caller's copy
+/// is current and this returns a `304 Not Modified` instead, which has no
+/// body.
+pub(crate) fn conditional_response<T>(
+    request: &RequestInfo,
+    body: T,
+    etag: String,
+) -> Result<HttpResponseHeaders<HttpResponseOk<T>>, HttpError>
+where
+    T: JsonSchema + Serialize + Send + Sync + 'static,
+{
+    if if_none_match(request, &etag) {
+        return Err(HttpError {
+            status_code: StatusCode::NOT_MODIFIED,
+            error_code: None,
+            external_message: String::new(),
+            internal_message: format!("{etag} has not changed"),
+        });
+    }
+
+    let mut response = HttpResponseHeaders::new_unnamed(HttpResponseOk(body));
+    response.headers_mut().insert(
+        ETAG,
+        HeaderValue::from_str(&etag).expect("ETag is a valid header value"),
+    );
+    Ok(response)
+}
+
+/// Returns whether any `If-None-Match` header in `request` matches `etag`
+///
+/// This uses the weak comparison that RFC 9110 requires for `If-None-Match`,
+/// so a `W/` prefix on either tag is ignored.
+fn if_none_match(request: &RequestInfo, etag: &str) -> bool {
+    fn opaque(tag: &str) -> &str {
+        tag.strip_prefix("W/").unwrap_or(tag)
+    }
+    let etag = opaque(etag);
+    request
+        .headers()
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || opaque(tag) == etag)
+}
@@ -4,6 +4,8 @@
 
 //! HTTP entrypoint functions for wicketd
 
+use crate::helpers::conditional_response;
+use crate::helpers::etag_for;
 use crate::helpers::sps_to_string;
 use crate::helpers::SpIdentifierDisplay;
 use crate::mgs::GetInventoryError;
@@ -13,6 +15,7 @@ use crate::SmfConfigValues;
 use bootstrap_agent_client::types::RackOperationStatus;
 use dropshot::ApiDescription;
 use dropshot::HttpError;
+use dropshot::HttpResponseHeaders;
 use dropshot::HttpResponseOk;
 use dropshot::HttpResponseUpdatedNoContent;
 use dropshot::Path;
@@ -71,7 +74,10 @@ impl WicketdApi for WicketdApiImpl {
 
     async fn get_rss_config(
         rqctx: RequestContext<Self::Context>,
-    ) -> Result<HttpResponseOk<CurrentRssUserConfig>, HttpError> {
+    ) -> Result<
+        HttpResponseHeaders<HttpResponseOk<CurrentRssUserConfig>>,
+        HttpError,
+    > {
         let ctx = rqctx.context();
 
         // We can't run RSS if we don't have an inventory from MGS yet; we always
@@ -85,7 +91,9 @@ impl WicketdApi for WicketdApiImpl {
             &ctx.log,
         );
 
-        Ok(HttpResponseOk((&*config).into()))
+        let config = CurrentRssUserConfig::from(&*config);
+        let etag = etag_for(&config)?;
+        conditional_response(&rqctx.request, config, etag)
     }
 
     async fn put_rss_config(
@@ -198,7 +206,10 @@ impl WicketdApi for WicketdApiImpl {
 
     async fn get_rack_setup_state(
         rqctx: RequestContext<ServerContext>,
-    ) -> Result<HttpResponseOk<RackOperationStatus>, HttpError> {
+    ) -> Result<
+        HttpResponseHeaders<HttpResponseOk<RackOperationStatus>>,
+        HttpError,
+    > {
         let ctx = rqctx.context();
 
         let sled_agent_addr = ctx.bootstrap_agent_addr().map_err(|err| {
@@ -234,7 +245,8 @@ impl WicketdApi for WicketdApiImpl {
             })?
             .into_inner();
 
-        Ok(HttpResponseOk(op_status))
+        let etag = etag_for(&op_status)?;
+        conditional_response(&rqctx.request, op_status, etag)
     }
 
     async fn post_run_rack_setup(
@@ -340,7 +352,10 @@ impl WicketdApi for WicketdApiImpl {
     async fn get_inventory(
         rqctx: RequestContext<ServerContext>,
         body_params: TypedBody<GetInventoryParams>,
-    ) -> Result<HttpResponseOk<GetInventoryResponse>, HttpError> {
+    ) -> Result<
+        HttpResponseHeaders<HttpResponseOk<GetInventoryResponse>>,
+        HttpError,
+    > {
         let GetInventoryParams { force_refresh } = body_params.into_inner();
         match rqctx
             .context()
@@ -348,7 +363,19 @@ impl WicketdApi for WicketdApiImpl {
             .get_inventory_refreshing_sps(force_refresh)
             .await
         {
-            Ok(response) => Ok(HttpResponseOk(response)),
+            Ok(response) => {
+                // `mgs_last_seen` is different on every request, but wicket
+                // only shows it to the second, so leave out the rest.
+                let etag_source = match &response {
+                    GetInventoryResponse::Response {
+                        inventory,
+                        mgs_last_seen,
+                    } => Some((inventory, mgs_last_seen.as_secs())),
+                    GetInventoryResponse::Unavailable => None,
+                };
+                let etag = etag_for(&etag_source)?;
+                conditional_response(&rqctx.request, response, etag)
+            }
             Err(GetInventoryError::InvalidSpIdentifier) => {
                 Err(HttpError::for_unavail(
                     None,
@@ -374,11 +401,16 @@ impl WicketdApi for WicketdApiImpl {
 
     async fn get_artifacts_and_event_reports(
         rqctx: RequestContext<ServerContext>,
-    ) -> Result<HttpResponseOk<GetArtifactsAndEventReportsResponse>, HttpError>
-    {
+    ) -> Result<
+        HttpResponseHeaders<
+            HttpResponseOk<GetArtifactsAndEventReportsResponse>,
+        >,
+        HttpError,
+    > {
         let response =
             rqctx.context().update_tracker.artifacts_and_event_reports().await;
-        Ok(HttpResponseOk(response))
+        let etag = etag_for(&response)?;
+        conditional_response(&rqctx.request, response, etag)
     }
 
     async fn get_baseboard(
@@ -392,7 +424,11 @@ impl WicketdApi for WicketdApiImpl {
 
     async fn get_location(
         rqctx: RequestContext<ServerContext>,
-    ) -> Result<HttpResponseOk<GetLocationResponse>, HttpError> {
+    ) -> Result<
+        HttpResponseHeaders<HttpResponseOk<GetLocationResponse>>,
+        HttpError,
+    > {
+        let request = &rqctx.request;
         let rqctx = rqctx.context();
         let inventory = inventory_or_unavail(&rqctx.mgs_handle).await?;
 
@@ -425,12 +461,14 @@ impl WicketdApi for WicketdApiImpl {
             }
         }
 
-        Ok(HttpResponseOk(GetLocationResponse {
+        let location = GetLocationResponse {
             sled_id,
             sled_baseboard,
             switch_baseboard,
             switch_id,
-        }))
+        };
+        let etag = etag_for(&location)?;
+        conditional_response(request, location, etag)
     }
 
     async fn post_start_update(
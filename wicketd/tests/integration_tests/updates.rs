@@ -17,6 +17,8 @@ use omicron_common::{
     api::internal::nexus::KnownArtifactKind,
     update::{ArtifactHashId, ArtifactKind},
 };
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
 use tokio::sync::oneshot;
 use update_engine::NestedError;
 use uuid::Uuid;
@@ -260,6 +262,48 @@ async fn test_updates() {
     wicketd_testctx.teardown().await;
 }
 
+#[tokio::test]
+async fn test_artifacts_etag() {
+    let gateway =
+        gateway_setup::test_setup("test_artifacts_etag", SpPort::One).await;
+    let wicketd_testctx = WicketdTestContext::setup(gateway).await;
+    let client = &wicketd_testctx.wicketd_client;
+
+    let response = client
+        .get_artifacts_and_event_reports()
+        .await
+        .expect("get_artifacts_and_event_reports succeeded");
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .expect("artifacts response has an ETag")
+        .clone();
+
+    // Nothing has changed, so a request naming that tag gets no body back.
+    let url = format!("{}/artifacts-and-event-reports", client.baseurl());
+    let response = client
+        .client()
+        .get(&url)
+        .header(IF_NONE_MATCH, etag.clone())
+        .send()
+        .await
+        .expect("conditional request sent");
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // A request naming some other tag gets the full response again.
+    let response = client
+        .client()
+        .get(&url)
+        .header(IF_NONE_MATCH, "W/\"stale\"")
+        .send()
+        .await
+        .expect("conditional request sent");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get(ETAG), Some(&etag));
+
+    wicketd_testctx.teardown().await;
+}
+
 // See documentation for extract_nested_artifact_pair in update_plan.rs for why
 // multi_thread is required.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]